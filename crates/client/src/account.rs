@@ -0,0 +1,40 @@
+use std::sync::{Arc, Mutex};
+
+use nomi_core::auth::{microsoft::DeviceCode, MicrosoftAccount, MicrosoftAuth};
+
+use crate::errors_pool::ErrorPoolExt;
+
+#[derive(Default)]
+pub struct LoginState {
+    /// Code shown to the user while the login is running.
+    pub device_code: Arc<Mutex<Option<DeviceCode>>>,
+    /// Account refreshed before a launch, not saved to the settings yet.
+    pub refreshed_account: Arc<Mutex<Option<MicrosoftAccount>>>,
+}
+
+impl LoginState {
+    pub fn take_refreshed_account(&self) -> Option<MicrosoftAccount> {
+        self.refreshed_account.lock().unwrap().take()
+    }
+}
+
+pub async fn task_login(
+    client_id: String,
+    device_code: Arc<Mutex<Option<DeviceCode>>>,
+) -> Option<MicrosoftAccount> {
+    let account = try_login(client_id, &device_code).await.report_error();
+    *device_code.lock().unwrap() = None;
+    account
+}
+
+async fn try_login(
+    client_id: String,
+    device_code: &Mutex<Option<DeviceCode>>,
+) -> anyhow::Result<MicrosoftAccount> {
+    let auth = MicrosoftAuth::new(client_id);
+
+    let code = auth.request_device_code().await?;
+    *device_code.lock().unwrap() = Some(code.clone());
+
+    auth.login(&code).await
+}
@@ -1,9 +1,16 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use egui_task_manager::*;
-use nomi_core::{configs::profile::VersionProfile, repository::fabric_meta::FabricVersions};
+use nomi_core::{
+    auth::MicrosoftAccount, configs::profile::VersionProfile, instance::session_logs::SessionLog,
+    repository::fabric_meta::FabricVersions,
+};
 
-use crate::{components::ProfilesConfig, errors_pool::ErrorPoolExt};
+use crate::{
+    components::{settings::SettingsState, ProfilesConfig},
+    errors_pool::ErrorPoolExt,
+    worlds::WorldEntry,
+};
 
 pub struct FabricDataCollection;
 
@@ -96,6 +103,40 @@ impl<'c> TasksCollection<'c> for GameDownloadingCollection {
     }
 }
 
+pub struct GameDirMigrationCollection;
+
+impl<'c> TasksCollection<'c> for GameDirMigrationCollection {
+    type Context = &'c mut ProfilesConfig;
+
+    type Target = Option<VersionProfile>;
+
+    type Executor = executors::Linear;
+
+    fn name() -> &'static str {
+        "Game directories migration collection"
+    }
+
+    fn handle(context: Self::Context) -> Handler<'c, Self::Target> {
+        Handler::new(|opt: Option<VersionProfile>| {
+            let Some(profile) = opt else {
+                return;
+            };
+
+            // The profile could have been deleted while its files were copied
+            let Some(prof) = context
+                .profiles
+                .iter_mut()
+                .find(|prof| prof.id == profile.id)
+            else {
+                return;
+            };
+
+            *prof = Arc::new(profile);
+            context.update_config().report_error();
+        })
+    }
+}
+
 pub struct GameDeletionCollection;
 
 impl<'c> TasksCollection<'c> for GameDeletionCollection {
@@ -113,3 +154,90 @@ impl<'c> TasksCollection<'c> for GameDeletionCollection {
         Handler::new(|()| ())
     }
 }
+
+pub struct WorldsCollection;
+
+impl<'c> TasksCollection<'c> for WorldsCollection {
+    type Context = &'c mut HashMap<usize, Vec<WorldEntry>>;
+
+    type Target = Option<(usize, Vec<WorldEntry>)>;
+
+    type Executor = executors::Linear;
+
+    fn name() -> &'static str {
+        "Worlds collection"
+    }
+
+    fn handle(context: Self::Context) -> Handler<'c, Self::Target> {
+        Handler::new(|opt: Option<(usize, Vec<WorldEntry>)>| {
+            if let Some((profile_id, worlds)) = opt {
+                context.insert(profile_id, worlds);
+            }
+        })
+    }
+}
+
+pub struct SessionLogsCollection;
+
+impl<'c> TasksCollection<'c> for SessionLogsCollection {
+    type Context = &'c mut HashMap<usize, Vec<SessionLog>>;
+
+    type Target = Option<(usize, Vec<SessionLog>)>;
+
+    type Executor = executors::Linear;
+
+    fn name() -> &'static str {
+        "Session logs collection"
+    }
+
+    fn handle(context: Self::Context) -> Handler<'c, Self::Target> {
+        Handler::new(|opt: Option<(usize, Vec<SessionLog>)>| {
+            if let Some((profile_id, logs)) = opt {
+                context.insert(profile_id, logs);
+            }
+        })
+    }
+}
+
+pub struct OptionsCollection;
+
+impl<'c> TasksCollection<'c> for OptionsCollection {
+    type Context = ();
+
+    type Target = ();
+
+    type Executor = executors::Linear;
+
+    fn name() -> &'static str {
+        "Options collection"
+    }
+
+    fn handle(_context: Self::Context) -> Handler<'c, Self::Target> {
+        Handler::new(|()| ())
+    }
+}
+
+pub struct LoginCollection;
+
+impl<'c> TasksCollection<'c> for LoginCollection {
+    type Context = &'c mut SettingsState;
+
+    type Target = Option<MicrosoftAccount>;
+
+    type Executor = executors::Linear;
+
+    fn name() -> &'static str {
+        "Login collection"
+    }
+
+    fn handle(context: Self::Context) -> Handler<'c, Self::Target> {
+        Handler::new(|account: Option<MicrosoftAccount>| {
+            if let Some(account) = account {
+                context.username.clone_from(&account.username);
+                context.uuid.clone_from(&account.uuid);
+                context.account = Some(account);
+                context.update_config();
+            }
+        })
+    }
+}
@@ -0,0 +1,165 @@
+use std::{
+    collections::{HashSet, VecDeque},
+    sync::{Arc, Mutex},
+};
+
+use eframe::egui::{self, Color32, RichText, ScrollArea};
+use nomi_core::instance::logs::{LogLevel, LogRecord};
+
+use super::Component;
+
+/// Number of records kept in memory. Older ones are dropped.
+const MAX_RECORDS: usize = 10_000;
+
+#[derive(Clone)]
+pub struct GameLogsState {
+    records: Arc<Mutex<VecDeque<LogRecord>>>,
+    pub hidden_levels: HashSet<LogLevel>,
+}
+
+impl Default for GameLogsState {
+    fn default() -> Self {
+        Self {
+            records: Arc::default(),
+            hidden_levels: HashSet::from([LogLevel::Trace, LogLevel::Debug]),
+        }
+    }
+}
+
+impl GameLogsState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a shared handle that can be used to push records from another thread.
+    pub fn records(&self) -> Arc<Mutex<VecDeque<LogRecord>>> {
+        self.records.clone()
+    }
+
+    pub fn push(records: &Mutex<VecDeque<LogRecord>>, record: LogRecord) {
+        let mut records = records.lock().unwrap();
+
+        if records.len() >= MAX_RECORDS {
+            records.pop_front();
+        }
+
+        records.push_back(record);
+    }
+
+    /// Shows the given records instead of the current ones, e.g. the log of a past session.
+    pub fn replace(records: &Mutex<VecDeque<LogRecord>>, mut new_records: Vec<LogRecord>) {
+        let excess = new_records.len().saturating_sub(MAX_RECORDS);
+        new_records.drain(..excess);
+
+        *records.lock().unwrap() = new_records.into();
+    }
+}
+
+pub struct GameLogs<'a> {
+    pub game_logs_state: &'a mut GameLogsState,
+}
+
+impl Component for GameLogs<'_> {
+    fn ui(self, ui: &mut egui::Ui) {
+        let state = self.game_logs_state;
+
+        ui.horizontal(|ui| {
+            for level in LogLevel::ALL {
+                let mut shown = !state.hidden_levels.contains(&level);
+                if ui.checkbox(&mut shown, level.as_str()).changed() {
+                    if shown {
+                        state.hidden_levels.remove(&level);
+                    } else {
+                        state.hidden_levels.insert(level);
+                    }
+                }
+            }
+
+            if ui.button("Clear").clicked() {
+                state.records.lock().unwrap().clear();
+            }
+        });
+
+        ui.separator();
+
+        let records = state.records.lock().unwrap();
+        let shown = records
+            .iter()
+            .enumerate()
+            .filter(|(_, record)| {
+                !record
+                    .level
+                    .is_some_and(|level| state.hidden_levels.contains(&level))
+            })
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+
+        // Every record takes a single row so only the visible ones are laid out
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+
+        ScrollArea::both()
+            .id_source("game_logs")
+            .stick_to_bottom(true)
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, shown.len(), |ui, rows| {
+                for index in &shown[rows] {
+                    record_ui(ui, &records[*index]);
+                }
+            });
+    }
+}
+
+fn record_ui(ui: &mut egui::Ui, record: &LogRecord) {
+    let response = ui.horizontal(|ui| {
+        if let Some(timestamp) = record.timestamp {
+            ui.label(RichText::new(timestamp.to_string()).monospace().weak());
+        }
+
+        if let Some(level) = record.level {
+            ui.label(
+                RichText::new(level.as_str())
+                    .monospace()
+                    .color(level_color(level)),
+            );
+        }
+
+        if let Some(thread) = &record.thread {
+            ui.label(RichText::new(format!("[{thread}]")).monospace().weak());
+        }
+
+        let first_line = record.message.lines().next().unwrap_or_default();
+        ui.label(RichText::new(first_line).monospace());
+
+        if let Some(throwable) = record.throwable.as_deref().and_then(|t| t.lines().next()) {
+            ui.label(
+                RichText::new(throwable)
+                    .monospace()
+                    .color(level_color(LogLevel::Error)),
+            );
+        }
+    });
+
+    // The rest of multi-line records is shown on hover
+    let details = match &record.throwable {
+        Some(throwable) => Some(format!("{}\n{throwable}", record.message)),
+        None => record
+            .message
+            .contains('\n')
+            .then(|| record.message.clone()),
+    };
+
+    if let Some(details) = details {
+        response
+            .response
+            .on_hover_text(RichText::new(details).monospace());
+    }
+}
+
+fn level_color(level: LogLevel) -> Color32 {
+    match level {
+        LogLevel::Trace | LogLevel::Debug => Color32::GRAY,
+        LogLevel::Info => Color32::LIGHT_GREEN,
+        LogLevel::Warn => Color32::YELLOW,
+        LogLevel::Error | LogLevel::Fatal => Color32::LIGHT_RED,
+    }
+}
@@ -1,34 +1,68 @@
-use std::{collections::HashSet, path::PathBuf, sync::Arc};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
 
 use eframe::egui::{self, popup_below_widget, Align2, Id, PopupCloseBehavior, TextWrapMode, Ui};
 use egui_extras::{Column, TableBuilder};
 use egui_task_manager::{Caller, Task, TaskManager};
 use nomi_core::{
+    auth::MicrosoftAuth,
     configs::profile::{ProfileState, VersionProfile},
     fs::write_toml_config_sync,
-    instance::launch::arguments::UserData,
+    game_paths::{is_shared_game_dir, remove_instance_dir, shared_folders_dir, MINECRAFT_DIR},
+    instance::{
+        crash::{
+            analyzer::{read_log_tail, CrashAnalyzer, LOG_TAIL_LINES},
+            Crash,
+        },
+        history::HistoryStore,
+        launch::{arguments::UserData, memory::MemoryMode, quick_play::QuickPlay, LaunchInstance},
+        logs::{read_log_file, LogParser},
+        resources::{ResourceSample, MAX_SAMPLES, SAMPLE_INTERVAL},
+        session_logs::SessionLog,
+        shared_folders::{unlink_shared_folder, SharedFolder},
+        supervisor::{GameHandle, StopRequest, Supervisor},
+    },
     repository::{launcher_manifest::LauncherManifest, username::Username},
     DOT_NOMI_PROFILES_CONFIG,
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    collections::{AssetsCollection, GameDeletionCollection, GameDownloadingCollection},
-    download::{task_assets, task_download_version},
+    account::LoginState,
+    collections::{
+        AssetsCollection, GameDeletionCollection, GameDirMigrationCollection,
+        GameDownloadingCollection, OptionsCollection, SessionLogsCollection, WorldsCollection,
+    },
+    download::{task_assets, task_download_version, task_migrate_game_dir},
     errors_pool::ErrorPoolExt,
+    options::{task_copy_options, task_save_default_options},
+    session_logs::task_list_session_logs,
     utils::spawn_tokio_future,
+    worlds::{
+        remove_backups, task_list_worlds, task_world_operation, WorldEntry, WorldOperation,
+        EXPORTS_DIR,
+    },
 };
 
 use super::{
     add_profile_menu::{AddProfileMenu, AddProfileMenuState},
-    settings::SettingsState,
+    game_logs::GameLogsState,
+    settings::{launch_hooks_ui, window_settings_ui, SettingsState},
     Component,
 };
 
 pub struct ProfilesPage<'a> {
     pub is_allowed_to_take_action: bool,
     pub manager: &'a mut TaskManager,
+    pub supervisor: &'a Supervisor,
     pub settings_state: &'a SettingsState,
+    pub game_logs_state: &'a GameLogsState,
+    pub login_state: &'a LoginState,
 
     pub is_profile_window_open: &'a mut bool,
 
@@ -41,6 +75,38 @@ pub struct ProfilesPage<'a> {
 pub struct ProfilesState {
     pub currently_downloading_profiles: HashSet<usize>,
     pub profiles: ProfilesConfig,
+    pub is_game_dirs_migrated: bool,
+    /// Shows the most recently played profiles first.
+    pub sort_by_last_played: bool,
+    /// Worlds of the profiles, loaded when the worlds menu is opened.
+    pub worlds: HashMap<usize, Vec<WorldEntry>>,
+    /// Session logs of the profiles, loaded when the logs menu is opened.
+    pub session_logs: HashMap<usize, Vec<SessionLog>>,
+}
+
+impl ProfilesState {
+    /// Gives every profile that still uses the shared game directory its own one.
+    pub fn migrate_game_dirs(&mut self, manager: &mut TaskManager) {
+        self.is_game_dirs_migrated = true;
+
+        for profile in &self.profiles.profiles {
+            let ProfileState::Downloaded(instance) = &profile.state else {
+                continue;
+            };
+
+            if !is_shared_game_dir(&instance.settings.game_dir, Path::new(MINECRAFT_DIR)) {
+                continue;
+            }
+
+            let profile = profile.clone();
+            let task = Task::new(
+                format!("Moving {} into its own directory", profile.name),
+                Caller::standard(task_migrate_game_dir(profile)),
+            );
+
+            manager.push_task::<GameDirMigrationCollection>(task);
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Default)]
@@ -92,7 +158,7 @@ impl Component for ProfilesPage<'_> {
 
         TableBuilder::new(ui)
             .column(Column::auto().at_least(120.0).at_most(240.0))
-            .columns(Column::auto(), 4)
+            .columns(Column::auto(), 12)
             .header(20.0, |mut header| {
                 header.col(|ui| {
                     ui.label("Name");
@@ -103,11 +169,44 @@ impl Component for ProfilesPage<'_> {
                 header.col(|ui| {
                     ui.label("Loader");
                 });
+                header.col(|ui| {
+                    let sort = &mut self.profiles_state.sort_by_last_played;
+                    if ui
+                        .selectable_label(*sort, "Played")
+                        .on_hover_text("Sort by last played")
+                        .clicked()
+                    {
+                        *sort = !*sort;
+                    }
+                });
             })
             .body(|mut body| {
                 let mut is_deleting = vec![];
+                let mut window_changes = vec![];
+                let mut memory_changes = vec![];
+                let mut hooks_changes = vec![];
+                let mut folders_changes = vec![];
+
+                let launcher = Launcher {
+                    supervisor: self.supervisor,
+                    settings_state: self.settings_state,
+                    game_logs_state: self.game_logs_state,
+                    login_state: self.login_state,
+                };
+
+                let history = self
+                    .supervisor
+                    .history()
+                    .map(HistoryStore::snapshot)
+                    .unwrap_or_default();
+
+                let profiles = &self.profiles_state.profiles.profiles;
+                let mut order = (0..profiles.len()).collect::<Vec<_>>();
+                if self.profiles_state.sort_by_last_played {
+                    order.sort_by_key(|&index| Reverse(history.last_played(profiles[index].id)));
+                }
 
-                for (index, profile) in self.profiles_state.profiles.profiles.iter().enumerate() {
+                for (index, profile) in order.into_iter().map(|index| (index, &profiles[index])) {
                     body.row(30.0, |mut row| {
                         row.col(|ui| {
                             ui.add(egui::Label::new(&profile.name).truncate());
@@ -118,36 +217,76 @@ impl Component for ProfilesPage<'_> {
                         row.col(|ui| {
                             ui.label(profile.loader_name());
                         });
+                        row.col(|ui| {
+                            let label = ui.label(format_playtime(history.playtime(profile.id)));
+                            if let Some(last_played) = history.last_played(profile.id) {
+                                label.on_hover_text(format!("Last played {}", format_ago(last_played)));
+                            }
+                        });
                         row.col(|ui| match &profile.state {
-                            ProfileState::Downloaded(instance) => {
-                                if ui
-                                    .add_enabled(
-                                        self.is_allowed_to_take_action,
-                                        egui::Button::new("Launch"),
-                                    )
-                                    .clicked()
-                                {
-                                    let instance = instance.clone();
-                                    let (tx, _rx) = tokio::sync::mpsc::channel(100);
+                            ProfileState::Downloaded(_) if self.supervisor.is_running(profile.id) => {
+                                ui.horizontal(|ui| {
+                                    if ui
+                                        .button("Stop")
+                                        .on_hover_text("Ask the game to exit. It is killed if it does not exit in time")
+                                        .clicked()
+                                    {
+                                        self.supervisor
+                                            .stop(profile.id, StopRequest::Graceful)
+                                            .report_error();
+                                    }
+
+                                    if ui.button("Kill").clicked() {
+                                        self.supervisor
+                                            .stop(profile.id, StopRequest::Forced)
+                                            .report_error();
+                                    }
+
+                                    if let Some(game) = self.supervisor.get(profile.id) {
+                                        resource_usage_ui(ui, &game);
+                                    }
+                                });
 
-                                    let user_data = UserData {
-                                        username: Username::new(
-                                            self.settings_state.username.clone(),
+                                ui.ctx().request_repaint_after(SAMPLE_INTERVAL);
+                            }
+                            ProfileState::Downloaded(instance) => {
+                                ui.horizontal(|ui| {
+                                    if ui
+                                        .add_enabled(
+                                            self.is_allowed_to_take_action,
+                                            egui::Button::new("Launch"),
                                         )
-                                        .unwrap(),
-                                        uuid: Some(self.settings_state.uuid.clone()),
-                                        access_token: None,
-                                    };
+                                        .clicked()
+                                    {
+                                        launcher.launch(profile, instance, None);
+                                    }
+
+                                    let popup_id = ui.make_persistent_id("join_server_popup_id");
+                                    let button = ui
+                                        .add_enabled(self.is_allowed_to_take_action, egui::Button::new("Join server"))
+                                        .on_hover_text("Launch the game and connect to a server right away");
+
+                                    if button.clicked() {
+                                        ui.memory_mut(|mem| mem.toggle_popup(popup_id));
+                                    }
+
+                                    popup_below_widget(ui, popup_id, &button, PopupCloseBehavior::CloseOnClickOutside, |ui| {
+                                        ui.set_min_width(200.0);
+
+                                        let address_id = Id::new("join_server_address").with(profile.id);
+                                        let mut address = ui.data_mut(|map| map.get_temp_mut_or_default::<String>(address_id).clone());
 
-                                    let java_runner = self.settings_state.java.clone();
+                                        ui.label("Server address");
+                                        ui.text_edit_singleline(&mut address);
 
-                                    spawn_tokio_future(tx, async move {
-                                        instance
-                                            .launch(user_data, &java_runner)
-                                            .await
-                                            .report_error()
+                                        if ui.add_enabled(!address.trim().is_empty(), egui::Button::new("Join")).clicked() {
+                                            launcher.launch(profile, instance, Some(QuickPlay::Multiplayer(address.trim().to_owned())));
+                                            ui.memory_mut(|mem| mem.close_popup());
+                                        }
+
+                                        ui.data_mut(|map| map.insert_temp(address_id, address));
                                     });
-                                }
+                                });
                             }
                             ProfileState::NotDownloaded { .. } => {
                                 if ui
@@ -176,6 +315,333 @@ impl Component for ProfilesPage<'_> {
                             }
                         });
 
+                        row.col(|ui| {
+                            if let ProfileState::Downloaded(instance) = &profile.state {
+                                let popup_id = ui.make_persistent_id("window_popup_id");
+                                let button = ui.button("Window").on_hover_text("Window settings of this profile");
+
+                                if button.clicked() {
+                                    ui.memory_mut(|mem| mem.toggle_popup(popup_id));
+                                }
+
+                                popup_below_widget(ui, popup_id, &button, PopupCloseBehavior::CloseOnClickOutside, |ui| {
+                                    ui.set_min_width(150.0);
+
+                                    let mut window = instance.settings.window;
+
+                                    let mut use_global = window.is_none();
+                                    if ui.checkbox(&mut use_global, "Use global settings").changed() {
+                                        window = (!use_global).then_some(self.settings_state.window);
+                                    }
+
+                                    if let Some(window) = &mut window {
+                                        window_settings_ui(ui, window);
+                                    }
+
+                                    if window != instance.settings.window {
+                                        window_changes.push((index, window));
+                                    }
+                                });
+                            }
+                        });
+
+                        row.col(|ui| {
+                            if let ProfileState::Downloaded(instance) = &profile.state {
+                                let popup_id = ui.make_persistent_id("memory_popup_id");
+                                let button = ui.button("Memory").on_hover_text("Heap size of the game");
+
+                                if button.clicked() {
+                                    ui.memory_mut(|mem| mem.toggle_popup(popup_id));
+                                }
+
+                                popup_below_widget(ui, popup_id, &button, PopupCloseBehavior::CloseOnClickOutside, |ui| {
+                                    ui.set_min_width(250.0);
+
+                                    let mut memory = instance.settings.memory;
+
+                                    ui.radio_value(&mut memory, MemoryMode::Manual, "Manual")
+                                        .on_hover_text("Use the heap size of the custom JVM arguments");
+                                    ui.radio_value(&mut memory, MemoryMode::Auto, "Automatic")
+                                        .on_hover_text("Pick the heap size at launch based on the system memory, the loader and the installed mods");
+
+                                    if memory != instance.settings.memory {
+                                        memory_changes.push((index, memory));
+                                    }
+                                });
+                            }
+                        });
+
+                        row.col(|ui| {
+                            if let ProfileState::Downloaded(instance) = &profile.state {
+                                let popup_id = ui.make_persistent_id("hooks_popup_id");
+                                let button = ui.button("Hooks").on_hover_text("Wrapper command, environment variables and commands run around the game");
+
+                                if button.clicked() {
+                                    ui.memory_mut(|mem| mem.toggle_popup(popup_id));
+                                }
+
+                                popup_below_widget(ui, popup_id, &button, PopupCloseBehavior::CloseOnClickOutside, |ui| {
+                                    ui.set_min_width(300.0);
+
+                                    ui.label("Empty fields fall back to the global settings, environment variables are merged with the global ones.");
+
+                                    let mut hooks = instance.settings.hooks.clone();
+                                    launch_hooks_ui(ui, profile.id, &mut hooks);
+
+                                    if hooks != instance.settings.hooks {
+                                        hooks_changes.push((index, hooks));
+                                    }
+                                });
+                            }
+                        });
+
+                        row.col(|ui| {
+                            if let ProfileState::Downloaded(instance) = &profile.state {
+                                let popup_id = ui.make_persistent_id("folders_popup_id");
+                                let button = ui.button("Folders").on_hover_text("Folders shared with the other profiles");
+
+                                if button.clicked() {
+                                    ui.memory_mut(|mem| mem.toggle_popup(popup_id));
+                                }
+
+                                popup_below_widget(ui, popup_id, &button, PopupCloseBehavior::CloseOnClickOutside, |ui| {
+                                    ui.set_min_width(200.0);
+
+                                    ui.label("Shared folders are linked at launch. Existing files are moved into the shared folder.");
+                                    ui.label("Unsharing a folder gives the profile an empty folder, the files stay in the shared one.");
+
+                                    let mut folders = instance.settings.shared_folders.clone();
+                                    for folder in SharedFolder::ALL {
+                                        let mut is_shared = folders.contains(&folder);
+                                        if ui.checkbox(&mut is_shared, folder.to_string()).changed() {
+                                            if is_shared {
+                                                folders.insert(folder);
+                                            } else {
+                                                folders.remove(&folder);
+                                            }
+                                        }
+                                    }
+
+                                    if folders != instance.settings.shared_folders {
+                                        folders_changes.push((index, folders));
+                                    }
+                                });
+                            }
+                        });
+
+                        row.col(|ui| {
+                            if let ProfileState::Downloaded(instance) = &profile.state {
+                                let popup_id = ui.make_persistent_id("worlds_popup_id");
+                                let button = ui.button("Worlds").on_hover_text("Back up, restore, import and export the worlds of this profile");
+
+                                if button.clicked() {
+                                    ui.memory_mut(|mem| mem.toggle_popup(popup_id));
+
+                                    let task = Task::new(format!("Looking for the worlds of {}", profile.name), Caller::standard(task_list_worlds(profile.id, instance.settings.game_dir.clone())));
+                                    self.manager.push_task::<WorldsCollection>(task);
+                                }
+
+                                popup_below_widget(ui, popup_id, &button, PopupCloseBehavior::CloseOnClickOutside, |ui| {
+                                    ui.set_min_width(300.0);
+
+                                    let is_busy = !self.manager.get_collection::<WorldsCollection>().tasks().is_empty();
+                                    let is_running = self.supervisor.is_running(profile.id);
+                                    let mut operation = None;
+
+                                    match self.profiles_state.worlds.get(&profile.id) {
+                                        None => {
+                                            ui.spinner();
+                                        }
+                                        Some(worlds) if worlds.is_empty() => {
+                                            ui.label("This profile has no worlds yet");
+                                        }
+                                        Some(worlds) => {
+                                            for WorldEntry { world, latest_backup } in worlds {
+                                                ui.separator();
+                                                ui.label(egui::RichText::new(&world.info.name).strong());
+                                                ui.label(format!(
+                                                    "{}, {}",
+                                                    world.info.game_version.as_deref().unwrap_or("unknown version"),
+                                                    world.info.last_played.map_or("never played".to_owned(), |time| format!("last played {}", format_ago(time)))
+                                                ));
+
+                                                ui.add_enabled_ui(!is_busy, |ui| {
+                                                    ui.horizontal(|ui| {
+                                                        if ui.button("Back up").clicked() {
+                                                            operation = Some(WorldOperation::Backup { world_dir: world.dir.clone() });
+                                                        }
+
+                                                        if ui
+                                                            .add_enabled(!is_running && latest_backup.is_some(), egui::Button::new("Restore"))
+                                                            .on_hover_text("Replace the world with its latest backup. The current state is backed up first")
+                                                            .clicked()
+                                                        {
+                                                            if let Some(backup) = latest_backup {
+                                                                operation = Some(WorldOperation::Restore { backup: backup.path.clone(), world_dir: world.dir.clone() });
+                                                            }
+                                                        }
+
+                                                        if ui.button("Export").on_hover_text(format!("Save the world as a zip file to {EXPORTS_DIR}")).clicked() {
+                                                            operation = Some(WorldOperation::Export { world_dir: world.dir.clone() });
+                                                        }
+                                                    });
+                                                });
+                                            }
+                                        }
+                                    }
+
+                                    ui.separator();
+
+                                    let archive_id = Id::new("import_world_archive").with(profile.id);
+                                    let mut archive = ui.data_mut(|map| map.get_temp_mut_or_default::<String>(archive_id).clone());
+
+                                    ui.label("Import a world from a zip file");
+                                    ui.horizontal(|ui| {
+                                        ui.text_edit_singleline(&mut archive);
+
+                                        if ui.add_enabled(!is_busy && !archive.trim().is_empty(), egui::Button::new("Import")).clicked() {
+                                            operation = Some(WorldOperation::Import { archive: PathBuf::from(archive.trim()) });
+                                            archive.clear();
+                                        }
+                                    });
+
+                                    ui.data_mut(|map| map.insert_temp(archive_id, archive));
+
+                                    if let Some(operation) = operation {
+                                        let profile_id = profile.id;
+                                        let game_dir = instance.settings.game_dir.clone();
+                                        let task = Task::new(operation.describe(), Caller::progressing(move |progress| task_world_operation(profile_id, game_dir, operation, progress)));
+                                        self.manager.push_task::<WorldsCollection>(task);
+                                    }
+                                });
+                            }
+                        });
+
+                        row.col(|ui| {
+                            if let ProfileState::Downloaded(instance) = &profile.state {
+                                let popup_id = ui.make_persistent_id("options_popup_id");
+                                let button = ui.button("Options").on_hover_text("Copy the game options, e.g. key bindings, between profiles");
+
+                                if button.clicked() {
+                                    ui.memory_mut(|mem| mem.toggle_popup(popup_id));
+                                }
+
+                                popup_below_widget(ui, popup_id, &button, PopupCloseBehavior::CloseOnClickOutside, |ui| {
+                                    ui.set_min_width(250.0);
+
+                                    let is_busy = !self.manager.get_collection::<OptionsCollection>().tasks().is_empty();
+
+                                    if ui
+                                        .add_enabled(!is_busy, egui::Button::new("Use as defaults"))
+                                        .on_hover_text("New profiles will start with the options of this profile")
+                                        .clicked()
+                                    {
+                                        let task = Task::new(
+                                            format!("Saving the options of {} as defaults", profile.name),
+                                            Caller::standard(task_save_default_options(instance.settings.game_dir.clone(), instance.settings.version.clone())),
+                                        );
+                                        self.manager.push_task::<OptionsCollection>(task);
+                                    }
+
+                                    ui.separator();
+
+                                    let source_id = Id::new("copy_options_source").with(profile.id);
+                                    let mut source = ui.data_mut(|map| map.get_temp::<usize>(source_id));
+
+                                    let sources = profiles
+                                        .iter()
+                                        .filter(|other| other.id != profile.id)
+                                        .filter_map(|other| match &other.state {
+                                            ProfileState::Downloaded(other_instance) => Some((other, other_instance)),
+                                            ProfileState::NotDownloaded { .. } => None,
+                                        })
+                                        .collect::<Vec<_>>();
+
+                                    ui.label("Copy the options of another profile. Key bindings are converted between versions");
+                                    egui::ComboBox::from_id_source(source_id)
+                                        .selected_text(sources.iter().find(|(other, _)| Some(other.id) == source).map_or("Select a profile", |(other, _)| other.name.as_str()))
+                                        .show_ui(ui, |ui| {
+                                            for (other, _) in &sources {
+                                                ui.selectable_value(&mut source, Some(other.id), format!("{} ({})", other.name, other.version()));
+                                            }
+                                        });
+
+                                    let selected = sources.iter().find(|(other, _)| Some(other.id) == source);
+                                    let is_running = self.supervisor.is_running(profile.id);
+
+                                    if ui
+                                        .add_enabled(!is_busy && !is_running && selected.is_some(), egui::Button::new("Copy"))
+                                        .on_hover_text("Options missing from the other profile are kept")
+                                        .clicked()
+                                    {
+                                        if let Some((other, other_instance)) = selected {
+                                            let task = Task::new(
+                                                format!("Copying the options of {} to {}", other.name, profile.name),
+                                                Caller::standard(task_copy_options(
+                                                    other_instance.settings.game_dir.clone(),
+                                                    other_instance.settings.version.clone(),
+                                                    instance.settings.game_dir.clone(),
+                                                    instance.settings.version.clone(),
+                                                )),
+                                            );
+                                            self.manager.push_task::<OptionsCollection>(task);
+                                        }
+                                    }
+
+                                    if let Some(source) = source {
+                                        ui.data_mut(|map| map.insert_temp(source_id, source));
+                                    }
+                                });
+                            }
+                        });
+
+                        row.col(|ui| {
+                            if let (ProfileState::Downloaded(_), Some(session_logs)) = (&profile.state, self.supervisor.session_logs()) {
+                                let popup_id = ui.make_persistent_id("logs_popup_id");
+                                let button = ui.button("Logs").on_hover_text("Output of the previous launches");
+
+                                if button.clicked() {
+                                    ui.memory_mut(|mem| mem.toggle_popup(popup_id));
+
+                                    let task = Task::new(format!("Looking for the logs of {}", profile.name), Caller::standard(task_list_session_logs(session_logs.clone(), profile.id)));
+                                    self.manager.push_task::<SessionLogsCollection>(task);
+                                }
+
+                                popup_below_widget(ui, popup_id, &button, PopupCloseBehavior::CloseOnClickOutside, |ui| {
+                                    ui.set_min_width(250.0);
+
+                                    let Some(logs) = self.profiles_state.session_logs.get(&profile.id) else {
+                                        ui.spinner();
+                                        return;
+                                    };
+
+                                    if logs.is_empty() {
+                                        ui.label("This profile has not been launched yet");
+                                    }
+
+                                    for log in logs {
+                                        ui.horizontal(|ui| {
+                                            ui.label(format!("{}, {}", format_ago(log.started), format_bytes(log.size)));
+
+                                            if ui.button("Show").on_hover_text("Show the log in the logs tab").clicked() {
+                                                let (tx, _rx) = tokio::sync::mpsc::channel(1);
+                                                let records = self.game_logs_state.records();
+                                                let path = log.path.clone();
+
+                                                spawn_tokio_future(tx, async move {
+                                                    let parsed = read_log_file(&path).await.report_error()?;
+                                                    GameLogsState::replace(&records, parsed);
+                                                    Some(())
+                                                });
+
+                                                ui.memory_mut(|mem| mem.close_popup());
+                                            }
+                                        });
+                                    }
+                                });
+                            }
+                        });
+
                         row.col(|ui| {
                             if let ProfileState::Downloaded(instance) = &profile.state {
                                 let popup_id = ui.make_persistent_id("delete_popup_id");
@@ -193,6 +659,7 @@ impl Component for ProfilesPage<'_> {
                                     let delete_client_id = Id::new("delete_client");
                                     let delete_libraries_id = Id::new("delete_libraries");
                                     let delete_assets_id = Id::new("delete_assets");
+                                    let delete_game_dir_id = Id::new("delete_game_dir");
 
                                     let mut make_checkbox = |text: &str, id, default: bool| {
                                         let mut state = ui.data_mut(|map| *map.get_temp_mut_or_insert_with(id, move || default));
@@ -203,6 +670,9 @@ impl Component for ProfilesPage<'_> {
                                     make_checkbox("Delete profile's client", delete_client_id, true);
                                     make_checkbox("Delete profile's libraries", delete_libraries_id, true);
                                     make_checkbox("Delete profile's assets", delete_assets_id, false);
+                                    if !is_shared_game_dir(&instance.settings.game_dir, Path::new(MINECRAFT_DIR)) {
+                                        make_checkbox("Delete profile's game folder (worlds, mods, options)", delete_game_dir_id, true);
+                                    }
 
                                     ui.label("Are you sure you want to delete this profile and it's data?");
                                     ui.horizontal(|ui| {
@@ -216,10 +686,24 @@ impl Component for ProfilesPage<'_> {
                                             let delete_client = checkbox_data(delete_client_id);
                                             let delete_libraries = checkbox_data(delete_libraries_id);
                                             let delete_assets = checkbox_data(delete_assets_id);
+                                            let delete_game_dir = checkbox_data(delete_game_dir_id);
 
                                             let instance = instance.clone();
+                                            let history = self.supervisor.history().cloned();
+                                            let session_logs = self.supervisor.session_logs().cloned();
+                                            let profile_id = profile.id;
                                             let caller = Caller::standard(async move {
                                                 instance.delete(delete_client, delete_libraries, delete_assets).await.report_error();
+                                                if delete_game_dir {
+                                                    remove_instance_dir(&instance.settings.game_dir, Path::new(MINECRAFT_DIR)).await.report_error();
+                                                }
+                                                remove_backups(profile_id).await.report_error();
+                                                if let Some(history) = history {
+                                                    history.remove_profile(profile_id).await.report_error();
+                                                }
+                                                if let Some(session_logs) = session_logs {
+                                                    session_logs.remove_profile(profile_id).await.report_error();
+                                                }
                                             });
 
                                             let task = Task::new(format!("Deleting the game's files ({})", version), caller);
@@ -238,6 +722,42 @@ impl Component for ProfilesPage<'_> {
                     });
                 }
 
+                for (index, window) in window_changes {
+                    let profile = Arc::make_mut(&mut self.profiles_state.profiles.profiles[index]);
+                    if let ProfileState::Downloaded(instance) = &mut profile.state {
+                        Arc::make_mut(instance).settings.window = window;
+                    }
+                    self.profiles_state.profiles.update_config().report_error();
+                }
+
+                for (index, memory) in memory_changes {
+                    let profile = Arc::make_mut(&mut self.profiles_state.profiles.profiles[index]);
+                    if let ProfileState::Downloaded(instance) = &mut profile.state {
+                        Arc::make_mut(instance).settings.memory = memory;
+                    }
+                    self.profiles_state.profiles.update_config().report_error();
+                }
+
+                for (index, hooks) in hooks_changes {
+                    let profile = Arc::make_mut(&mut self.profiles_state.profiles.profiles[index]);
+                    if let ProfileState::Downloaded(instance) = &mut profile.state {
+                        Arc::make_mut(instance).settings.hooks = hooks;
+                    }
+                    self.profiles_state.profiles.update_config().report_error();
+                }
+
+                for (index, folders) in folders_changes {
+                    let profile = Arc::make_mut(&mut self.profiles_state.profiles.profiles[index]);
+                    if let ProfileState::Downloaded(instance) = &mut profile.state {
+                        let settings = &mut Arc::make_mut(instance).settings;
+                        for &folder in settings.shared_folders.difference(&folders) {
+                            unlink_shared_folder(&settings.game_dir, &shared_folders_dir(), folder).report_error();
+                        }
+                        settings.shared_folders = folders;
+                    }
+                    self.profiles_state.profiles.update_config().report_error();
+                }
+
                 is_deleting.drain(..).for_each(|index| {
                     self.profiles_state.profiles.profiles.remove(index);
                     self.profiles_state.profiles.update_config().report_error();
@@ -245,3 +765,202 @@ impl Component for ProfilesPage<'_> {
             });
     }
 }
+
+struct Launcher<'a> {
+    supervisor: &'a Supervisor,
+    settings_state: &'a SettingsState,
+    game_logs_state: &'a GameLogsState,
+    login_state: &'a LoginState,
+}
+
+impl Launcher<'_> {
+    fn launch(
+        &self,
+        profile: &VersionProfile,
+        instance: &LaunchInstance,
+        quick_play: Option<QuickPlay>,
+    ) {
+        let profile_id = profile.id;
+
+        let mut instance = instance.clone();
+        instance.settings.window = instance
+            .settings
+            .window
+            .or(Some(self.settings_state.window));
+        instance.settings.hooks = instance.settings.hooks.merged(&self.settings_state.hooks);
+        instance.settings.profile_name = Some(profile.name.clone());
+        instance.settings.use_argfile |= self.settings_state.use_argfile;
+
+        let (tx, _rx) = tokio::sync::mpsc::channel(100);
+
+        let username = self.settings_state.username.clone();
+        let uuid = Some(self.settings_state.uuid.clone()).filter(|uuid| !uuid.is_empty());
+        let account = self.settings_state.account.clone();
+        let auth = MicrosoftAuth::new(self.settings_state.microsoft_client_id.trim());
+        let refreshed_account = self.login_state.refreshed_account.clone();
+
+        let java_runner = self.settings_state.java.clone();
+        let supervisor = self.supervisor.clone();
+        let records = self.game_logs_state.records();
+
+        spawn_tokio_future(tx, async move {
+            // An expired token would get the game kicked from every server
+            let user_data = match account {
+                Some(account) => {
+                    let mut validated = account.clone();
+                    let user_data = auth.launch_user_data(&mut validated).await.report_error()?;
+                    if validated != account {
+                        *refreshed_account.lock().unwrap() = Some(validated);
+                    }
+                    user_data
+                }
+                None => UserData {
+                    username: Username::new(username).unwrap(),
+                    uuid,
+                    access_token: None,
+                    xuid: None,
+                    client_id: None,
+                },
+            };
+
+            let handle = supervisor
+                .launch(profile_id, &instance, user_data, &java_runner, quick_play)
+                .await
+                .report_error()?;
+
+            records.lock().unwrap().clear();
+            LogParser::new()
+                .parse_output(&handle, |record| {
+                    GameLogsState::push(&records, record);
+                })
+                .await;
+
+            let exit = handle.wait().await?;
+            if let Some(crash) = &exit.crash {
+                report_crash(crash, &instance.settings.game_dir).await;
+            }
+
+            Some(exit)
+        });
+    }
+}
+
+/// Graph of the recent memory and CPU usage with the details on hover.
+fn resource_usage_ui(ui: &mut Ui, game: &GameHandle) {
+    let samples = game.resource_history();
+
+    let (rect, response) = ui.allocate_exact_size(egui::vec2(60.0, 20.0), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_stroke(rect, 2.0, ui.visuals().widgets.noninteractive.bg_stroke);
+
+    let max_memory = samples
+        .iter()
+        .map(|sample| sample.memory)
+        .max()
+        .unwrap_or(0)
+        .max(1) as f32;
+    let max_cpu = samples
+        .iter()
+        .map(|sample| sample.cpu_percent)
+        .fold(100.0, f32::max);
+
+    // The newest sample is on the right
+    let step = rect.width() / (MAX_SAMPLES - 1) as f32;
+    let line = |value: fn(&ResourceSample) -> f32, max: f32| {
+        samples
+            .iter()
+            .rev()
+            .enumerate()
+            .map(|(index, sample)| {
+                egui::pos2(
+                    rect.right() - index as f32 * step,
+                    rect.bottom() - value(sample) / max * rect.height(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    painter.add(egui::Shape::line(
+        line(|sample| sample.memory as f32, max_memory),
+        egui::Stroke::new(1.0, ui.visuals().hyperlink_color),
+    ));
+    painter.add(egui::Shape::line(
+        line(|sample| sample.cpu_percent, max_cpu),
+        egui::Stroke::new(1.0, ui.visuals().warn_fg_color),
+    ));
+
+    let uptime = format_uptime(game.uptime());
+    let details = match game.resource_usage() {
+        Some(sample) => format!(
+            "Memory: {}\nCPU: {:.0}%\nUptime: {uptime}",
+            format_bytes(sample.memory),
+            sample.cpu_percent
+        ),
+        None => format!("Uptime: {uptime}"),
+    };
+
+    if let Some(sample) = game.resource_usage() {
+        ui.label(format_bytes(sample.memory));
+    }
+
+    response.on_hover_text(details);
+}
+
+fn format_bytes(bytes: u64) -> String {
+    let kib = bytes as f64 / 1024.0;
+    if kib < 1024.0 {
+        format!("{kib:.0} KiB")
+    } else if kib < 1024.0 * 1024.0 {
+        format!("{:.0} MiB", kib / 1024.0)
+    } else {
+        format!("{:.1} GiB", kib / (1024.0 * 1024.0))
+    }
+}
+
+fn format_uptime(uptime: Duration) -> String {
+    let seconds = uptime.as_secs();
+    format!(
+        "{}:{:02}:{:02}",
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+/// `95 min` -> `1.6 h`
+fn format_playtime(playtime: Duration) -> String {
+    let minutes = playtime.as_secs() / 60;
+    if minutes < 60 {
+        format!("{minutes} min")
+    } else {
+        format!("{:.1} h", playtime.as_secs_f64() / 3600.0)
+    }
+}
+
+fn format_ago(time: SystemTime) -> String {
+    let elapsed = SystemTime::now()
+        .duration_since(time)
+        .unwrap_or_default()
+        .as_secs();
+
+    match elapsed {
+        0..=59 => "just now".to_owned(),
+        60..=3599 => format!("{} min ago", elapsed / 60),
+        3600..=172_799 => format!("{} h ago", elapsed / 3600),
+        _ => format!("{} days ago", elapsed / 86400),
+    }
+}
+
+async fn report_crash(crash: &Crash, game_dir: &Path) {
+    let log = read_log_tail(game_dir, LOG_TAIL_LINES)
+        .await
+        .unwrap_or_default();
+
+    if let Some(analyzer) = CrashAnalyzer::load().await.report_error() {
+        for diagnosis in analyzer.analyze(crash, &log) {
+            Err::<(), _>(format!("{}\n{}", diagnosis.diagnosis, diagnosis.fix)).report_error();
+        }
+    }
+
+    Err::<(), _>(crash.clone()).report_error();
+}
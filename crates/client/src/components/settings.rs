@@ -3,15 +3,27 @@ use std::path::PathBuf;
 use eframe::egui::{self, RichText};
 use egui_file_dialog::FileDialog;
 use egui_form::{garde::field_path, Form, FormField};
-use egui_task_manager::TaskManager;
+use egui_task_manager::{Caller, Task, TaskManager};
 use garde::{Error, Validate};
 use nomi_core::{
-    fs::write_toml_config_sync, regex::Regex, repository::java_runner::JavaRunner, Uuid,
+    auth::MicrosoftAccount,
+    fs::write_toml_config_sync,
+    instance::launch::{
+        hooks::{is_valid_env_key, LaunchHooks},
+        Resolution, WindowSettings,
+    },
+    regex::Regex,
+    repository::{java_runner::JavaRunner, username::Username},
     DOT_NOMI_JAVA_EXECUTABLE, DOT_NOMI_LOGS_DIR, DOT_NOMI_SETTINGS_CONFIG,
 };
 use serde::{Deserialize, Serialize};
 
-use crate::{collections::JavaCollection, errors_pool::ErrorPoolExt, states::JavaState};
+use crate::{
+    account::{task_login, LoginState},
+    collections::{JavaCollection, LoginCollection},
+    errors_pool::ErrorPoolExt,
+    states::JavaState,
+};
 
 use super::Component;
 
@@ -22,16 +34,36 @@ pub struct SettingsPage<'a> {
     pub settings_state: &'a mut SettingsState,
     pub client_settings_state: &'a mut ClientSettingsState,
     pub file_dialog: &'a mut FileDialog,
+    pub login_state: &'a LoginState,
 }
 
 #[derive(Debug, Validate, Serialize, Deserialize, Clone)]
 pub struct SettingsState {
     #[garde(custom(check_username))]
     pub username: String,
+    /// Empty means the offline UUID of the username.
     #[garde(custom(check_uuid))]
+    #[serde(default)]
     pub uuid: String,
     #[garde(skip)]
     pub java: JavaRunner,
+    #[garde(skip)]
+    #[serde(default)]
+    pub window: WindowSettings,
+    #[garde(skip)]
+    #[serde(default)]
+    pub hooks: LaunchHooks,
+    #[garde(skip)]
+    #[serde(default)]
+    pub use_argfile: bool,
+    /// Id of the Azure application used to log in with Microsoft.
+    #[garde(skip)]
+    #[serde(default)]
+    pub microsoft_client_id: String,
+    /// `None` means the game is played offline.
+    #[garde(skip)]
+    #[serde(default)]
+    pub account: Option<MicrosoftAccount>,
 
     #[garde(skip)]
     pub client_settings: ClientSettingsState,
@@ -47,8 +79,13 @@ impl Default for SettingsState {
     fn default() -> Self {
         SettingsState {
             username: "Nomi".to_owned(),
-            uuid: Uuid::new_v4().to_string(),
+            uuid: String::new(),
             java: JavaRunner::command("java"),
+            window: WindowSettings::default(),
+            hooks: LaunchHooks::default(),
+            use_argfile: false,
+            microsoft_client_id: String::new(),
+            account: None,
             client_settings: ClientSettingsState::default(),
         }
     }
@@ -67,6 +104,159 @@ impl Default for ClientSettingsState {
     }
 }
 
+pub fn window_settings_ui(ui: &mut egui::Ui, window: &mut WindowSettings) {
+    let mut is_custom = window.resolution.is_some();
+    if ui.checkbox(&mut is_custom, "Custom resolution").changed() {
+        window.resolution = is_custom.then(Resolution::default);
+    }
+
+    if let Some(resolution) = &mut window.resolution {
+        ui.horizontal(|ui| {
+            ui.add(egui::DragValue::new(&mut resolution.width).clamp_range(1..=16384));
+            ui.label("x");
+            ui.add(egui::DragValue::new(&mut resolution.height).clamp_range(1..=16384));
+        });
+    }
+
+    ui.checkbox(&mut window.fullscreen, "Fullscreen");
+}
+
+fn microsoft_login_ui(
+    ui: &mut egui::Ui,
+    manager: &mut TaskManager,
+    settings_state: &mut SettingsState,
+    login_state: &LoginState,
+) {
+    let is_logging_in = !manager
+        .get_collection::<LoginCollection>()
+        .tasks()
+        .is_empty();
+
+    ui.horizontal(|ui| {
+        ui.label("Azure client id");
+        ui.add_enabled(
+            !is_logging_in,
+            egui::TextEdit::singleline(&mut settings_state.microsoft_client_id),
+        );
+    });
+
+    let client_id = settings_state.microsoft_client_id.trim().to_owned();
+
+    if ui
+        .add_enabled(
+            !is_logging_in && !client_id.is_empty(),
+            egui::Button::new("Log in with Microsoft"),
+        )
+        .on_hover_text("Play online with your Minecraft account")
+        .clicked()
+    {
+        settings_state.update_config();
+
+        let device_code = login_state.device_code.clone();
+        let task = Task::new(
+            "Logging in with Microsoft",
+            Caller::standard(task_login(client_id, device_code)),
+        );
+        manager.push_task::<LoginCollection>(task);
+    }
+
+    if let Some(code) = login_state.device_code.lock().unwrap().as_ref() {
+        ui.label("Open the page below and enter the code");
+        ui.horizontal(|ui| {
+            ui.label(RichText::new(&code.user_code).monospace().strong());
+            if ui.button("Copy").clicked() {
+                ui.output_mut(|output| output.copied_text.clone_from(&code.user_code));
+            }
+        });
+        ui.hyperlink(&code.verification_uri);
+    } else if is_logging_in {
+        ui.spinner();
+    }
+}
+
+/// Edits the hooks. Empty fields are stored as `None`.
+///
+/// `id_salt` keeps the name of a new environment variable apart from other editors.
+pub fn launch_hooks_ui(ui: &mut egui::Ui, id_salt: impl std::hash::Hash, hooks: &mut LaunchHooks) {
+    optional_text_ui(
+        ui,
+        "Wrapper command",
+        "gamemoderun mangohud",
+        &mut hooks.wrapper,
+    );
+    optional_text_ui(ui, "Pre-launch command", "", &mut hooks.pre_launch);
+    optional_text_ui(ui, "Post-exit command", "", &mut hooks.post_exit);
+
+    ui.label("Environment variables");
+
+    let mut removed = None;
+    for (key, value) in &mut hooks.env {
+        ui.horizontal(|ui| {
+            if is_valid_env_key(key) {
+                ui.label(key.as_str());
+            } else {
+                ui.label(RichText::new(key.as_str()).color(ui.visuals().error_fg_color))
+                    .on_hover_text("Invalid name, the game will not launch until it is removed");
+            }
+            ui.text_edit_singleline(value);
+            if ui.button("Remove").clicked() {
+                removed = Some(key.clone());
+            }
+        });
+    }
+
+    if let Some(key) = removed {
+        hooks.env.remove(&key);
+    }
+
+    let new_key_id = egui::Id::new("new_env_variable").with(id_salt);
+    let mut new_key = ui.data_mut(|map| map.get_temp_mut_or_default::<String>(new_key_id).clone());
+
+    ui.horizontal(|ui| {
+        ui.add(egui::TextEdit::singleline(&mut new_key).hint_text("__GL_THREADED_OPTIMIZATIONS"));
+
+        let key = new_key.trim();
+        if ui
+            .add_enabled(
+                is_valid_env_key(key) && !hooks.env.contains_key(key),
+                egui::Button::new("Add"),
+            )
+            .clicked()
+        {
+            hooks.env.insert(key.to_owned(), String::new());
+            new_key.clear();
+        }
+    });
+
+    let key = new_key.trim();
+    if !key.is_empty() && !is_valid_env_key(key) {
+        ui.label(
+            RichText::new("Use only letters, digits and `_`, and do not start with a digit")
+                .color(ui.visuals().error_fg_color),
+        );
+    }
+
+    ui.data_mut(|map| map.insert_temp(new_key_id, new_key));
+
+    ui.label(
+        RichText::new(
+            "Placeholders: ${profile_name}, ${version_name}, ${game_directory}, ${natives_directory}, ${exit_code}",
+        )
+        .small(),
+    );
+}
+
+fn optional_text_ui(ui: &mut egui::Ui, label: &str, hint: &str, value: &mut Option<String>) {
+    let mut text = value.clone().unwrap_or_default();
+    ui.label(label);
+    if ui
+        .add(egui::TextEdit::singleline(&mut text).hint_text(hint))
+        .changed()
+    {
+        *value = (!text.trim().is_empty()).then_some(text);
+    }
+}
+
 fn check_username(value: &str, _context: &()) -> garde::Result {
     let regex = Regex::new(r"^[a-zA-Z0-9_]{3,16}$").map_err(|_| {
         Error::new("Cannot create regex (this is a bug, please create an issue on the github)")
@@ -88,6 +278,10 @@ A-Z characters, a-z characters, 0-9 numbers, `_` (underscore) symbol
 }
 
 fn check_uuid(value: &str, _context: &()) -> garde::Result {
+    if value.is_empty() {
+        return Ok(());
+    }
+
     let regex = Regex::new(r"^[0-9a-f]{8}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{4}-[0-9a-f]{12}$")
         .map_err(|_| {
             Error::new("Cannot create regex (this is a bug, please create an issue on the github)")
@@ -151,12 +345,35 @@ impl Component for SettingsPage<'_> {
                         egui::TextEdit::singleline(&mut self.settings_state.username),
                     );
 
+                let offline_uuid = Username::new(settings_data.username.as_str())
+                    .map(|username| username.offline_uuid().to_string())
+                    .unwrap_or_default();
+
                 FormField::new(&mut form, field_path!("uuid"))
                     .label("UUID")
                     .ui(
                         ui,
-                        egui::TextEdit::singleline(&mut self.settings_state.uuid),
+                        egui::TextEdit::singleline(&mut self.settings_state.uuid)
+                            .hint_text(offline_uuid),
                     );
+
+                ui.label(
+                    RichText::new("Leave empty to use the offline UUID derived from the username")
+                        .small(),
+                );
+
+                ui.separator();
+
+                if let Some(account) = &settings_data.account {
+                    ui.label(format!("Logged in with Microsoft as {}", account.username));
+
+                    if ui.button("Log out").clicked() {
+                        self.settings_state.account = None;
+                        self.settings_state.update_config();
+                    }
+                } else {
+                    microsoft_login_ui(ui, self.manager, self.settings_state, self.login_state);
+                }
             });
 
             ui.collapsing("Java", |ui| {
@@ -195,6 +412,17 @@ impl Component for SettingsPage<'_> {
                             }
                         ))
                     });
+
+                ui.checkbox(&mut self.settings_state.use_argfile, "Pass JVM arguments through a file")
+                    .on_hover_text("Keeps the command line short for profiles with many mods. Requires Java 9 or newer, older versions get the arguments inline");
+            });
+
+            ui.collapsing("Game window", |ui| {
+                window_settings_ui(ui, &mut self.settings_state.window);
+            });
+
+            ui.collapsing("Launch hooks", |ui| {
+                launch_hooks_ui(ui, "global", &mut self.settings_state.hooks);
             });
 
             ui.collapsing("Client", |ui| {
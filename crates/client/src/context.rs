@@ -1,5 +1,7 @@
 use crate::{
-    components::{self, profiles::ProfilesPage, settings::SettingsPage, Component},
+    components::{
+        self, game_logs::GameLogs, profiles::ProfilesPage, settings::SettingsPage, Component,
+    },
     errors_pool::ErrorPoolExt,
     states::States,
     Tab, TabKind,
@@ -10,8 +12,10 @@ use egui_file_dialog::FileDialog;
 use egui_task_manager::TaskManager;
 use egui_tracing::EventCollector;
 use nomi_core::{
+    instance::{history::HistoryStore, session_logs::SessionLogs, supervisor::Supervisor},
     repository::launcher_manifest::{Latest, LauncherManifest},
     state::get_launcher_manifest,
+    DOT_NOMI_LAUNCH_HISTORY, DOT_NOMI_SESSION_LOGS_DIR,
 };
 
 pub struct MyContext {
@@ -20,6 +24,7 @@ pub struct MyContext {
     pub file_dialog: FileDialog,
 
     pub manager: TaskManager,
+    pub supervisor: Supervisor,
     pub states: States,
 
     pub is_allowed_to_take_action: bool,
@@ -52,6 +57,9 @@ impl MyContext {
 
             states: States::new(),
             manager: TaskManager::new(),
+            supervisor: Supervisor::new()
+                .with_history(HistoryStore::load(DOT_NOMI_LAUNCH_HISTORY))
+                .with_session_logs(SessionLogs::new(DOT_NOMI_SESSION_LOGS_DIR)),
             is_allowed_to_take_action: true,
         }
     }
@@ -69,7 +77,10 @@ impl TabViewer for MyContext {
             TabKind::Profiles => ProfilesPage {
                 is_allowed_to_take_action: self.is_allowed_to_take_action,
                 manager: &mut self.manager,
+                supervisor: &self.supervisor,
                 settings_state: &self.states.settings,
+                game_logs_state: &self.states.game_logs,
+                login_state: &self.states.login,
                 profiles_state: &mut self.states.profiles,
                 menu_state: &mut self.states.add_profile_menu_state,
 
@@ -82,10 +93,17 @@ impl TabViewer for MyContext {
                 manager: &mut self.manager,
                 settings_state: &mut self.states.settings,
                 client_settings_state: &mut self.states.client_settings,
+                login_state: &self.states.login,
                 file_dialog: &mut self.file_dialog,
             }
             .ui(ui),
             TabKind::Logs => {
+                ui.collapsing("Game", |ui| {
+                    GameLogs {
+                        game_logs_state: &mut self.states.game_logs,
+                    }
+                    .ui(ui);
+                });
                 ScrollArea::horizontal().show(ui, |ui| {
                     ui.add(egui_tracing::Logs::new(self.collector.clone()));
                 });
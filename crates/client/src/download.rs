@@ -1,4 +1,8 @@
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    collections::BTreeSet,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use anyhow::{anyhow, Context};
 use egui_task_manager::TaskProgressShared;
@@ -9,14 +13,19 @@ use nomi_core::{
         traits::{DownloadResult, Downloader, DownloaderIO, DownloaderIOExt},
         AssetsDownloader, DownloadQueue,
     },
-    game_paths::GamePaths,
-    instance::{launch::LaunchSettings, Instance, InstanceBuilder},
+    game_paths::{
+        instance_dir, is_shared_game_dir, migrate_shared_game_dir, GamePaths, MINECRAFT_DIR,
+    },
+    instance::{
+        launch::{hooks::LaunchHooks, memory::MemoryMode, LaunchSettings},
+        Instance, InstanceBuilder,
+    },
     loaders::{fabric::Fabric, vanilla::Vanilla},
     repository::java_runner::JavaRunner,
     state::get_launcher_manifest,
 };
 
-use crate::errors_pool::ErrorPoolExt;
+use crate::{errors_pool::ErrorPoolExt, options::apply_default_options};
 
 pub async fn task_download_version(
     profile: Arc<VersionProfile>,
@@ -31,9 +40,6 @@ async fn try_download_version(
     profile: Arc<VersionProfile>,
     progress_shared: TaskProgressShared,
 ) -> anyhow::Result<VersionProfile> {
-    let current_dir = PathBuf::from("./");
-    let mc_dir: std::path::PathBuf = current_dir.join("minecraft");
-
     let ProfileState::NotDownloaded {
         version,
         version_type,
@@ -43,12 +49,12 @@ async fn try_download_version(
         return Err(anyhow!("This profile is already downloaded"));
     };
 
-    let game_paths = GamePaths {
-        game: mc_dir.clone(),
-        assets: mc_dir.join("assets"),
-        version: mc_dir.join("versions").join(profile.version()),
-        libraries: mc_dir.join("libraries"),
-    };
+    let game_paths = GamePaths::for_profile(
+        Path::new(MINECRAFT_DIR),
+        profile.version(),
+        profile.id,
+        &profile.name,
+    );
 
     let builder = Instance::builder()
         .name(profile.name.clone())
@@ -81,6 +87,12 @@ async fn try_download_version(
             .join(format!("{}.jar", &version)),
         version: version.to_string(),
         version_type: version_type.clone(),
+        memory: MemoryMode::default(),
+        window: None,
+        profile_name: Some(profile.name.clone()),
+        hooks: LaunchHooks::default(),
+        use_argfile: false,
+        shared_folders: BTreeSet::new(),
     };
 
     let launch_instance = instance.launch_instance(
@@ -105,6 +117,11 @@ async fn try_download_version(
 
     Box::new(downloader).download(&mapped_sender).await;
 
+    // A broken defaults file should not fail the download
+    apply_default_options(&game_paths.game, version)
+        .await
+        .report_error();
+
     let profile = VersionProfile {
         id: profile.id,
         name: profile.name.clone(),
@@ -150,3 +167,29 @@ async fn try_assets(
 
     Ok(())
 }
+
+/// Moves a profile created before the game directories were isolated into its own directory.
+///
+/// Returns `None` if the profile already has its own directory.
+pub async fn task_migrate_game_dir(profile: Arc<VersionProfile>) -> Option<VersionProfile> {
+    let ProfileState::Downloaded(instance) = &profile.state else {
+        return None;
+    };
+
+    let root = Path::new(MINECRAFT_DIR);
+    if !is_shared_game_dir(&instance.settings.game_dir, root) {
+        return None;
+    }
+
+    let target = instance_dir(root, profile.id, &profile.name);
+    migrate_shared_game_dir(root.to_path_buf(), target.clone())
+        .await
+        .report_error()?;
+
+    let mut profile = VersionProfile::clone(&profile);
+    if let ProfileState::Downloaded(instance) = &mut profile.state {
+        Arc::make_mut(instance).settings.game_dir = target;
+    }
+
+    Some(profile)
+}
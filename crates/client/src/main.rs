@@ -1,4 +1,6 @@
-use collections::{AssetsCollection, GameDownloadingCollection, JavaCollection};
+use collections::{
+    AssetsCollection, GameDirMigrationCollection, GameDownloadingCollection, JavaCollection,
+};
 use components::{add_tab_menu::AddTab, Component};
 use context::MyContext;
 use eframe::{
@@ -17,10 +19,14 @@ use tracing_subscriber::{
     EnvFilter,
 };
 
+pub mod account;
 pub mod components;
 pub mod download;
 pub mod errors_pool;
+pub mod options;
+pub mod session_logs;
 pub mod utils;
+pub mod worlds;
 
 pub mod simplify;
 
@@ -117,6 +123,11 @@ impl MyTabs {
 
 impl eframe::App for MyTabs {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if let Some(account) = self.context.states.login.take_refreshed_account() {
+            self.context.states.settings.account = Some(account);
+            self.context.states.settings.update_config();
+        }
+
         self.context
             .manager
             .add_collection::<collections::AssetsCollection>(())
@@ -127,10 +138,28 @@ impl eframe::App for MyTabs {
             .add_collection::<collections::GameDownloadingCollection>(
                 &mut self.context.states.profiles.profiles,
             )
-            .add_collection::<collections::JavaCollection>(());
+            .add_collection::<collections::GameDirMigrationCollection>(
+                &mut self.context.states.profiles.profiles,
+            )
+            .add_collection::<collections::JavaCollection>(())
+            .add_collection::<collections::LoginCollection>(&mut self.context.states.settings)
+            .add_collection::<collections::OptionsCollection>(())
+            .add_collection::<collections::SessionLogsCollection>(
+                &mut self.context.states.profiles.session_logs,
+            )
+            .add_collection::<collections::WorldsCollection>(
+                &mut self.context.states.profiles.worlds,
+            );
 
         ctx.set_pixels_per_point(self.context.states.client_settings.pixels_per_point);
 
+        if !self.context.states.profiles.is_game_dirs_migrated {
+            self.context
+                .states
+                .profiles
+                .migrate_game_dirs(&mut self.context.manager);
+        }
+
         if !self.context.states.java.is_downloaded {
             self.context
                 .states
@@ -251,6 +280,7 @@ impl eframe::App for MyTabs {
             manager.get_collection::<AssetsCollection>(),
             manager.get_collection::<JavaCollection>(),
             manager.get_collection::<GameDownloadingCollection>(),
+            manager.get_collection::<GameDirMigrationCollection>(),
         ]
         .iter()
         .all(|c| c.tasks().is_empty());
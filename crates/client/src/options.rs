@@ -0,0 +1,39 @@
+use std::path::{Path, PathBuf};
+
+use nomi_core::instance::options::{copy_options, DefaultOptions};
+
+use crate::errors_pool::ErrorPoolExt;
+
+/// Makes the options of the profile the ones every new profile starts with.
+pub async fn task_save_default_options(game_dir: PathBuf, game_version: String) {
+    try_save_default_options(&game_dir, &game_version)
+        .await
+        .report_error();
+}
+
+async fn try_save_default_options(game_dir: &Path, game_version: &str) -> anyhow::Result<()> {
+    DefaultOptions::from_game_dir(game_dir, game_version)
+        .await?
+        .save()
+        .await
+}
+
+pub async fn task_copy_options(
+    from_dir: PathBuf,
+    from_version: String,
+    to_dir: PathBuf,
+    to_version: String,
+) {
+    copy_options(&from_dir, &from_version, &to_dir, &to_version)
+        .await
+        .report_error();
+}
+
+/// Gives a new profile the default options, if there are any.
+pub async fn apply_default_options(game_dir: &Path, game_version: &str) -> anyhow::Result<()> {
+    if let Some(defaults) = DefaultOptions::load().await? {
+        defaults.apply(game_dir, game_version).await?;
+    }
+
+    Ok(())
+}
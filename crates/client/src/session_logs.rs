@@ -0,0 +1,15 @@
+use nomi_core::instance::session_logs::{SessionLog, SessionLogs};
+
+use crate::errors_pool::ErrorPoolExt;
+
+pub async fn task_list_session_logs(
+    session_logs: SessionLogs,
+    profile_id: usize,
+) -> Option<(usize, Vec<SessionLog>)> {
+    let logs = tokio::task::spawn_blocking(move || session_logs.list(profile_id))
+        .await
+        .report_error()?
+        .report_error()?;
+
+    Some((profile_id, logs))
+}
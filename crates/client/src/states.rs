@@ -1,4 +1,7 @@
-use std::{collections::HashSet, path::PathBuf};
+use std::{
+    collections::{HashMap, HashSet},
+    path::PathBuf,
+};
 
 use egui_task_manager::{Caller, Task, TaskManager};
 use nomi_core::{
@@ -14,9 +17,11 @@ use nomi_core::{
 use tracing::info;
 
 use crate::{
+    account::LoginState,
     collections::JavaCollection,
     components::{
         add_tab_menu::TabsState,
+        game_logs::GameLogsState,
         profiles::ProfilesState,
         settings::{ClientSettingsState, SettingsState},
         AddProfileMenuState, ProfilesConfig,
@@ -34,6 +39,8 @@ pub struct States {
     pub settings: SettingsState,
     pub client_settings: ClientSettingsState,
     pub add_profile_menu_state: AddProfileMenuState,
+    pub game_logs: GameLogsState,
+    pub login: LoginState,
 }
 
 impl Default for States {
@@ -56,10 +63,16 @@ impl Default for States {
                 currently_downloading_profiles: HashSet::new(),
                 profiles: read_toml_config_sync::<ProfilesConfig>(DOT_NOMI_PROFILES_CONFIG)
                     .unwrap_or_default(),
+                is_game_dirs_migrated: false,
+                sort_by_last_played: false,
+                worlds: HashMap::new(),
+                session_logs: HashMap::new(),
             },
             client_settings: settings.client_settings.clone(),
             settings,
             add_profile_menu_state: AddProfileMenuState::default(),
+            game_logs: GameLogsState::new(),
+            login: LoginState::default(),
         }
     }
 }
@@ -0,0 +1,149 @@
+use std::path::{Path, PathBuf};
+
+use egui_task_manager::TaskProgressShared;
+use nomi_core::{
+    downloads::progress::MappedSender,
+    instance::worlds::{
+        backup_world, count_archive_files, count_world_files, export_world, import_world,
+        list_backups, list_worlds, restore_backup, Backup, FileProgress, World,
+    },
+    DOT_NOMI_BACKUPS_DIR,
+};
+
+use crate::errors_pool::ErrorPoolExt;
+
+/// Backups kept for every world.
+pub const KEPT_BACKUPS: usize = 5;
+
+/// Directory worlds are exported to.
+pub const EXPORTS_DIR: &str = "./exports";
+
+pub enum WorldOperation {
+    Backup { world_dir: PathBuf },
+    Restore { backup: PathBuf, world_dir: PathBuf },
+    Export { world_dir: PathBuf },
+    Import { archive: PathBuf },
+}
+
+impl WorldOperation {
+    pub fn describe(&self) -> String {
+        let name = |path: &Path| {
+            path.file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        };
+
+        match self {
+            WorldOperation::Backup { world_dir } => format!("Backing up {}", name(world_dir)),
+            WorldOperation::Restore { world_dir, .. } => format!("Restoring {}", name(world_dir)),
+            WorldOperation::Export { world_dir } => format!("Exporting {}", name(world_dir)),
+            WorldOperation::Import { archive } => format!("Importing {}", name(archive)),
+        }
+    }
+}
+
+/// Backups of the profile's worlds.
+pub fn backups_dir(profile_id: usize) -> PathBuf {
+    Path::new(DOT_NOMI_BACKUPS_DIR).join(profile_id.to_string())
+}
+
+/// Removes the backups of a deleted profile, so a new profile with the same id does not see them.
+pub async fn remove_backups(profile_id: usize) -> anyhow::Result<()> {
+    match tokio::fs::remove_dir_all(backups_dir(profile_id)).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// A world with its latest backup, both loaded in a task so the worlds menu does not read the disk every frame.
+pub struct WorldEntry {
+    pub world: World,
+    pub latest_backup: Option<Backup>,
+}
+
+pub async fn task_list_worlds(
+    profile_id: usize,
+    game_dir: PathBuf,
+) -> Option<(usize, Vec<WorldEntry>)> {
+    let worlds = list_worlds(&game_dir).await.report_error()?;
+
+    let entries = tokio::task::spawn_blocking(move || {
+        let backups_dir = backups_dir(profile_id);
+
+        worlds
+            .into_iter()
+            .map(|world| WorldEntry {
+                latest_backup: list_backups(&backups_dir, &world.folder_name())
+                    .report_error()
+                    .and_then(|backups| backups.into_iter().next()),
+                world,
+            })
+            .collect::<Vec<_>>()
+    })
+    .await
+    .report_error()?;
+
+    Some((profile_id, entries))
+}
+
+/// Runs the operation and returns the updated list of the profile's worlds.
+pub async fn task_world_operation(
+    profile_id: usize,
+    game_dir: PathBuf,
+    operation: WorldOperation,
+    progress_shared: TaskProgressShared,
+) -> Option<(usize, Vec<WorldEntry>)> {
+    try_world_operation(profile_id, &game_dir, operation, progress_shared)
+        .await
+        .report_error();
+
+    task_list_worlds(profile_id, game_dir).await
+}
+
+async fn try_world_operation(
+    profile_id: usize,
+    game_dir: &Path,
+    operation: WorldOperation,
+    progress_shared: TaskProgressShared,
+) -> anyhow::Result<()> {
+    let total = match &operation {
+        WorldOperation::Backup { world_dir } | WorldOperation::Export { world_dir } => {
+            count_world_files(world_dir).await?
+        }
+        WorldOperation::Restore { backup, world_dir } => {
+            count_world_files(world_dir).await? + count_archive_files(backup).await?
+        }
+        WorldOperation::Import { archive } => count_archive_files(archive).await?,
+    };
+
+    let _ = progress_shared.set_total(total);
+
+    let sender: MappedSender<FileProgress, _> =
+        MappedSender::new_progress_mapper(Box::new(progress_shared.sender()));
+    let progress = &sender;
+
+    match operation {
+        WorldOperation::Backup { world_dir } => {
+            backup_world(&world_dir, &backups_dir(profile_id), KEPT_BACKUPS, progress).await?;
+        }
+        WorldOperation::Restore { backup, world_dir } => {
+            // Keeps the current state so the restore can be undone
+            backup_world(&world_dir, &backups_dir(profile_id), KEPT_BACKUPS, progress).await?;
+            restore_backup(&backup, &world_dir, progress).await?;
+        }
+        WorldOperation::Export { world_dir } => {
+            let name = world_dir
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let archive = Path::new(EXPORTS_DIR).join(format!("{name}.zip"));
+
+            export_world(&world_dir, &archive, progress).await?;
+        }
+        WorldOperation::Import { archive } => {
+            import_world(&archive, game_dir, progress).await?;
+        }
+    }
+
+    Ok(())
+}
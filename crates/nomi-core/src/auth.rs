@@ -0,0 +1,490 @@
+//! Microsoft account login: device code, then Xbox Live, XSTS and Minecraft services.
+
+use std::time::{Duration, SystemTime};
+
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::{instance::launch::arguments::UserData, repository::username::Username, Uuid};
+
+pub mod microsoft;
+pub mod minecraft;
+pub mod xbox;
+
+use self::microsoft::{DeviceCode, MicrosoftTokens};
+
+/// Tokens expiring sooner than this are refreshed before a launch, so they last for the session.
+pub const REFRESH_MARGIN: Duration = Duration::from_hours(1);
+
+#[derive(Debug, thiserror::Error)]
+pub enum AuthError {
+    #[error("The login was declined")]
+    Declined,
+    #[error("The login code has expired, please try again")]
+    Expired,
+    #[error("Microsoft rejected the login: {0}")]
+    Microsoft(String),
+    #[error("This Microsoft account has no Xbox account, create one at xbox.com first")]
+    NoXboxAccount,
+    #[error("Xbox Live is not available in the country of this account")]
+    CountryNotAvailable,
+    #[error("This account needs adult verification on the Xbox website")]
+    AdultVerification,
+    #[error("This is a child account, an adult must add it to a Microsoft family")]
+    ChildAccount,
+    #[error("Xbox Live rejected the login (error {0})")]
+    Xbox(u64),
+    #[error("This account does not own Minecraft")]
+    NotOwned,
+    #[error("This account has no Minecraft profile yet, choose a name on minecraft.net first")]
+    NoProfile,
+    #[error("The Microsoft login has expired, please sign in again")]
+    SignInAgain,
+}
+
+/// URLs of every step of the login, so it can be tested against a local stand-in.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AuthEndpoints {
+    pub device_code: String,
+    pub token: String,
+    pub xbox_live: String,
+    pub xsts: String,
+    pub minecraft_login: String,
+    pub entitlements: String,
+    pub profile: String,
+}
+
+impl Default for AuthEndpoints {
+    fn default() -> Self {
+        Self {
+            device_code: "https://login.microsoftonline.com/consumers/oauth2/v2.0/devicecode"
+                .into(),
+            token: "https://login.microsoftonline.com/consumers/oauth2/v2.0/token".into(),
+            xbox_live: "https://user.auth.xboxlive.com/user/authenticate".into(),
+            xsts: "https://xsts.auth.xboxlive.com/xsts/authorize".into(),
+            minecraft_login: "https://api.minecraftservices.com/authentication/login_with_xbox"
+                .into(),
+            entitlements: "https://api.minecraftservices.com/entitlements/mcstore".into(),
+            profile: "https://api.minecraftservices.com/minecraft/profile".into(),
+        }
+    }
+}
+
+impl AuthEndpoints {
+    /// Every endpoint on one server, with the paths of the real ones.
+    pub fn with_base(base: &str) -> Self {
+        let base = base.trim_end_matches('/');
+
+        Self {
+            device_code: format!("{base}/devicecode"),
+            token: format!("{base}/token"),
+            xbox_live: format!("{base}/user/authenticate"),
+            xsts: format!("{base}/xsts/authorize"),
+            minecraft_login: format!("{base}/authentication/login_with_xbox"),
+            entitlements: format!("{base}/entitlements/mcstore"),
+            profile: format!("{base}/minecraft/profile"),
+        }
+    }
+}
+
+/// A logged in Microsoft account.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MicrosoftAccount {
+    pub username: String,
+    /// With dashes.
+    pub uuid: String,
+    /// Minecraft access token passed to the game.
+    pub access_token: String,
+    pub expires_at: SystemTime,
+    /// Microsoft refresh token, logs in again without the user.
+    pub refresh_token: String,
+    pub xuid: Option<String>,
+}
+
+impl MicrosoftAccount {
+    /// Whether the token expires within [`REFRESH_MARGIN`].
+    pub fn expires_soon(&self) -> bool {
+        self.expires_at <= SystemTime::now() + REFRESH_MARGIN
+    }
+
+    pub fn user_data(&self) -> anyhow::Result<UserData> {
+        Ok(UserData {
+            username: Username::new(&self.username)?,
+            uuid: Some(self.uuid.clone()),
+            access_token: Some(self.access_token.clone()),
+            xuid: self.xuid.clone(),
+            client_id: None,
+        })
+    }
+}
+
+/// Logs in with a Microsoft account.
+///
+/// `client_id` is the id of the Azure application the user grants access to.
+#[derive(Debug, Clone)]
+pub struct MicrosoftAuth {
+    client: Client,
+    client_id: String,
+    endpoints: AuthEndpoints,
+}
+
+impl MicrosoftAuth {
+    pub fn new(client_id: impl Into<String>) -> Self {
+        Self {
+            client: Client::new(),
+            client_id: client_id.into(),
+            endpoints: AuthEndpoints::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_endpoints(mut self, endpoints: AuthEndpoints) -> Self {
+        self.endpoints = endpoints;
+        self
+    }
+
+    pub fn endpoints(&self) -> &AuthEndpoints {
+        &self.endpoints
+    }
+
+    /// Starts the login. The user enters [`DeviceCode::user_code`] at [`DeviceCode::verification_uri`].
+    pub async fn request_device_code(&self) -> anyhow::Result<DeviceCode> {
+        microsoft::request_device_code(&self.client, &self.endpoints.device_code, &self.client_id)
+            .await
+    }
+
+    /// Waits until the user has entered the code and logs in to Minecraft.
+    pub async fn login(&self, code: &DeviceCode) -> anyhow::Result<MicrosoftAccount> {
+        let tokens =
+            microsoft::wait_for_tokens(&self.client, &self.endpoints.token, &self.client_id, code)
+                .await?;
+
+        self.login_with_tokens(tokens).await
+    }
+
+    /// Returns an account that can join servers, refreshed if its token has expired or was rejected.
+    ///
+    /// The account is used as is if Minecraft services cannot be reached and the token has not expired yet.
+    pub async fn validate(&self, account: MicrosoftAccount) -> anyhow::Result<MicrosoftAccount> {
+        if !account.expires_soon() {
+            match minecraft::is_token_valid(
+                &self.client,
+                &self.endpoints.profile,
+                &account.access_token,
+            )
+            .await
+            {
+                Ok(true) => return Ok(account),
+                Ok(false) => {}
+                Err(e) => {
+                    warn!("Cannot check the Minecraft login, using it as is. Error: {e}");
+                    return Ok(account);
+                }
+            }
+        }
+
+        self.refresh(&account).await
+    }
+
+    /// User data of a launch with the account, see [`MicrosoftAuth::validate`].
+    ///
+    /// The account is replaced if it has been refreshed, save it afterwards if it has changed.
+    pub async fn launch_user_data(
+        &self,
+        account: &mut MicrosoftAccount,
+    ) -> anyhow::Result<UserData> {
+        *account = self.validate(account.clone()).await?;
+        account.user_data()
+    }
+
+    /// Logs in again with the refresh token of the account.
+    pub async fn refresh(&self, account: &MicrosoftAccount) -> anyhow::Result<MicrosoftAccount> {
+        let tokens = microsoft::refresh_tokens(
+            &self.client,
+            &self.endpoints.token,
+            &self.client_id,
+            &account.refresh_token,
+        )
+        .await?;
+
+        self.login_with_tokens(tokens).await
+    }
+
+    /// Goes through Xbox Live, XSTS and Minecraft services and fetches the profile.
+    pub async fn login_with_tokens(
+        &self,
+        tokens: MicrosoftTokens,
+    ) -> anyhow::Result<MicrosoftAccount> {
+        let endpoints = &self.endpoints;
+
+        let xbox_live =
+            xbox::authenticate(&self.client, &endpoints.xbox_live, &tokens.access_token).await?;
+        let xsts = xbox::authorize_xsts(&self.client, &endpoints.xsts, &xbox_live.token).await?;
+        let minecraft =
+            minecraft::login_with_xbox(&self.client, &endpoints.minecraft_login, &xsts).await?;
+
+        let owns_game = minecraft::owns_game(
+            &self.client,
+            &endpoints.entitlements,
+            &minecraft.access_token,
+        )
+        .await?;
+        let profile =
+            minecraft::fetch_profile(&self.client, &endpoints.profile, &minecraft.access_token)
+                .await?;
+
+        // Game Pass accounts have a profile without listing the game in the entitlements
+        let profile = match (profile, owns_game) {
+            (Some(profile), _) => profile,
+            (None, true) => return Err(AuthError::NoProfile.into()),
+            (None, false) => return Err(AuthError::NotOwned.into()),
+        };
+
+        Ok(MicrosoftAccount {
+            username: profile.name,
+            uuid: Uuid::try_parse(&profile.id)?.hyphenated().to_string(),
+            access_token: minecraft.access_token,
+            expires_at: minecraft.expires_at,
+            refresh_token: tokens.refresh_token,
+            xuid: xsts.xuid.or(xbox_live.xuid),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    };
+
+    use tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    struct Request {
+        path: String,
+        /// Lowercase.
+        head: String,
+        body: String,
+    }
+
+    type Handler = Arc<dyn Fn(&Request) -> (u16, String) + Send + Sync>;
+
+    /// A tiny HTTP server answering every request with the handler. Returns its address.
+    async fn stand_in(handler: Handler) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}", listener.local_addr().unwrap());
+
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let handler = handler.clone();
+
+                tokio::spawn(async move {
+                    let mut buf = Vec::new();
+                    let head_end = loop {
+                        let mut chunk = [0; 1024];
+                        let read = stream.read(&mut chunk).await.unwrap();
+                        buf.extend_from_slice(&chunk[..read]);
+
+                        if let Some(end) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                            break end + 4;
+                        }
+                        assert!(read > 0, "the request has no end");
+                    };
+
+                    let head = String::from_utf8_lossy(&buf[..head_end]).to_ascii_lowercase();
+                    let length = head
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .map_or(0, |length| length.trim().parse().unwrap());
+
+                    while buf.len() < head_end + length {
+                        let mut chunk = [0; 1024];
+                        let read = stream.read(&mut chunk).await.unwrap();
+                        buf.extend_from_slice(&chunk[..read]);
+                    }
+
+                    let request = Request {
+                        path: head.split(' ').nth(1).unwrap().to_owned(),
+                        body: String::from_utf8_lossy(&buf[head_end..]).into_owned(),
+                        head,
+                    };
+
+                    let (status, body) = handler(&request);
+                    let response = format!(
+                        "HTTP/1.1 {status} Stand-in\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+                        body.len()
+                    );
+                    stream.write_all(response.as_bytes()).await.unwrap();
+                });
+            }
+        });
+
+        address
+    }
+
+    /// Answers like the real services for an account that owns the game.
+    fn standard_handler(request: &Request, polls: &AtomicUsize) -> (u16, String) {
+        match request.path.as_str() {
+            "/devicecode" => (
+                200,
+                r#"{"device_code":"device","user_code":"ABCD-1234","verification_uri":"https://microsoft.com/link","expires_in":900,"interval":0,"message":"Enter the code"}"#.into(),
+            ),
+            "/token" if request.body.contains("grant_type=refresh_token") => {
+                assert!(request.body.contains("refresh_token=refresh"));
+                (200, r#"{"access_token":"ms-token","refresh_token":"refresh-2","expires_in":3600}"#.into())
+            }
+            "/token" => {
+                assert!(request.body.contains("device_code=device"));
+                if polls.fetch_add(1, Ordering::SeqCst) == 0 {
+                    (400, r#"{"error":"authorization_pending"}"#.into())
+                } else {
+                    (200, r#"{"access_token":"ms-token","refresh_token":"refresh","expires_in":3600}"#.into())
+                }
+            }
+            "/user/authenticate" => {
+                assert!(request.body.contains("d=ms-token"));
+                (200, r#"{"Token":"xbl-token","DisplayClaims":{"xui":[{"uhs":"hash"}]}}"#.into())
+            }
+            "/xsts/authorize" => {
+                assert!(request.body.contains("xbl-token"));
+                (200, r#"{"Token":"xsts-token","DisplayClaims":{"xui":[{"uhs":"hash"}]}}"#.into())
+            }
+            "/authentication/login_with_xbox" => {
+                assert!(request.body.contains("XBL3.0 x=hash;xsts-token"));
+                (200, r#"{"username":"some-id","access_token":"mc-token","expires_in":86400,"token_type":"Bearer"}"#.into())
+            }
+            "/entitlements/mcstore" => {
+                assert!(request.head.contains("authorization: bearer mc-token"));
+                (200, r#"{"items":[{"name":"product_minecraft","signature":"x"},{"name":"game_minecraft","signature":"x"}]}"#.into())
+            }
+            "/minecraft/profile" => {
+                assert!(request.head.contains("authorization: bearer mc-token"));
+                (200, r#"{"id":"069a79f444e94726a5befca90e38aaf5","name":"Notch","skins":[]}"#.into())
+            }
+            path => panic!("unexpected request to {path}"),
+        }
+    }
+
+    #[tokio::test]
+    async fn login_test() {
+        let polls = Arc::new(AtomicUsize::new(0));
+        let handler_polls = polls.clone();
+        let address = stand_in(Arc::new(move |request| {
+            standard_handler(request, &handler_polls)
+        }))
+        .await;
+
+        let auth = MicrosoftAuth::new("client").with_endpoints(AuthEndpoints::with_base(&address));
+
+        let code = auth.request_device_code().await.unwrap();
+        assert_eq!(code.user_code, "ABCD-1234");
+
+        let account = auth.login(&code).await.unwrap();
+        assert_eq!(polls.load(Ordering::SeqCst), 2);
+        assert_eq!(account.username, "Notch");
+        assert_eq!(account.uuid, "069a79f4-44e9-4726-a5be-fca90e38aaf5");
+        assert_eq!(account.access_token, "mc-token");
+        assert_eq!(account.refresh_token, "refresh");
+        assert!(account.expires_at > SystemTime::now());
+
+        let user_data = account.user_data().unwrap();
+        assert_eq!(user_data.access_token.as_deref(), Some("mc-token"));
+    }
+
+    #[tokio::test]
+    async fn errors_test() {
+        let address = stand_in(Arc::new(|request| match request.path.as_str() {
+            "/token" => (400, r#"{"error":"authorization_declined"}"#.into()),
+            "/xsts/authorize" => (
+                401,
+                r#"{"Identity":"0","XErr":2148916233,"Message":""}"#.into(),
+            ),
+            _ => standard_handler(request, &AtomicUsize::new(1)),
+        }))
+        .await;
+
+        let auth = MicrosoftAuth::new("client").with_endpoints(AuthEndpoints::with_base(&address));
+
+        let code = auth.request_device_code().await.unwrap();
+        let error = auth.login(&code).await.unwrap_err();
+        assert!(matches!(error.downcast_ref(), Some(AuthError::Declined)));
+
+        let tokens = MicrosoftTokens {
+            access_token: "ms-token".into(),
+            refresh_token: "refresh".into(),
+            expires_at: SystemTime::now(),
+        };
+        let error = auth.login_with_tokens(tokens.clone()).await.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref(),
+            Some(AuthError::NoXboxAccount)
+        ));
+
+        let address = stand_in(Arc::new(|request| match request.path.as_str() {
+            "/entitlements/mcstore" => (200, r#"{"items":[]}"#.into()),
+            "/minecraft/profile" => (404, r#"{"error":"NOT_FOUND"}"#.into()),
+            _ => standard_handler(request, &AtomicUsize::new(1)),
+        }))
+        .await;
+
+        let auth = MicrosoftAuth::new("client").with_endpoints(AuthEndpoints::with_base(&address));
+        let error = auth.login_with_tokens(tokens).await.unwrap_err();
+        assert!(matches!(error.downcast_ref(), Some(AuthError::NotOwned)));
+    }
+
+    #[tokio::test]
+    async fn validate_test() {
+        let account = MicrosoftAccount {
+            username: "Notch".into(),
+            uuid: "069a79f4-44e9-4726-a5be-fca90e38aaf5".into(),
+            access_token: "mc-token".into(),
+            expires_at: SystemTime::now() + REFRESH_MARGIN * 2,
+            refresh_token: "refresh".into(),
+            xuid: None,
+        };
+
+        let address = stand_in(Arc::new(|request| {
+            if request.path == "/token" {
+                assert!(request.body.contains("grant_type=refresh_token"));
+            }
+            standard_handler(request, &AtomicUsize::new(1))
+        }))
+        .await;
+        let auth = MicrosoftAuth::new("client").with_endpoints(AuthEndpoints::with_base(&address));
+
+        // Still valid
+        assert_eq!(auth.validate(account.clone()).await.unwrap(), account);
+
+        // Expires soon
+        let expiring = MicrosoftAccount {
+            expires_at: SystemTime::now(),
+            ..account.clone()
+        };
+        let mut refreshed = expiring.clone();
+        let user_data = auth.launch_user_data(&mut refreshed).await.unwrap();
+        assert_eq!(refreshed.refresh_token, "refresh-2");
+        assert!(!refreshed.expires_soon());
+        assert_eq!(user_data.access_token, Some(refreshed.access_token.clone()));
+
+        // Rejected by Minecraft services and the refresh token is no longer valid
+        let address = stand_in(Arc::new(|request| match request.path.as_str() {
+            "/minecraft/profile" => (401, String::new()),
+            "/token" => (
+                400,
+                r#"{"error":"invalid_grant","error_description":"AADSTS70000"}"#.into(),
+            ),
+            path => panic!("unexpected request to {path}"),
+        }))
+        .await;
+        let auth = MicrosoftAuth::new("client").with_endpoints(AuthEndpoints::with_base(&address));
+
+        let error = auth.validate(account).await.unwrap_err();
+        assert!(matches!(error.downcast_ref(), Some(AuthError::SignInAgain)));
+    }
+}
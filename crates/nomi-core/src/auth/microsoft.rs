@@ -0,0 +1,190 @@
+use std::time::{Duration, Instant, SystemTime};
+
+use reqwest::{Client, Response};
+use serde::Deserialize;
+
+use super::AuthError;
+
+/// Signing in to Xbox Live and refreshing the login without the user.
+pub const SCOPE: &str = "XboxLive.signin offline_access";
+
+const DEVICE_CODE_GRANT: &str = "urn:ietf:params:oauth:grant-type:device_code";
+const REFRESH_TOKEN_GRANT: &str = "refresh_token";
+/// Added to the polling interval every time the server asks to slow down.
+const SLOW_DOWN_STEP: Duration = Duration::from_secs(5);
+
+/// Code the user enters on the Microsoft website to log in.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DeviceCode {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    /// Seconds until the code expires.
+    pub expires_in: u64,
+    /// Seconds between the checks whether the user has logged in.
+    #[serde(default = "default_interval")]
+    pub interval: u64,
+    /// Instructions for the user, in their language.
+    #[serde(default)]
+    pub message: String,
+}
+
+fn default_interval() -> u64 {
+    5
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MicrosoftTokens {
+    pub access_token: String,
+    pub refresh_token: String,
+    pub expires_at: SystemTime,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: String,
+    expires_in: u64,
+}
+
+#[derive(Deserialize)]
+struct TokenError {
+    error: String,
+    #[serde(default)]
+    error_description: String,
+}
+
+enum Poll {
+    Pending,
+    SlowDown,
+    Done(MicrosoftTokens),
+}
+
+pub async fn request_device_code(
+    client: &Client,
+    url: &str,
+    client_id: &str,
+) -> anyhow::Result<DeviceCode> {
+    let response = client
+        .post(url)
+        .form(&[("client_id", client_id), ("scope", SCOPE)])
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(token_error(response).await);
+    }
+
+    Ok(response.json().await?)
+}
+
+/// Waits until the user has entered the code.
+pub async fn wait_for_tokens(
+    client: &Client,
+    url: &str,
+    client_id: &str,
+    code: &DeviceCode,
+) -> anyhow::Result<MicrosoftTokens> {
+    let deadline = Instant::now() + Duration::from_secs(code.expires_in);
+    let mut interval = Duration::from_secs(code.interval);
+
+    loop {
+        tokio::time::sleep(interval).await;
+
+        if Instant::now() > deadline {
+            return Err(AuthError::Expired.into());
+        }
+
+        match poll_tokens(client, url, client_id, &code.device_code).await? {
+            Poll::Pending => {}
+            Poll::SlowDown => interval += SLOW_DOWN_STEP,
+            Poll::Done(tokens) => return Ok(tokens),
+        }
+    }
+}
+
+async fn poll_tokens(
+    client: &Client,
+    url: &str,
+    client_id: &str,
+    device_code: &str,
+) -> anyhow::Result<Poll> {
+    let response = client
+        .post(url)
+        .form(&[
+            ("grant_type", DEVICE_CODE_GRANT),
+            ("client_id", client_id),
+            ("device_code", device_code),
+        ])
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        return Ok(Poll::Done(tokens_from(response.json().await?)));
+    }
+
+    let error = response.json::<TokenError>().await?;
+    match error.error.as_str() {
+        "authorization_pending" => Ok(Poll::Pending),
+        "slow_down" => Ok(Poll::SlowDown),
+        "authorization_declined" => Err(AuthError::Declined.into()),
+        "expired_token" => Err(AuthError::Expired.into()),
+        _ => Err(AuthError::Microsoft(describe(error)).into()),
+    }
+}
+
+/// Gets new tokens without the user.
+///
+/// Fails with [`AuthError::SignInAgain`] if the refresh token has expired or was revoked.
+pub async fn refresh_tokens(
+    client: &Client,
+    url: &str,
+    client_id: &str,
+    refresh_token: &str,
+) -> anyhow::Result<MicrosoftTokens> {
+    let response = client
+        .post(url)
+        .form(&[
+            ("grant_type", REFRESH_TOKEN_GRANT),
+            ("client_id", client_id),
+            ("refresh_token", refresh_token),
+            ("scope", SCOPE),
+        ])
+        .send()
+        .await?;
+
+    if response.status().is_success() {
+        return Ok(tokens_from(response.json().await?));
+    }
+
+    let error = response.json::<TokenError>().await?;
+    match error.error.as_str() {
+        "invalid_grant" => Err(AuthError::SignInAgain.into()),
+        _ => Err(AuthError::Microsoft(describe(error)).into()),
+    }
+}
+
+fn tokens_from(response: TokenResponse) -> MicrosoftTokens {
+    MicrosoftTokens {
+        access_token: response.access_token,
+        refresh_token: response.refresh_token,
+        expires_at: SystemTime::now() + Duration::from_secs(response.expires_in),
+    }
+}
+
+async fn token_error(response: Response) -> anyhow::Error {
+    let status = response.status();
+
+    match response.json::<TokenError>().await {
+        Ok(error) => AuthError::Microsoft(describe(error)).into(),
+        Err(_) => AuthError::Microsoft(status.to_string()).into(),
+    }
+}
+
+fn describe(error: TokenError) -> String {
+    if error.error_description.is_empty() {
+        error.error
+    } else {
+        error.error_description
+    }
+}
@@ -0,0 +1,114 @@
+use std::time::{Duration, SystemTime};
+
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::xbox::XboxToken;
+
+/// Any of these means the account owns the game.
+const GAME_ENTITLEMENTS: [&str; 2] = ["product_minecraft", "game_minecraft"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinecraftToken {
+    pub access_token: String,
+    pub expires_at: SystemTime,
+}
+
+#[derive(Deserialize)]
+struct LoginResponse {
+    access_token: String,
+    expires_in: u64,
+}
+
+#[derive(Deserialize)]
+struct Entitlements {
+    #[serde(default)]
+    items: Vec<Entitlement>,
+}
+
+#[derive(Deserialize)]
+struct Entitlement {
+    name: String,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MinecraftProfile {
+    /// UUID without dashes.
+    pub id: String,
+    pub name: String,
+}
+
+pub async fn login_with_xbox(
+    client: &Client,
+    url: &str,
+    xsts: &XboxToken,
+) -> anyhow::Result<MinecraftToken> {
+    let body = json!({
+        "identityToken": format!("XBL3.0 x={};{}", xsts.user_hash, xsts.token),
+    });
+
+    let response = client
+        .post(url)
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<LoginResponse>()
+        .await?;
+
+    Ok(MinecraftToken {
+        access_token: response.access_token,
+        expires_at: SystemTime::now() + Duration::from_secs(response.expires_in),
+    })
+}
+
+pub async fn owns_game(client: &Client, url: &str, access_token: &str) -> anyhow::Result<bool> {
+    let entitlements = client
+        .get(url)
+        .bearer_auth(access_token)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<Entitlements>()
+        .await?;
+
+    Ok(entitlements
+        .items
+        .iter()
+        .any(|item| GAME_ENTITLEMENTS.contains(&item.name.as_str())))
+}
+
+/// Asks Minecraft services whether the token is still accepted.
+pub async fn is_token_valid(
+    client: &Client,
+    url: &str,
+    access_token: &str,
+) -> anyhow::Result<bool> {
+    let response = client.get(url).bearer_auth(access_token).send().await?;
+
+    match response.status() {
+        StatusCode::UNAUTHORIZED => Ok(false),
+        // The token is fine, the account just has no profile
+        StatusCode::NOT_FOUND => Ok(true),
+        _ => response
+            .error_for_status()
+            .map(|_| true)
+            .map_err(Into::into),
+    }
+}
+
+/// Returns `None` if the account has no profile, e.g. it does not own the game.
+pub async fn fetch_profile(
+    client: &Client,
+    url: &str,
+    access_token: &str,
+) -> anyhow::Result<Option<MinecraftProfile>> {
+    let response = client.get(url).bearer_auth(access_token).send().await?;
+
+    if response.status() == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+
+    Ok(Some(response.error_for_status()?.json().await?))
+}
@@ -0,0 +1,122 @@
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::AuthError;
+
+const XBOX_LIVE_RELYING_PARTY: &str = "http://auth.xboxlive.com";
+const MINECRAFT_RELYING_PARTY: &str = "rp://api.minecraftservices.com/";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XboxToken {
+    pub token: String,
+    /// User hash, needed together with the XSTS token.
+    pub user_hash: String,
+    pub xuid: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct XboxResponse {
+    token: String,
+    display_claims: DisplayClaims,
+}
+
+#[derive(Deserialize)]
+struct DisplayClaims {
+    xui: Vec<XboxUser>,
+}
+
+#[derive(Deserialize)]
+struct XboxUser {
+    uhs: String,
+    #[serde(default)]
+    xid: Option<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct XboxError {
+    x_err: u64,
+}
+
+/// Exchanges the Microsoft access token for an Xbox Live token.
+pub async fn authenticate(
+    client: &Client,
+    url: &str,
+    microsoft_access_token: &str,
+) -> anyhow::Result<XboxToken> {
+    let body = json!({
+        "Properties": {
+            "AuthMethod": "RPS",
+            "SiteName": "user.auth.xboxlive.com",
+            "RpsTicket": format!("d={microsoft_access_token}"),
+        },
+        "RelyingParty": XBOX_LIVE_RELYING_PARTY,
+        "TokenType": "JWT",
+    });
+
+    request(client, url, &body).await
+}
+
+/// Exchanges the Xbox Live token for an XSTS token for Minecraft services.
+pub async fn authorize_xsts(
+    client: &Client,
+    url: &str,
+    xbox_live_token: &str,
+) -> anyhow::Result<XboxToken> {
+    let body = json!({
+        "Properties": {
+            "SandboxId": "RETAIL",
+            "UserTokens": [xbox_live_token],
+        },
+        "RelyingParty": MINECRAFT_RELYING_PARTY,
+        "TokenType": "JWT",
+    });
+
+    request(client, url, &body).await
+}
+
+async fn request(
+    client: &Client,
+    url: &str,
+    body: &serde_json::Value,
+) -> anyhow::Result<XboxToken> {
+    let response = client
+        .post(url)
+        .header(reqwest::header::ACCEPT, "application/json")
+        .json(body)
+        .send()
+        .await?;
+
+    if response.status() == StatusCode::UNAUTHORIZED {
+        if let Ok(error) = response.json::<XboxError>().await {
+            return Err(xbox_error(error.x_err).into());
+        }
+        return Err(AuthError::Xbox(0).into());
+    }
+
+    let response = response.error_for_status()?.json::<XboxResponse>().await?;
+    let user = response
+        .display_claims
+        .xui
+        .into_iter()
+        .next()
+        .ok_or(AuthError::Xbox(0))?;
+
+    Ok(XboxToken {
+        token: response.token,
+        user_hash: user.uhs,
+        xuid: user.xid,
+    })
+}
+
+fn xbox_error(code: u64) -> AuthError {
+    match code {
+        2_148_916_233 => AuthError::NoXboxAccount,
+        2_148_916_235 => AuthError::CountryNotAvailable,
+        2_148_916_236 | 2_148_916_237 => AuthError::AdultVerification,
+        2_148_916_238 => AuthError::ChildAccount,
+        code => AuthError::Xbox(code),
+    }
+}
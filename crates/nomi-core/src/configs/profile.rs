@@ -5,7 +5,10 @@ use serde::{Deserialize, Serialize};
 use typed_builder::TypedBuilder;
 
 use crate::{
-    instance::launch::{arguments::UserData, LaunchInstance},
+    instance::{
+        crash::Crash,
+        launch::{arguments::UserData, quick_play::QuickPlay, LaunchInstance},
+    },
     repository::{java_runner::JavaRunner, manifest::VersionType},
 };
 
@@ -68,9 +71,12 @@ impl VersionProfile {
         &self,
         user_data: UserData,
         java_runner: &JavaRunner,
-    ) -> anyhow::Result<()> {
+        quick_play: Option<QuickPlay>,
+    ) -> anyhow::Result<Option<Crash>> {
         match &self.state {
-            ProfileState::Downloaded(instance) => instance.launch(user_data, java_runner).await,
+            ProfileState::Downloaded(instance) => {
+                instance.launch(user_data, java_runner, quick_play).await
+            }
             ProfileState::NotDownloaded { .. } => Err(anyhow!("This profile is not downloaded!")),
         }
     }
@@ -3,7 +3,12 @@ pub const DOT_NOMI_TEMP_DIR: &str = "./.nomi/temp";
 pub const DOT_NOMI_CONFIGS_DIR: &str = "./.nomi/configs";
 pub const DOT_NOMI_PROFILES_CONFIG: &str = "./.nomi/configs/Profiles.toml";
 pub const DOT_NOMI_SETTINGS_CONFIG: &str = "./.nomi/configs/Settings.toml";
+pub const DOT_NOMI_CRASH_RULES_CONFIG: &str = "./.nomi/configs/CrashRules.toml";
+pub const DOT_NOMI_DEFAULT_OPTIONS_CONFIG: &str = "./.nomi/configs/DefaultOptions.toml";
+pub const DOT_NOMI_LAUNCH_HISTORY: &str = "./.nomi/LaunchHistory.json";
+pub const DOT_NOMI_BACKUPS_DIR: &str = "./.nomi/backups";
 pub const DOT_NOMI_LOGS_DIR: &str = "./.nomi/logs";
+pub const DOT_NOMI_SESSION_LOGS_DIR: &str = "./.nomi/logs/sessions";
 pub const DOT_NOMI_JAVA_DIR: &str = "./.nomi/java";
 pub const DOT_NOMI_JAVA_EXECUTABLE: &str = "./.nomi/java/jdk-22.0.1/bin/java";
 
@@ -1,4 +1,24 @@
-use std::path::PathBuf;
+use std::path::{Component, Path, PathBuf};
+
+/// Directory with the files shared by all profiles.
+pub const MINECRAFT_DIR: &str = "./minecraft";
+
+/// Directory inside [`MINECRAFT_DIR`] with the game directories of the profiles.
+pub const INSTANCES_DIR_NAME: &str = "instances";
+
+/// Directory inside [`MINECRAFT_DIR`] with the folders linked into the profiles' directories.
+pub const SHARED_FOLDERS_DIR_NAME: &str = "shared";
+
+/// Entries of the shared directory that are never copied into the profiles' directories.
+const SHARED_ENTRIES: [&str; 7] = [
+    "assets",
+    "libraries",
+    "versions",
+    INSTANCES_DIR_NAME,
+    SHARED_FOLDERS_DIR_NAME,
+    "logs",
+    "crash-reports",
+];
 
 #[derive(Debug, Clone)]
 pub struct GamePaths {
@@ -7,3 +27,186 @@ pub struct GamePaths {
     pub version: PathBuf,
     pub libraries: PathBuf,
 }
+
+impl GamePaths {
+    /// Paths of a profile with its own game directory.
+    ///
+    /// Assets, libraries and versions are shared between the profiles.
+    pub fn for_profile(root: &Path, version: &str, profile_id: usize, profile_name: &str) -> Self {
+        Self {
+            game: instance_dir(root, profile_id, profile_name),
+            assets: root.join("assets"),
+            version: root.join("versions").join(version),
+            libraries: root.join("libraries"),
+        }
+    }
+}
+
+/// Directory the shared folders of the profiles point to.
+pub fn shared_folders_dir() -> PathBuf {
+    Path::new(MINECRAFT_DIR).join(SHARED_FOLDERS_DIR_NAME)
+}
+
+/// Game directory of the profile, e.g. `minecraft/instances/Fabric-pack-3`.
+pub fn instance_dir(root: &Path, profile_id: usize, profile_name: &str) -> PathBuf {
+    let name = profile_name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || matches!(c, '-' | '_' | '.') {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+
+    let name = name.trim_matches(['-', '.']);
+
+    root.join(INSTANCES_DIR_NAME).join(if name.is_empty() {
+        profile_id.to_string()
+    } else {
+        format!("{name}-{profile_id}")
+    })
+}
+
+/// Returns `true` if the game directory is the directory shared by all profiles.
+///
+/// Profiles created before the game directories were isolated use it.
+pub fn is_shared_game_dir(game_dir: &Path, root: &Path) -> bool {
+    let normalize = |path: &Path| {
+        path.components()
+            .filter(|component| !matches!(component, Component::CurDir))
+            .collect::<PathBuf>()
+    };
+
+    normalize(game_dir) == normalize(root)
+}
+
+/// Removes the game directory of a deleted profile. Does nothing for the directory shared by all profiles.
+///
+/// Links to the shared folders are removed without touching the folders they point to.
+pub async fn remove_instance_dir(game_dir: &Path, root: &Path) -> anyhow::Result<()> {
+    if is_shared_game_dir(game_dir, root) {
+        return Ok(());
+    }
+
+    match tokio::fs::remove_dir_all(game_dir).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Copies the game files (mods, configs, worlds, options etc.) of the shared directory into `target`.
+///
+/// Files that already exist in `target` are kept, so an interrupted migration can be repeated.
+pub async fn migrate_shared_game_dir(root: PathBuf, target: PathBuf) -> anyhow::Result<()> {
+    tokio::task::spawn_blocking(move || {
+        std::fs::create_dir_all(&target)?;
+
+        for entry in std::fs::read_dir(&root)? {
+            let entry = entry?;
+
+            if SHARED_ENTRIES
+                .iter()
+                .any(|shared| entry.file_name() == *shared)
+            {
+                continue;
+            }
+
+            copy_missing(&entry.path(), &target.join(entry.file_name()))?;
+        }
+
+        Ok(())
+    })
+    .await?
+}
+
+fn copy_missing(from: &Path, to: &Path) -> std::io::Result<()> {
+    if from.is_dir() {
+        std::fs::create_dir_all(to)?;
+
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            copy_missing(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else if !to.exists() {
+        std::fs::copy(from, to)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instance_dir_test() {
+        let root = Path::new(MINECRAFT_DIR);
+
+        assert_eq!(
+            instance_dir(root, 3, "Fabric pack"),
+            root.join("instances").join("Fabric-pack-3")
+        );
+        assert_eq!(
+            instance_dir(root, 4, "../../etc"),
+            root.join("instances").join("etc-4")
+        );
+        assert_eq!(
+            instance_dir(root, 5, "///"),
+            root.join("instances").join("5")
+        );
+
+        let paths = GamePaths::for_profile(root, "1.20.4", 3, "Fabric pack");
+        assert_eq!(paths.libraries, root.join("libraries"));
+        assert_eq!(paths.version, root.join("versions").join("1.20.4"));
+    }
+
+    #[test]
+    fn shared_game_dir_test() {
+        let root = Path::new(MINECRAFT_DIR);
+
+        assert!(is_shared_game_dir(
+            &PathBuf::from("./").join("minecraft"),
+            root
+        ));
+        assert!(is_shared_game_dir(Path::new("minecraft"), root));
+        assert!(!is_shared_game_dir(&instance_dir(root, 0, "Vanilla"), root));
+    }
+
+    #[tokio::test]
+    async fn migrate_test() {
+        let root = std::env::temp_dir().join(format!("nomi-migrate-test-{}", std::process::id()));
+        let target = instance_dir(&root, 0, "Vanilla");
+
+        std::fs::create_dir_all(root.join("mods")).unwrap();
+        std::fs::create_dir_all(root.join("libraries")).unwrap();
+        std::fs::write(root.join("mods").join("sodium.jar"), "sodium").unwrap();
+        std::fs::write(root.join("options.txt"), "fov:0.5").unwrap();
+        std::fs::write(root.join("libraries").join("lib.jar"), "lib").unwrap();
+
+        std::fs::create_dir_all(&target).unwrap();
+        std::fs::write(target.join("options.txt"), "fov:1.0").unwrap();
+
+        migrate_shared_game_dir(root.clone(), target.clone())
+            .await
+            .unwrap();
+
+        assert!(target.join("mods").join("sodium.jar").exists());
+        assert!(!target.join("libraries").exists());
+        assert!(!target.join(INSTANCES_DIR_NAME).exists());
+        assert_eq!(
+            std::fs::read_to_string(target.join("options.txt")).unwrap(),
+            "fov:1.0"
+        );
+
+        remove_instance_dir(&target, &root).await.unwrap();
+        assert!(!target.exists());
+        remove_instance_dir(&target, &root).await.unwrap();
+
+        remove_instance_dir(&root, &root).await.unwrap();
+        assert!(root.join("options.txt").exists());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,486 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+    process::ExitStatus,
+    time::SystemTime,
+};
+
+use tracing::warn;
+
+pub mod analyzer;
+
+const CRASH_REPORTS_DIR: &str = "crash-reports";
+const JVM_ERROR_LOG_PREFIX: &str = "hs_err_pid";
+
+const WALKTHROUGH: &str = "A detailed walkthrough of the error";
+const SYSTEM_DETAILS: &str = "System Details";
+
+/// Keys of the system details that contain the list of the loaded mods.
+const MOD_LIST_KEYS: [&str; 3] = ["Mod List", "Fabric Mods", "Loaded mods"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitReason {
+    Code(i32),
+    /// The process was terminated by a signal (unix only).
+    Signal(i32),
+    Unknown,
+}
+
+impl ExitReason {
+    pub fn from_status(status: ExitStatus) -> Self {
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::ExitStatusExt;
+
+            if let Some(signal) = status.signal() {
+                return Self::Signal(signal);
+            }
+        }
+
+        status.code().map_or(Self::Unknown, Self::Code)
+    }
+
+    pub fn is_success(&self) -> bool {
+        matches!(self, Self::Code(0))
+    }
+}
+
+impl Display for ExitReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExitReason::Code(code) => write!(f, "exit code {code}"),
+            ExitReason::Signal(signal) => write!(f, "signal {signal}"),
+            ExitReason::Unknown => f.write_str("unknown reason"),
+        }
+    }
+}
+
+/// Makes the JVM write its fatal error log into the game directory instead of the working directory.
+pub fn jvm_error_file_argument(game_dir: &Path) -> String {
+    format!(
+        "-XX:ErrorFile={}",
+        game_dir
+            .join(format!("{JVM_ERROR_LOG_PREFIX}%p.log"))
+            .display()
+    )
+}
+
+/// Information about an abnormal exit of the game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crash {
+    pub reason: ExitReason,
+    /// The game's crash report written during this launch.
+    pub report: Option<CrashReport>,
+    /// The JVM's fatal error log written during this launch.
+    pub jvm_error: Option<JvmErrorLog>,
+}
+
+impl Crash {
+    /// Returns `Some` if the game has exited abnormally or has written a crash report.
+    ///
+    /// Only files modified after `started_at` are taken into account.
+    pub async fn detect(
+        reason: ExitReason,
+        game_dir: Option<&Path>,
+        started_at: SystemTime,
+    ) -> Option<Self> {
+        let mut report = None;
+        let mut jvm_error = None;
+
+        if let Some(game_dir) = game_dir {
+            let report_path = newest_file(&game_dir.join(CRASH_REPORTS_DIR), started_at, |name| {
+                name.starts_with("crash-") && has_extension(name, "txt")
+            })
+            .await;
+
+            if let Some(path) = report_path {
+                report = CrashReport::read(&path)
+                    .await
+                    .inspect_err(|e| warn!("Cannot read the crash report {path:?}. Error: {e}"))
+                    .ok();
+            }
+
+            // The JVM is told to write its log there, see [`jvm_error_file_argument`]
+            let log_path = newest_file(game_dir, started_at, |name| {
+                name.starts_with(JVM_ERROR_LOG_PREFIX) && has_extension(name, "log")
+            })
+            .await;
+
+            if let Some(path) = log_path {
+                jvm_error = JvmErrorLog::read(&path)
+                    .await
+                    .inspect_err(|e| warn!("Cannot read the JVM error log {path:?}. Error: {e}"))
+                    .ok();
+            }
+        }
+
+        if reason.is_success() && report.is_none() && jvm_error.is_none() {
+            return None;
+        }
+
+        Some(Self {
+            reason,
+            report,
+            jvm_error,
+        })
+    }
+}
+
+impl Display for Crash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "The game has crashed ({})", self.reason)?;
+
+        if let Some(report) = &self.report {
+            if let Some(description) = &report.description {
+                write!(f, ": {description}")?;
+            }
+            if let Some(exception) = report.stack_trace.lines().next() {
+                write!(f, "\n{exception}")?;
+            }
+            write!(f, "\nCrash report: {}", report.path.display())?;
+        }
+
+        if let Some(jvm_error) = &self.jvm_error {
+            for line in &jvm_error.summary {
+                write!(f, "\n{line}")?;
+            }
+            write!(f, "\nJVM error log: {}", jvm_error.path.display())?;
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CrashReport {
+    pub path: PathBuf,
+    /// The joke line below the header.
+    pub comment: Option<String>,
+    pub time: Option<String>,
+    pub description: Option<String>,
+    /// The exception and its stack trace.
+    pub stack_trace: String,
+    /// `-- Title --` sections of the detailed walkthrough except the system details.
+    pub sections: Vec<CrashSection>,
+    /// `Key: value` pairs of the system details. Multi-line values are joined with `\n`.
+    pub system_details: Vec<(String, String)>,
+    pub mods: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrashSection {
+    pub title: String,
+    pub content: String,
+}
+
+impl CrashReport {
+    pub async fn read(path: &Path) -> std::io::Result<Self> {
+        let content = tokio::fs::read_to_string(path).await?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            ..Self::parse(&content)
+        })
+    }
+
+    pub fn parse(content: &str) -> Self {
+        let mut report = Self::default();
+        let mut lines = content.lines().peekable();
+
+        // Header
+        while let Some(line) = lines.next_if(|line| !line.starts_with("Time:")) {
+            if let Some(comment) = line.trim().strip_prefix("//") {
+                report.comment = Some(comment.trim().to_owned());
+            }
+        }
+
+        while let Some(line) = lines.next_if(|line| !line.trim().is_empty()) {
+            if let Some(time) = line.strip_prefix("Time:") {
+                report.time = Some(time.trim().to_owned());
+            } else if let Some(description) = line.strip_prefix("Description:") {
+                report.description = Some(description.trim().to_owned());
+            }
+        }
+
+        let stack_trace = lines
+            .by_ref()
+            .take_while(|line| !line.starts_with(WALKTHROUGH))
+            .collect::<Vec<_>>();
+        stack_trace
+            .join("\n")
+            .trim()
+            .clone_into(&mut report.stack_trace);
+
+        let mut section: Option<(String, Vec<&str>)> = None;
+        for line in lines {
+            if let Some(title) = section_title(line) {
+                if let Some((title, content)) = section.take() {
+                    report.push_section(title, &content);
+                }
+                section = Some((title.to_owned(), vec![]));
+            } else if let Some((_, content)) = section.as_mut() {
+                content.push(line);
+            }
+        }
+
+        if let Some((title, content)) = section {
+            report.push_section(title, &content);
+        }
+
+        report.mods = report
+            .system_details
+            .iter()
+            .filter(|(key, _)| MOD_LIST_KEYS.contains(&key.as_str()))
+            .flat_map(|(_, value)| value.lines())
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(ToOwned::to_owned)
+            .collect();
+
+        report
+    }
+
+    fn push_section(&mut self, title: String, content: &[&str]) {
+        if title == SYSTEM_DETAILS {
+            self.system_details = parse_details(content);
+        } else {
+            self.sections.push(CrashSection {
+                title,
+                content: content.join("\n").trim().to_owned(),
+            });
+        }
+    }
+}
+
+fn section_title(line: &str) -> Option<&str> {
+    line.strip_prefix("-- ")?.strip_suffix(" --")
+}
+
+/// Parses `\tKey: value` lines. Lines indented deeper are the continuation of the previous value.
+fn parse_details(lines: &[&str]) -> Vec<(String, String)> {
+    let mut details: Vec<(String, String)> = vec![];
+
+    for line in lines {
+        let indent = line.len() - line.trim_start().len();
+        let line = line.trim();
+
+        if line.is_empty() || line == "Details:" {
+            continue;
+        }
+
+        match details.last_mut() {
+            Some((_, value)) if indent > 1 => {
+                if !value.is_empty() {
+                    value.push('\n');
+                }
+                value.push_str(line);
+            }
+            _ => {
+                let (key, value) = line.split_once(':').unwrap_or((line, ""));
+                details.push((key.trim().to_owned(), value.trim().to_owned()));
+            }
+        }
+    }
+
+    details
+}
+
+/// The JVM's `hs_err_pid<pid>.log` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JvmErrorLog {
+    pub path: PathBuf,
+    /// The comment block at the beginning of the file.
+    pub summary: Vec<String>,
+}
+
+impl JvmErrorLog {
+    pub async fn read(path: &Path) -> std::io::Result<Self> {
+        let content = tokio::fs::read_to_string(path).await?;
+
+        Ok(Self {
+            path: path.to_path_buf(),
+            summary: Self::parse_summary(&content),
+        })
+    }
+
+    fn parse_summary(content: &str) -> Vec<String> {
+        content
+            .lines()
+            .skip_while(|line| line.trim().is_empty())
+            .map_while(|line| line.strip_prefix('#'))
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(ToOwned::to_owned)
+            .collect()
+    }
+}
+
+fn has_extension(name: &str, extension: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case(extension))
+}
+
+/// Finds the most recently modified file in `dir` that matches the name and was modified after `since`.
+async fn newest_file(
+    dir: &Path,
+    since: SystemTime,
+    matches: impl Fn(&str) -> bool,
+) -> Option<PathBuf> {
+    let mut entries = tokio::fs::read_dir(dir).await.ok()?;
+    let mut newest: Option<(SystemTime, PathBuf)> = None;
+
+    while let Ok(Some(entry)) = entries.next_entry().await {
+        if !entry.file_name().to_str().is_some_and(&matches) {
+            continue;
+        }
+
+        let Ok(modified) = entry.metadata().await.and_then(|meta| meta.modified()) else {
+            continue;
+        };
+
+        if modified >= since && newest.as_ref().is_none_or(|(time, _)| modified > *time) {
+            newest = Some((modified, entry.path()));
+        }
+    }
+
+    newest.map(|(_, path)| path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REPORT: &str = "---- Minecraft Crash Report ----
+// Who set us up the TNT?
+
+Time: 2024-05-01 12:00:00
+Description: Rendering overlay
+
+java.lang.NullPointerException: Cannot invoke \"Object.toString()\" because \"value\" is null
+\tat net.minecraft.client.gui.Gui.render(Gui.java:42)
+\tat net.minecraft.client.Minecraft.runTick(Minecraft.java:1200)
+
+
+A detailed walkthrough of the error, its code path and all known details is as follows:
+---------------------------------------------------------------------------------------
+
+-- Head --
+Thread: Render thread
+Stacktrace:
+\tat net.minecraft.client.gui.Gui.render(Gui.java:42)
+
+-- System Details --
+Details:
+\tMinecraft Version: 1.20.1
+\tJava Version: 17.0.8, Eclipse Adoptium
+\tFabric Mods:
+\t\tfabric-api: Fabric API 0.92.0+1.20.1
+\t\tfabricloader: Fabric Loader 0.15.11
+\tLaunched Version: fabric-loader-0.15.11-1.20.1
+";
+
+    const JVM_ERROR: &str = "#
+# A fatal error has been detected by the Java Runtime Environment:
+#
+#  SIGSEGV (0xb) at pc=0x00007f1c2a1b2c3d, pid=12345, tid=12346
+#
+# Problematic frame:
+# C  [libGL.so.1+0x1234]
+#
+
+---------------  S U M M A R Y ------------
+";
+
+    #[test]
+    fn parse_report_test() {
+        let report = CrashReport::parse(REPORT);
+
+        assert_eq!(report.comment.as_deref(), Some("Who set us up the TNT?"));
+        assert_eq!(report.time.as_deref(), Some("2024-05-01 12:00:00"));
+        assert_eq!(report.description.as_deref(), Some("Rendering overlay"));
+        assert!(report
+            .stack_trace
+            .starts_with("java.lang.NullPointerException"));
+        assert_eq!(report.stack_trace.lines().count(), 3);
+
+        assert_eq!(report.sections.len(), 1);
+        assert_eq!(report.sections[0].title, "Head");
+
+        assert_eq!(
+            report.system_details[0],
+            ("Minecraft Version".to_owned(), "1.20.1".to_owned())
+        );
+        assert_eq!(report.system_details.len(), 4);
+        assert_eq!(
+            report.mods,
+            [
+                "fabric-api: Fabric API 0.92.0+1.20.1",
+                "fabricloader: Fabric Loader 0.15.11"
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_jvm_error_test() {
+        let summary = JvmErrorLog::parse_summary(JVM_ERROR);
+
+        assert_eq!(
+            summary[0],
+            "A fatal error has been detected by the Java Runtime Environment:"
+        );
+        assert_eq!(summary[3], "C  [libGL.so.1+0x1234]");
+    }
+
+    #[tokio::test]
+    async fn detect_test() {
+        let game_dir = std::env::temp_dir().join(format!("nomi-crash-test-{}", std::process::id()));
+        let reports = game_dir.join(CRASH_REPORTS_DIR);
+        std::fs::create_dir_all(&reports).unwrap();
+
+        let started_at = SystemTime::now() - std::time::Duration::from_secs(1);
+
+        assert!(
+            Crash::detect(ExitReason::Code(0), Some(&game_dir), started_at)
+                .await
+                .is_none()
+        );
+
+        let crash = Crash::detect(ExitReason::Code(1), Some(&game_dir), started_at)
+            .await
+            .unwrap();
+        assert!(crash.report.is_none());
+
+        let path = reports.join("crash-2024-05-01_12.00.00-client.txt");
+        std::fs::write(&path, REPORT).unwrap();
+
+        let crash = Crash::detect(ExitReason::Code(-1), Some(&game_dir), started_at)
+            .await
+            .unwrap();
+        let report = crash.report.unwrap();
+        assert_eq!(report.path, path);
+        assert_eq!(report.description.as_deref(), Some("Rendering overlay"));
+        assert!(crash.jvm_error.is_none());
+
+        // Left by an earlier session
+        let stale = std::fs::File::create(game_dir.join("hs_err_pid1.log")).unwrap();
+        stale
+            .set_modified(started_at - std::time::Duration::from_secs(60))
+            .unwrap();
+
+        let crash = Crash::detect(ExitReason::Code(1), Some(&game_dir), started_at)
+            .await
+            .unwrap();
+        assert!(crash.jvm_error.is_none());
+
+        let path = game_dir.join("hs_err_pid2.log");
+        std::fs::write(&path, "#\n# A fatal error has been detected\n#\n").unwrap();
+
+        let crash = Crash::detect(ExitReason::Code(134), Some(&game_dir), started_at)
+            .await
+            .unwrap();
+        assert_eq!(crash.jvm_error.unwrap().path, path);
+
+        std::fs::remove_dir_all(game_dir).unwrap();
+    }
+}
@@ -0,0 +1,287 @@
+use std::{collections::HashSet, path::Path};
+
+use regex::{Captures, Regex};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::DOT_NOMI_CRASH_RULES_CONFIG;
+
+use super::Crash;
+
+const KNOWN_PROBLEMS: &str = include_str!("known_problems.toml");
+
+/// Number of lines at the end of `latest.log` checked by the analyzer.
+pub const LOG_TAIL_LINES: usize = 500;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CrashRule {
+    pub id: String,
+    /// Once a rule of the group has matched, the following rules of the same group are skipped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Regular expression. Its named groups can be used in `diagnosis` and `fix` as `{name}`.
+    pub pattern: String,
+    pub diagnosis: String,
+    pub fix: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+struct CrashRulesFile {
+    #[serde(default)]
+    rule: Vec<CrashRule>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnosis {
+    pub rule_id: String,
+    pub diagnosis: String,
+    pub fix: String,
+    /// The text matched by the rule.
+    pub evidence: String,
+}
+
+/// Matches crash reports and logs against a set of known problems.
+#[derive(Debug, Clone, Default)]
+pub struct CrashAnalyzer {
+    rules: Vec<(CrashRule, Regex)>,
+}
+
+impl CrashAnalyzer {
+    /// Creates an analyzer with the rules bundled with Nomi.
+    pub fn builtin() -> anyhow::Result<Self> {
+        Self::from_toml(KNOWN_PROBLEMS)
+    }
+
+    /// Creates an analyzer with the bundled rules preceded by the user's rules
+    /// from [`DOT_NOMI_CRASH_RULES_CONFIG`] if the file exists.
+    pub async fn load() -> anyhow::Result<Self> {
+        let mut analyzer = Self::default();
+
+        match tokio::fs::read_to_string(DOT_NOMI_CRASH_RULES_CONFIG).await {
+            Ok(content) => analyzer.extend(Self::from_toml(&content)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Cannot read {DOT_NOMI_CRASH_RULES_CONFIG}. Error: {e}"),
+        }
+
+        analyzer.extend(Self::builtin()?);
+
+        Ok(analyzer)
+    }
+
+    pub fn from_toml(content: &str) -> anyhow::Result<Self> {
+        let file = toml::from_str::<CrashRulesFile>(content)?;
+        Self::from_rules(file.rule)
+    }
+
+    pub fn from_rules(rules: Vec<CrashRule>) -> anyhow::Result<Self> {
+        let rules = rules
+            .into_iter()
+            .map(|rule| {
+                Regex::new(&rule.pattern)
+                    .map(|regex| (rule, regex))
+                    .map_err(|e| anyhow::anyhow!("Invalid pattern of the crash rule. Error: {e}"))
+            })
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self { rules })
+    }
+
+    /// Appends the rules of `other` after the rules of `self`.
+    pub fn extend(&mut self, other: Self) {
+        self.rules.extend(other.rules);
+    }
+
+    pub fn rules(&self) -> impl Iterator<Item = &CrashRule> {
+        self.rules.iter().map(|(rule, _)| rule)
+    }
+
+    /// Analyzes the crash and the tail of the game's log.
+    pub fn analyze(&self, crash: &Crash, log: &str) -> Vec<Diagnosis> {
+        let mut text = String::new();
+
+        if let Some(report) = &crash.report {
+            for part in report
+                .description
+                .iter()
+                .chain(std::iter::once(&report.stack_trace))
+                .chain(report.sections.iter().map(|section| &section.content))
+            {
+                text.push_str(part);
+                text.push('\n');
+            }
+        }
+
+        if let Some(jvm_error) = &crash.jvm_error {
+            for line in &jvm_error.summary {
+                text.push_str(line);
+                text.push('\n');
+            }
+        }
+
+        text.push_str(log);
+
+        self.analyze_text(&text)
+    }
+
+    pub fn analyze_text(&self, text: &str) -> Vec<Diagnosis> {
+        let mut found = Vec::new();
+        let mut seen = HashSet::new();
+        let mut matched_groups = HashSet::new();
+
+        for (rule, regex) in &self.rules {
+            if rule
+                .group
+                .as_ref()
+                .is_some_and(|group| matched_groups.contains(group))
+            {
+                continue;
+            }
+
+            for captures in regex.captures_iter(text) {
+                let diagnosis = Diagnosis {
+                    rule_id: rule.id.clone(),
+                    diagnosis: fill(&rule.diagnosis, regex, &captures),
+                    fix: fill(&rule.fix, regex, &captures),
+                    evidence: captures[0].to_owned(),
+                };
+
+                if let Some(group) = &rule.group {
+                    matched_groups.insert(group.clone());
+                }
+
+                if seen.insert((diagnosis.diagnosis.clone(), diagnosis.fix.clone())) {
+                    found.push(diagnosis);
+                }
+            }
+        }
+
+        found
+    }
+}
+
+/// Replaces `{name}` with the named groups of the match.
+fn fill(template: &str, regex: &Regex, captures: &Captures<'_>) -> String {
+    regex
+        .capture_names()
+        .flatten()
+        .fold(template.to_owned(), |text, name| {
+            let value = captures.name(name).map_or("", |value| value.as_str());
+            text.replace(&format!("{{{name}}}"), value)
+        })
+}
+
+/// Reads the last `lines` lines of `<game_dir>/logs/latest.log`.
+pub async fn read_log_tail(game_dir: &Path, lines: usize) -> anyhow::Result<String> {
+    let content = tokio::fs::read_to_string(game_dir.join("logs").join("latest.log")).await?;
+
+    let skip = content.lines().count().saturating_sub(lines);
+    Ok(content.lines().skip(skip).collect::<Vec<_>>().join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instance::crash::{CrashReport, ExitReason};
+
+    use super::*;
+
+    fn diagnose(text: &str) -> Vec<String> {
+        CrashAnalyzer::builtin()
+            .unwrap()
+            .analyze_text(text)
+            .into_iter()
+            .map(|diagnosis| diagnosis.rule_id)
+            .collect()
+    }
+
+    #[test]
+    fn builtin_rules_test() {
+        let analyzer = CrashAnalyzer::builtin().unwrap();
+        assert!(analyzer.rules().count() > 0);
+    }
+
+    #[test]
+    fn java_version_test() {
+        let log = "java.lang.UnsupportedClassVersionError: net/minecraft/client/main/Main has been \
+                   compiled by a more recent version of the Java Runtime (class file version 65.0), \
+                   this version of the Java Runtime only recognizes class file versions up to 61.0";
+
+        assert_eq!(diagnose(log), ["java-version-21"]);
+
+        let log = log.replace("65.0", "66.0");
+        let diagnoses = CrashAnalyzer::builtin().unwrap().analyze_text(&log);
+        assert_eq!(diagnoses.len(), 1);
+        assert!(diagnoses[0].diagnosis.contains("class file version 66"));
+        assert!(diagnoses[0].diagnosis.contains("(61)"));
+    }
+
+    #[test]
+    fn fabric_missing_dependency_test() {
+        let log = "net.fabricmc.loader.impl.FormattedException: Mod resolution encountered an incompatible mod set!
+A potential solution has been determined:
+\t - Install fabric-api, any version.
+Unmet dependency listing:
+\t - Mod 'Sodium Extra' (sodium-extra) 0.5.1 requires any version of fabric-api, which is missing!
+\t - Mod 'Iris' (iris) 1.6.4 requires any version of fabric-api, which is missing!";
+
+        let diagnoses = CrashAnalyzer::builtin().unwrap().analyze_text(log);
+        assert_eq!(diagnoses.len(), 2);
+        assert_eq!(
+            diagnoses[0].diagnosis,
+            "Sodium Extra requires fabric-api which is not installed."
+        );
+        assert_eq!(
+            diagnoses[0].fix,
+            "Install fabric-api into the profile's `mods` folder."
+        );
+    }
+
+    #[test]
+    fn analyze_crash_test() {
+        let crash = Crash {
+            reason: ExitReason::Code(-1),
+            report: Some(CrashReport {
+                description: Some("Initializing game".into()),
+                stack_trace: "java.lang.OutOfMemoryError: Java heap space".into(),
+                ..Default::default()
+            }),
+            jvm_error: None,
+        };
+
+        let analyzer = CrashAnalyzer::builtin().unwrap();
+        let diagnoses = analyzer.analyze(
+            &crash,
+            "[12:00:00] [main/WARN]: Mixin apply for mod sodium failed sodium.mixins.json",
+        );
+
+        let ids = diagnoses
+            .iter()
+            .map(|diagnosis| diagnosis.rule_id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["mixin-apply-failed", "out-of-memory"]);
+        assert!(diagnoses[0].fix.starts_with("Update sodium"));
+    }
+
+    #[test]
+    fn custom_rules_test() {
+        let mut analyzer = CrashAnalyzer::from_toml(
+            r#"
+[[rule]]
+id = "custom"
+pattern = 'Broken mod (?P<mod>\w+)'
+diagnosis = "{mod} is broken"
+fix = "Remove {mod}"
+"#,
+        )
+        .unwrap();
+        analyzer.extend(CrashAnalyzer::builtin().unwrap());
+
+        let diagnoses = analyzer.analyze_text("Broken mod foo");
+        assert_eq!(diagnoses[0].diagnosis, "foo is broken");
+        assert_eq!(diagnoses[0].fix, "Remove foo");
+
+        assert!(CrashAnalyzer::from_toml(
+            "[[rule]]\nid = \"a\"\npattern = '('\ndiagnosis = \"\"\nfix = \"\""
+        )
+        .is_err());
+    }
+}
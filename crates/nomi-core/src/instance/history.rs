@@ -0,0 +1,241 @@
+use std::{
+    collections::{BTreeMap, VecDeque},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::fs::write_json_config;
+
+/// Number of launches kept for every profile. The playtime counts all of them.
+pub const MAX_LAUNCHES_PER_PROFILE: usize = 50;
+
+/// A finished launch of a profile.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LaunchRecord {
+    pub started_at: SystemTime,
+    pub ended_at: SystemTime,
+    /// `None` if the game was terminated by a signal.
+    pub exit_code: Option<i32>,
+    /// Java command or path the game was launched with.
+    pub java: String,
+}
+
+impl LaunchRecord {
+    pub fn duration(&self) -> Duration {
+        self.ended_at
+            .duration_since(self.started_at)
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileHistory {
+    /// Total time played, including the launches that are no longer kept.
+    pub playtime: Duration,
+    pub launch_count: usize,
+    /// The latest launches, the newest one is the last.
+    pub launches: VecDeque<LaunchRecord>,
+}
+
+impl ProfileHistory {
+    pub fn last_played(&self) -> Option<SystemTime> {
+        self.launches.back().map(|launch| launch.ended_at)
+    }
+
+    fn push(&mut self, record: LaunchRecord) {
+        self.playtime += record.duration();
+        self.launch_count += 1;
+
+        self.launches.push_back(record);
+        while self.launches.len() > MAX_LAUNCHES_PER_PROFILE {
+            self.launches.pop_front();
+        }
+    }
+}
+
+/// Launch history of all profiles.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct LaunchHistory {
+    profiles: BTreeMap<usize, ProfileHistory>,
+}
+
+impl LaunchHistory {
+    pub fn profile(&self, profile_id: usize) -> Option<&ProfileHistory> {
+        self.profiles.get(&profile_id)
+    }
+
+    pub fn playtime(&self, profile_id: usize) -> Duration {
+        self.profile(profile_id)
+            .map(|history| history.playtime)
+            .unwrap_or_default()
+    }
+
+    pub fn last_played(&self, profile_id: usize) -> Option<SystemTime> {
+        self.profile(profile_id)
+            .and_then(ProfileHistory::last_played)
+    }
+
+    pub fn record(&mut self, profile_id: usize, record: LaunchRecord) {
+        self.profiles.entry(profile_id).or_default().push(record);
+    }
+
+    pub fn remove(&mut self, profile_id: usize) -> Option<ProfileHistory> {
+        self.profiles.remove(&profile_id)
+    }
+}
+
+/// [`LaunchHistory`] kept in memory and saved to a JSON file after every change.
+///
+/// Cloning the store is cheap and all clones share the same history.
+#[derive(Debug, Clone)]
+pub struct HistoryStore {
+    path: PathBuf,
+    history: Arc<Mutex<LaunchHistory>>,
+    /// Keeps the saves in the same order as the changes.
+    save_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl HistoryStore {
+    /// Reads the history from `path`. A missing or broken file gives an empty history.
+    pub fn load(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+
+        let history = match std::fs::read_to_string(&path) {
+            Ok(s) => serde_json::from_str(&s).unwrap_or_else(|e| {
+                warn!(
+                    "Cannot parse the launch history {}. Error: {e}",
+                    path.display()
+                );
+                LaunchHistory::default()
+            }),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => LaunchHistory::default(),
+            Err(e) => {
+                warn!(
+                    "Cannot read the launch history {}. Error: {e}",
+                    path.display()
+                );
+                LaunchHistory::default()
+            }
+        };
+
+        Self {
+            path,
+            history: Arc::new(Mutex::new(history)),
+            save_lock: Arc::default(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn snapshot(&self) -> LaunchHistory {
+        self.history().clone()
+    }
+
+    pub fn playtime(&self, profile_id: usize) -> Duration {
+        self.history().playtime(profile_id)
+    }
+
+    pub fn last_played(&self, profile_id: usize) -> Option<SystemTime> {
+        self.history().last_played(profile_id)
+    }
+
+    pub async fn record(&self, profile_id: usize, record: LaunchRecord) -> anyhow::Result<()> {
+        self.update(|history| history.record(profile_id, record))
+            .await
+    }
+
+    /// Forgets the profile, e.g. after it has been deleted, so a new profile with the same id starts from scratch.
+    pub async fn remove_profile(&self, profile_id: usize) -> anyhow::Result<()> {
+        self.update(|history| {
+            history.remove(profile_id);
+        })
+        .await
+    }
+
+    async fn update(&self, change: impl FnOnce(&mut LaunchHistory)) -> anyhow::Result<()> {
+        let _guard = self.save_lock.lock().await;
+
+        let history = {
+            let mut history = self.history();
+            change(&mut history);
+            history.clone()
+        };
+
+        write_json_config(&history, &self.path).await
+    }
+
+    fn history(&self) -> std::sync::MutexGuard<'_, LaunchHistory> {
+        // The history is always left in a consistent state so a poisoned lock is fine
+        self.history
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(started_at: u64, minutes: u64) -> LaunchRecord {
+        let started_at = SystemTime::UNIX_EPOCH + Duration::from_secs(started_at);
+
+        LaunchRecord {
+            started_at,
+            ended_at: started_at + Duration::from_secs(minutes * 60),
+            exit_code: Some(0),
+            java: "java".into(),
+        }
+    }
+
+    #[test]
+    fn playtime_test() {
+        let mut history = LaunchHistory::default();
+
+        for index in 0..=MAX_LAUNCHES_PER_PROFILE as u64 {
+            history.record(1, record(index * 3600, 30));
+        }
+        history.record(2, record(0, 5));
+
+        let profile = history.profile(1).unwrap();
+        assert_eq!(profile.launches.len(), MAX_LAUNCHES_PER_PROFILE);
+        assert_eq!(profile.launch_count, MAX_LAUNCHES_PER_PROFILE + 1);
+        assert_eq!(
+            history.playtime(1),
+            Duration::from_secs(30 * 60 * (MAX_LAUNCHES_PER_PROFILE as u64 + 1))
+        );
+        assert_eq!(
+            history.last_played(1),
+            Some(
+                SystemTime::UNIX_EPOCH
+                    + Duration::from_secs(MAX_LAUNCHES_PER_PROFILE as u64 * 3600 + 30 * 60)
+            )
+        );
+
+        assert_eq!(history.playtime(3), Duration::ZERO);
+        assert!(history.last_played(3).is_none());
+    }
+
+    #[tokio::test]
+    async fn store_test() {
+        let path =
+            std::env::temp_dir().join(format!("nomi-history-test-{}.json", std::process::id()));
+
+        let store = HistoryStore::load(&path);
+        store.record(1, record(0, 10)).await.unwrap();
+        store.record(2, record(0, 20)).await.unwrap();
+        store.remove_profile(2).await.unwrap();
+
+        let loaded = HistoryStore::load(&path);
+        assert_eq!(loaded.snapshot(), store.snapshot());
+        assert_eq!(loaded.playtime(1), Duration::from_secs(600));
+        assert!(loaded.snapshot().profile(2).is_none());
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -1,7 +1,9 @@
 use std::{
+    collections::BTreeSet,
     fs::{File, OpenOptions},
     io,
-    path::{Path, PathBuf},
+    path::PathBuf,
+    time::SystemTime,
 };
 
 use arguments::UserData;
@@ -10,19 +12,41 @@ use tokio::process::Command;
 use tracing::{debug, info, trace, warn};
 
 use crate::{
+    configs::profile::Loader,
     downloads::Assets,
     fs::read_json_config,
+    game_paths::shared_folders_dir,
     repository::{
         java_runner::JavaRunner,
         manifest::{Manifest, VersionType},
     },
 };
 
-use self::arguments::ArgumentsBuilder;
+use self::{
+    argfile::{write_argfile, ARGFILE_NAME, MIN_ARGFILE_JAVA_VERSION},
+    arguments::ArgumentsBuilder,
+    command::LaunchCommand,
+    hooks::{HookContext, LaunchHooks},
+    memory::{is_heap_argument, HeapSize, MemoryMode, ProfileInfo, SystemMemory},
+    natives::NativeLibrary,
+    quick_play::QuickPlay,
+};
 
-use super::{profile::LoaderProfile, Undefined};
+use super::{
+    crash::{jvm_error_file_argument, Crash, ExitReason},
+    profile::LoaderProfile,
+    shared_folders::{link_shared_folders, SharedFolder},
+    Undefined,
+};
 
+pub mod argfile;
 pub mod arguments;
+pub mod command;
+pub mod hooks;
+pub mod memory;
+pub mod natives;
+pub mod placeholders;
+pub mod quick_play;
 pub mod rules;
 
 #[cfg(windows)]
@@ -43,6 +67,45 @@ pub struct LaunchSettings {
 
     pub version: String,
     pub version_type: VersionType,
+
+    #[serde(default)]
+    pub memory: MemoryMode,
+    /// `None` means the global window settings are used.
+    #[serde(default)]
+    pub window: Option<WindowSettings>,
+    /// Name of the profile the instance belongs to, available to the hooks.
+    #[serde(default)]
+    pub profile_name: Option<String>,
+    #[serde(default)]
+    pub hooks: LaunchHooks,
+    /// Passes the JVM arguments through an `@argfile` in the game directory on Java 9+.
+    #[serde(default)]
+    pub use_argfile: bool,
+    /// Folders linked to the ones shared by all profiles. Checked and repaired at every launch.
+    #[serde(default)]
+    pub shared_folders: BTreeSet<SharedFolder>,
+}
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Debug, Clone, Copy)]
+pub struct WindowSettings {
+    /// `None` means the game's default size.
+    pub resolution: Option<Resolution>,
+    pub fullscreen: bool,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Debug, Clone, Copy)]
+pub struct Resolution {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Default for Resolution {
+    fn default() -> Self {
+        Self {
+            width: 854,
+            height: 480,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, Clone)]
@@ -61,7 +124,7 @@ impl LaunchInstance {
         delete_assets: bool,
     ) -> anyhow::Result<()> {
         let manifest = read_json_config::<Manifest>(&self.settings.manifest_file).await?;
-        let arguments_builder = ArgumentsBuilder::new(self, &manifest).with_classpath();
+        let arguments_builder = ArgumentsBuilder::new(self, &manifest).with_classpath()?;
 
         if delete_client {
             let _ = tokio::fs::remove_file(&self.settings.version_jar_file)
@@ -118,98 +181,289 @@ impl LaunchInstance {
         self.loader_profile.as_ref()
     }
 
-    fn process_natives(&self, natives: &[PathBuf]) -> anyhow::Result<()> {
-        for lib in natives {
-            let reader = OpenOptions::new().read(true).open(lib)?;
-            std::fs::create_dir_all(&self.settings.natives_dir)?;
+    /// Values of the hook placeholders. The version name is used if the profile name is unknown.
+    pub fn hook_context(&self) -> HookContext {
+        HookContext {
+            profile_name: self
+                .settings
+                .profile_name
+                .clone()
+                .unwrap_or_else(|| self.settings.version.clone()),
+            version: self.settings.version.clone(),
+            game_dir: self.settings.game_dir.clone(),
+            natives_dir: self.settings.natives_dir.clone(),
+            exit_code: None,
+        }
+    }
 
-            let mut archive = zip::ZipArchive::new(reader)?;
+    /// Picks the heap size if [`MemoryMode::Auto`] is selected.
+    fn heap_size(&self) -> Option<HeapSize> {
+        if self.settings.memory != MemoryMode::Auto {
+            return None;
+        }
 
-            let mut names = vec![];
-            archive
-                .file_names()
-                .map(String::from)
-                .for_each(|el| names.push(el));
+        let memory = SystemMemory::read()
+            .inspect_err(|e| {
+                warn!("Cannot read the system memory, using the custom JVM arguments. Error: {e}");
+            })
+            .ok()?;
+
+        let vanilla = Loader::Vanilla;
+        let loader = self
+            .loader_profile
+            .as_ref()
+            .map_or(&vanilla, |profile| &profile.loader);
+
+        let heap = HeapSize::pick(
+            memory,
+            &ProfileInfo::new(loader, &self.settings.game_dir, &self.settings.version),
+        );
+
+        info!(
+            "Automatically picked heap size: -Xms{}M -Xmx{}M. Reason: {}",
+            heap.min, heap.max, heap.reason
+        );
+
+        Some(heap)
+    }
 
-            names
-                .into_iter()
-                .filter(|l| {
-                    let path = Path::new(l).extension();
+    fn process_natives(&self, natives: &[NativeLibrary]) -> anyhow::Result<()> {
+        const NATIVE_EXTENSIONS: [&str; 4] = ["dll", "so", "dylib", "jnilib"];
 
-                    let check = |expected_ext| {
-                        path.map_or(false, |ext| ext.eq_ignore_ascii_case(expected_ext))
-                    };
+        std::fs::create_dir_all(&self.settings.natives_dir)?;
 
-                    check("dll") || check("so") || check("dylib")
-                })
-                .try_for_each(|lib| {
-                    let mut file = archive.by_name(&lib)?;
-                    let mut out = File::create(self.settings.natives_dir.join(lib))?;
-                    io::copy(&mut file, &mut out)?;
+        for lib in natives {
+            let reader = OpenOptions::new().read(true).open(&lib.path)?;
+            let mut archive = zip::ZipArchive::new(reader)?;
 
-                    Ok::<_, anyhow::Error>(())
-                })?;
+            for index in 0..archive.len() {
+                let mut file = archive.by_index(index)?;
+
+                // Protects from paths like `../../file`
+                let Some(name) = file.enclosed_name() else {
+                    continue;
+                };
+
+                if file.is_dir() {
+                    continue;
+                }
+
+                let is_extracted = match &lib.exclude {
+                    Some(exclude) => {
+                        let name = name.to_string_lossy().replace('\\', "/");
+                        !exclude.iter().any(|prefix| name.starts_with(prefix))
+                    }
+                    None => name.extension().is_some_and(|ext| {
+                        NATIVE_EXTENSIONS
+                            .iter()
+                            .any(|expected| ext.eq_ignore_ascii_case(expected))
+                    }),
+                };
+
+                if !is_extracted {
+                    continue;
+                }
+
+                let target = self.settings.natives_dir.join(&name);
+                if let Some(parent) = target.parent() {
+                    std::fs::create_dir_all(parent)?;
+                }
+
+                let mut out = File::create(target)?;
+                io::copy(&mut file, &mut out)?;
+            }
         }
 
         Ok(())
     }
 
-    pub async fn launch(
+    /// Builds the command that starts the game.
+    ///
+    /// Native libraries are extracted as part of it.
+    pub async fn command(
         &self,
         user_data: UserData,
         java_runner: &JavaRunner,
+        quick_play: Option<QuickPlay>,
+    ) -> anyhow::Result<Command> {
+        let (mut launch_command, natives) = self
+            .resolve_command(user_data, java_runner, quick_play)
+            .await?;
+
+        self.process_natives(&natives)?;
+
+        link_shared_folders(
+            &self.settings.game_dir,
+            &shared_folders_dir(),
+            self.settings.shared_folders.iter().copied(),
+        )?;
+
+        if self.settings.use_argfile {
+            self.move_to_argfile(&mut launch_command, java_runner)
+                .await?;
+        }
+
+        Ok(launch_command.to_command())
+    }
+
+    /// Replaces the JVM arguments with an `@argfile` if the runtime supports it.
+    async fn move_to_argfile(
+        &self,
+        launch_command: &mut LaunchCommand,
+        java_runner: &JavaRunner,
     ) -> anyhow::Result<()> {
-        let manifest = read_json_config::<Manifest>(&self.settings.manifest_file).await?;
+        match java_runner.major_version().await {
+            Ok(version) if version >= MIN_ARGFILE_JAVA_VERSION => {}
+            Ok(version) => {
+                info!("Java {version} does not support argument files, passing the JVM arguments inline");
+                return Ok(());
+            }
+            Err(e) => {
+                warn!("Cannot get the Java version, passing the JVM arguments inline. Error: {e}");
+                return Ok(());
+            }
+        }
 
-        let arguments_builder = ArgumentsBuilder::new(self, &manifest)
-            .with_classpath()
-            .with_userdata(user_data);
+        let argfile = write_argfile(&self.argfile_path(), &launch_command.jvm_arguments).await?;
 
-        self.process_natives(arguments_builder.get_native_libs())?;
+        launch_command.jvm_arguments = vec![argfile];
 
-        let custom_jvm_arguments = arguments_builder.custom_jvm_arguments();
-        let manifest_jvm_arguments = arguments_builder.manifest_jvm_arguments();
-        let manifest_game_arguments = arguments_builder.manifest_game_arguments();
+        Ok(())
+    }
 
-        let main_class = arguments_builder.get_main_class();
+    fn argfile_path(&self) -> PathBuf {
+        self.settings.game_dir.join(ARGFILE_NAME)
+    }
 
-        let loader_arguments = arguments_builder.loader_arguments();
+    /// Removes the argument file written by [`Self::command`]. Call it once the game has exited.
+    pub async fn remove_argfile(&self) {
+        match tokio::fs::remove_file(self.argfile_path()).await {
+            Ok(()) => {}
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => warn!("Cannot remove the argument file. Error: {e}"),
+        }
+    }
 
-        let loader_jvm_arguments = loader_arguments.jvm_arguments();
-        let loader_game_arguments = loader_arguments.game_arguments();
+    /// Resolves the command [`Self::command`] would run without starting the game.
+    ///
+    /// With `redact_token` set, the access token is replaced with [`command::REDACTED`].
+    /// Native libraries are not extracted, so the command only works after the profile has been launched once.
+    pub async fn launch_command(
+        &self,
+        user_data: UserData,
+        java_runner: &JavaRunner,
+        quick_play: Option<QuickPlay>,
+        redact_token: bool,
+    ) -> anyhow::Result<LaunchCommand> {
+        let token = user_data.access_token.clone().filter(|_| redact_token);
 
-        let mut child = Command::new(java_runner.get())
-            .args(custom_jvm_arguments)
-            .args(loader_jvm_arguments)
-            .args(dbg!(manifest_jvm_arguments))
-            .arg(main_class)
-            .args(dbg!(manifest_game_arguments))
-            .args(loader_game_arguments)
-            .spawn()?;
+        let (mut launch_command, _) = self
+            .resolve_command(user_data, java_runner, quick_play)
+            .await?;
 
-        child
-            .wait()
-            .await?
-            .code()
-            .inspect(|code| info!("Minecraft exit code: {}", code));
+        if let Some(token) = token {
+            launch_command.redact(&token);
+        }
 
-        Ok(())
+        Ok(launch_command)
     }
-}
 
-pub mod macros {
-    macro_rules! replace {
-        (
-            $initial:ident,
-            $($name:literal => $value:expr),+
-        ) => {
-            $initial
-            $(
-               .replace($name, $value)
-            )+
+    async fn resolve_command(
+        &self,
+        user_data: UserData,
+        java_runner: &JavaRunner,
+        quick_play: Option<QuickPlay>,
+    ) -> anyhow::Result<(LaunchCommand, Vec<NativeLibrary>)> {
+        let manifest = read_json_config::<Manifest>(&self.settings.manifest_file).await?;
+
+        let arguments_builder = ArgumentsBuilder::new(self, &manifest)
+            .with_classpath()?
+            .with_userdata(user_data)
+            .with_quick_play(quick_play);
+
+        let heap_size = self.heap_size();
+
+        let custom_jvm_arguments = arguments_builder
+            .custom_jvm_arguments()
+            .iter()
+            .filter(|arg| heap_size.is_none() || !is_heap_argument(arg))
+            .cloned();
+
+        let jvm_arguments = std::iter::once(jvm_error_file_argument(&self.settings.game_dir))
+            .chain(heap_size.iter().flat_map(HeapSize::to_arguments))
+            .chain(custom_jvm_arguments)
+            .chain(arguments_builder.loader_jvm_arguments())
+            .chain(arguments_builder.manifest_jvm_arguments())
+            .collect();
+
+        let game_arguments = arguments_builder
+            .manifest_game_arguments()
+            .into_iter()
+            .chain(arguments_builder.loader_game_arguments())
+            .collect();
+
+        let hook_context = self.hook_context();
+
+        let launch_command = LaunchCommand {
+            working_dir: std::env::current_dir()?,
+            env: self.settings.hooks.env(&hook_context)?,
+            wrapper: self.settings.hooks.wrapper(&hook_context),
+            program: java_runner.get_string(),
+            jvm_arguments,
+            main_class: arguments_builder.get_main_class().to_owned(),
+            game_arguments,
+        };
+
+        Ok((launch_command, arguments_builder.get_native_libs().to_vec()))
+    }
+
+    pub async fn launch(
+        &self,
+        user_data: UserData,
+        java_runner: &JavaRunner,
+        quick_play: Option<QuickPlay>,
+    ) -> anyhow::Result<Option<Crash>> {
+        let mut command = self.command(user_data, java_runner, quick_play).await?;
+
+        let hook_context = self.hook_context();
+        let started = async {
+            self.settings.hooks.run_pre_launch(&hook_context).await?;
+            Ok::<_, anyhow::Error>((SystemTime::now(), command.spawn()?))
         };
+
+        let (started_at, mut child) = match started.await {
+            Ok(started) => started,
+            Err(e) => {
+                self.remove_argfile().await;
+                return Err(e);
+            }
+        };
+
+        let status = child.wait().await;
+        self.remove_argfile().await;
+        let status = status?;
+        status
+            .code()
+            .inspect(|code| info!("Minecraft exit code: {}", code));
+
+        self.settings
+            .hooks
+            .run_post_exit(&hook_context.with_exit_code(status.code()))
+            .await;
+
+        let crash = Crash::detect(
+            ExitReason::from_status(status),
+            Some(&self.settings.game_dir),
+            started_at,
+        )
+        .await;
+
+        if let Some(crash) = &crash {
+            warn!("{crash}");
+        }
+
+        Ok(crash)
     }
-    pub(crate) use replace;
 }
 
 #[derive(Default)]
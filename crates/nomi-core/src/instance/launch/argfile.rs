@@ -0,0 +1,60 @@
+use std::{fmt::Write, path::Path};
+
+use crate::fs::write_to_file;
+
+/// The first Java version that reads arguments from `@argfile`s.
+pub const MIN_ARGFILE_JAVA_VERSION: u32 = 9;
+
+/// Name of the argument file inside the game directory of the profile.
+///
+/// The version directory is shared by the profiles of the same version, so the file is not kept there.
+pub const ARGFILE_NAME: &str = "jvm_arguments.txt";
+
+/// Formats the arguments as a Java argument file.
+///
+/// Every argument is quoted on its own line, so paths with spaces and backslashes are kept as is.
+pub fn to_argfile(arguments: &[String]) -> String {
+    let mut argfile = String::new();
+
+    for argument in arguments {
+        let escaped = argument
+            .replace('\\', r"\\")
+            .replace('"', r#"\""#)
+            .replace('\n', r"\n")
+            .replace('\r', r"\r");
+        let _ = writeln!(argfile, "\"{escaped}\"");
+    }
+
+    argfile
+}
+
+/// Writes the arguments to `path` and returns the argument that makes java read them.
+pub async fn write_argfile(path: &Path, arguments: &[String]) -> anyhow::Result<String> {
+    write_to_file(to_argfile(arguments).as_bytes(), path).await?;
+
+    Ok(format!("@{}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn argfile_test() {
+        let arguments = [
+            "-Xmx2G".to_owned(),
+            "-cp".to_owned(),
+            r"C:\Users\Some User\lib.jar;C:\a.jar".to_owned(),
+            r#"-Dquoted="value""#.to_owned(),
+        ];
+
+        assert_eq!(
+            to_argfile(&arguments),
+            r#""-Xmx2G"
+"-cp"
+"C:\\Users\\Some User\\lib.jar;C:\\a.jar"
+"-Dquoted=\"value\""
+"#
+        );
+    }
+}
@@ -1,21 +1,22 @@
 use std::{marker::PhantomData, path::PathBuf};
 
-use itertools::Itertools;
+use tracing::warn;
 
 use crate::{
-    instance::{
-        launch::{macros::replace, rules::is_library_passes},
-        profile::LoaderProfile,
-    },
+    instance::profile::LoaderProfile,
     repository::{
-        manifest::{Argument, Arguments, Classifiers, DownloadFile, Manifest, Value},
+        manifest::{Argument, Arguments, Manifest, Value},
         username::Username,
     },
-    utils::path_to_string,
-    NOMI_NAME, NOMI_VERSION,
 };
 
-use super::{rules::is_rule_passes, LaunchInstance, CLASSPATH_SEPARATOR};
+use super::{
+    natives::{native_download, NativeLibrary},
+    placeholders::Variables,
+    quick_play::{is_quick_play_supported, QuickPlay},
+    rules::{EnabledFeatures, Environment},
+    LaunchInstance, CLASSPATH_SEPARATOR,
+};
 
 pub enum Undefined {}
 pub enum WithUserData {}
@@ -26,8 +27,9 @@ pub struct ArgumentsBuilder<'a, S = Undefined, U = Undefined> {
     manifest: &'a Manifest,
     classpath: Vec<PathBuf>,
     classpath_string: String,
-    native_libs: Vec<PathBuf>,
+    native_libs: Vec<NativeLibrary>,
     user_data: UserData,
+    quick_play: Option<QuickPlay>,
 
     _classpath_marker: PhantomData<S>,
     _user_data_marker: PhantomData<U>,
@@ -36,8 +38,12 @@ pub struct ArgumentsBuilder<'a, S = Undefined, U = Undefined> {
 #[derive(Default)]
 pub struct UserData {
     pub username: Username,
+    /// `None` means the offline UUID of the username is used.
     pub uuid: Option<String>,
     pub access_token: Option<String>,
+    /// Xbox user id of Microsoft accounts.
+    pub xuid: Option<String>,
+    pub client_id: Option<String>,
 }
 
 struct JvmArguments(Vec<Argument>);
@@ -67,6 +73,7 @@ impl<'a> ArgumentsBuilder<'a, Undefined, Undefined> {
             classpath_string: String::new(),
             native_libs: Vec::new(),
             user_data: UserData::default(),
+            quick_play: None,
             _classpath_marker: PhantomData,
             _user_data_marker: PhantomData,
         }
@@ -74,9 +81,9 @@ impl<'a> ArgumentsBuilder<'a, Undefined, Undefined> {
 }
 
 impl<'a, U> ArgumentsBuilder<'a, Undefined, U> {
-    pub fn with_classpath(self) -> ArgumentsBuilder<'a, WithClasspath, U> {
-        let (classpath, native_libs) = dbg!(self.classpath());
-        ArgumentsBuilder {
+    pub fn with_classpath(self) -> anyhow::Result<ArgumentsBuilder<'a, WithClasspath, U>> {
+        let (classpath, native_libs) = self.classpath()?;
+        Ok(ArgumentsBuilder {
             instance: self.instance,
             manifest: self.manifest,
             user_data: self.user_data,
@@ -87,14 +94,20 @@ impl<'a, U> ArgumentsBuilder<'a, Undefined, U> {
             .collect::<String>(),
             classpath,
             native_libs,
+            quick_play: self.quick_play,
             _classpath_marker: PhantomData,
             _user_data_marker: PhantomData,
-        }
+        })
     }
 }
 
 impl<'a, S> ArgumentsBuilder<'a, S, Undefined> {
-    pub fn with_userdata(self, user_data: UserData) -> ArgumentsBuilder<'a, S, WithUserData> {
+    pub fn with_userdata(self, mut user_data: UserData) -> ArgumentsBuilder<'a, S, WithUserData> {
+        let username = &user_data.username;
+        user_data
+            .uuid
+            .get_or_insert_with(|| username.offline_uuid().to_string());
+
         ArgumentsBuilder {
             instance: self.instance,
             manifest: self.manifest,
@@ -102,12 +115,22 @@ impl<'a, S> ArgumentsBuilder<'a, S, Undefined> {
             classpath_string: self.classpath_string,
             classpath: self.classpath,
             native_libs: self.native_libs,
+            quick_play: self.quick_play,
             _classpath_marker: PhantomData,
             _user_data_marker: PhantomData,
         }
     }
 }
 
+impl<'a, S, U> ArgumentsBuilder<'a, S, U> {
+    /// Sets where the game goes right after it starts.
+    #[must_use]
+    pub fn with_quick_play(mut self, quick_play: Option<QuickPlay>) -> Self {
+        self.quick_play = quick_play;
+        self
+    }
+}
+
 impl<'a, U> ArgumentsBuilder<'a, WithClasspath, U> {
     pub fn classpath_as_str(&self) -> &str {
         &self.classpath_string
@@ -124,7 +147,7 @@ impl<'a, U> ArgumentsBuilder<'a, WithClasspath, U> {
             .map_or(&self.manifest.main_class, |profile| &profile.main_class)
     }
 
-    pub fn get_native_libs(&self) -> &[PathBuf] {
+    pub fn get_native_libs(&self) -> &[NativeLibrary] {
         self.native_libs.as_slice()
     }
 
@@ -150,8 +173,8 @@ impl<'a> ArgumentsBuilder<'a, WithClasspath, WithUserData> {
                         "-Djava.library.path={}",
                         &self.instance.settings.natives_dir.display()
                     ),
-                    "-Dminecraft.launcher.brand=${launcher_name}".into(),
-                    "-Dminecraft.launcher.version=${launcher_version}".into(),
+                    self.substitute("-Dminecraft.launcher.brand=${launcher_name}"),
+                    self.substitute("-Dminecraft.launcher.version=${launcher_version}"),
                     format!(
                         "-Dminecraft.client.jar={}",
                         &self.instance.settings.version_jar_file.display()
@@ -164,43 +187,120 @@ impl<'a> ArgumentsBuilder<'a, WithClasspath, WithUserData> {
     }
 
     pub fn manifest_game_arguments(&self) -> Vec<String> {
-        self.arguments_parser(
+        let mut arguments = self.arguments_parser(
             |_, GameArguments(game)| game.clone(),
             |arguments| {
                 arguments
                     .split_whitespace()
-                    .map(|arg| self.parse_args_from_str(arg))
+                    .map(|arg| self.substitute(arg))
                     .collect()
             },
-        )
+        );
+
+        let window = self.instance.settings.window.unwrap_or_default();
+
+        // New manifests provide the resolution arguments behind the `has_custom_resolution` feature
+        if let (Some(resolution), Arguments::Old(_)) = (window.resolution, &self.manifest.arguments)
+        {
+            arguments.extend([
+                "--width".to_owned(),
+                resolution.width.to_string(),
+                "--height".to_owned(),
+                resolution.height.to_string(),
+            ]);
+        }
+
+        if window.fullscreen {
+            arguments.push("--fullscreen".to_owned());
+        }
+
+        if let Some(quick_play) = &self.quick_play {
+            if !is_quick_play_supported(self.manifest) {
+                if let Some(legacy) = quick_play.legacy_arguments() {
+                    arguments.extend(legacy);
+                } else {
+                    warn!(
+                        "Quick Play is not supported by version {}, {quick_play} is ignored",
+                        self.instance.settings.version
+                    );
+                }
+            }
+        }
+
+        arguments
     }
 
-    fn parse_args_from_str(&self, source: &str) -> String {
-        replace!(source,
-            "${assets_root}" => &path_to_string(&self.instance.settings.assets),
-            "${game_assets}" => &path_to_string(&self.instance.settings.assets),
-            "${game_directory}" => &path_to_string(&self.instance.settings.game_dir),
-            "${natives_directory}" => &path_to_string(&self.instance.settings.natives_dir),
-            "${launcher_name}" => NOMI_NAME,
-            "${launcher_version}" => NOMI_VERSION,
-            "${auth_access_token}" => self.user_data
-                .access_token
-                .clone()
-                .unwrap_or("null".to_string())
-                .as_str(),
-            "${auth_session}" => "null",
-            "${auth_player_name}" => self.user_data.username.get(),
-            "${auth_uuid}" => self.user_data
-                .uuid
-                .clone()
-                .unwrap_or(uuid::Uuid::new_v4().to_string())
-                .as_str(),
-            "${version_type}" => &self.instance.settings.version_type.as_str(),
-            "${version_name}" => &self.instance.settings.version,
-            "${assets_index_name}" => &self.manifest.asset_index.id,
-            "${user_properties}" => "{}",
-            "${classpath}" => &self.classpath_as_str()
-        )
+    pub fn enabled_features(&self) -> EnabledFeatures {
+        let quick_play = self
+            .quick_play
+            .as_ref()
+            .filter(|_| is_quick_play_supported(self.manifest));
+
+        EnabledFeatures {
+            has_custom_resolution: self
+                .instance
+                .settings
+                .window
+                .is_some_and(|window| window.resolution.is_some()),
+            is_quick_play_singleplayer: matches!(quick_play, Some(QuickPlay::Singleplayer(_))),
+            is_quick_play_multiplayer: matches!(quick_play, Some(QuickPlay::Multiplayer(_))),
+            is_quick_play_realms: matches!(quick_play, Some(QuickPlay::Realm(_))),
+            ..Default::default()
+        }
+    }
+
+    /// Arguments of the loader profile with the placeholders resolved.
+    pub fn loader_jvm_arguments(&self) -> Vec<String> {
+        self.loader_arguments()
+            .jvm_arguments()
+            .iter()
+            .map(|arg| self.substitute(arg))
+            .collect()
+    }
+
+    /// Arguments of the loader profile with the placeholders resolved.
+    pub fn loader_game_arguments(&self) -> Vec<String> {
+        self.loader_arguments()
+            .game_arguments()
+            .iter()
+            .map(|arg| self.substitute(arg))
+            .collect()
+    }
+
+    pub fn variables(&self) -> Variables<'_> {
+        let settings = &self.instance.settings;
+
+        Variables {
+            username: self.user_data.username.get(),
+            uuid: self.user_data.uuid.as_deref().unwrap_or_default(),
+            access_token: self.user_data.access_token.as_deref(),
+            xuid: self.user_data.xuid.as_deref(),
+            client_id: self.user_data.client_id.as_deref(),
+            version_name: &settings.version,
+            version_type: settings.version_type.as_str(),
+            game_dir: &settings.game_dir,
+            assets_dir: &settings.assets,
+            assets_index_name: &self.manifest.asset_index.id,
+            natives_dir: &settings.natives_dir,
+            libraries_dir: &settings.libraries_dir,
+            classpath: self.classpath_as_str(),
+            resolution: settings
+                .window
+                .and_then(|window| window.resolution)
+                .unwrap_or_default(),
+            quick_play: self.quick_play.as_ref(),
+        }
+    }
+
+    /// Resolves the placeholders of the argument and warns about the unknown ones.
+    fn substitute(&self, source: &str) -> String {
+        let substitution = self.variables().substitute(source);
+
+        for name in &substitution.unknown {
+            warn!("Unknown placeholder `${{{name}}}` in the launch argument `{source}`");
+        }
+
+        substitution.value
     }
 
     fn arguments_parser(
@@ -218,22 +318,23 @@ impl<'a> ArgumentsBuilder<'a, WithClasspath, WithUserData> {
     }
 
     fn parse_arguments(&self, args: Vec<Argument>) -> Vec<String> {
+        let environment = Environment::current().with_features(self.enabled_features());
+
         args.into_iter()
             .flat_map(|arg| match arg {
                 Argument::Struct { rules, value } => {
-                    if !rules.iter().all(is_rule_passes) {
+                    if !environment.is_allowed(&rules) {
                         return vec![String::new()];
                     }
 
                     match value {
-                        Value::String(v) => vec![self.parse_args_from_str(&v)],
-                        Value::Array(arr) => arr
-                            .into_iter()
-                            .map(|arg| self.parse_args_from_str(&arg))
-                            .collect(),
+                        Value::String(v) => vec![self.substitute(&v)],
+                        Value::Array(arr) => {
+                            arr.into_iter().map(|arg| self.substitute(&arg)).collect()
+                        }
                     }
                 }
-                Argument::String(arg) => vec![self.parse_args_from_str(&arg)],
+                Argument::String(arg) => vec![self.substitute(&arg)],
             })
             .filter(|arg| !arg.is_empty())
             .collect::<Vec<String>>()
@@ -241,46 +342,38 @@ impl<'a> ArgumentsBuilder<'a, WithClasspath, WithUserData> {
 }
 
 impl<'a, S, U> ArgumentsBuilder<'a, S, U> {
-    fn classpath(&self) -> (Vec<PathBuf>, Vec<PathBuf>) {
-        fn match_natives(natives: &Classifiers) -> Option<&DownloadFile> {
-            match std::env::consts::OS {
-                "linux" => natives.natives_linux.as_ref(),
-                "windows" => natives.natives_windows.as_ref(),
-                "macos" => natives.natives_macos.as_ref(),
-                _ => unreachable!(),
-            }
-        }
+    fn classpath(&self) -> anyhow::Result<(Vec<PathBuf>, Vec<NativeLibrary>)> {
+        let environment = Environment::current();
 
-        let mut classpath = vec![Some(self.instance.settings.version_jar_file.clone())];
+        let mut classpath = vec![self.instance.settings.version_jar_file.clone()];
         let mut native_libs = vec![];
 
-        self.manifest
+        for lib in self
+            .manifest
             .libraries
             .iter()
-            .filter(|lib| is_library_passes(lib))
-            .map(|lib| {
-                (
-                    lib.downloads
-                        .artifact
-                        .as_ref()
-                        .and_then(|artifact| artifact.path.as_ref())
-                        .map(|path| self.instance.settings.libraries_dir.join(path)),
-                    lib.downloads
-                        .classifiers
-                        .as_ref()
-                        .and_then(|natives| match_natives(natives))
-                        .and_then(|native_lib| native_lib.path.as_ref())
-                        .map(|path| self.instance.settings.libraries_dir.join(path)),
-                )
-            })
-            .for_each(|(lib, native)| {
-                classpath.extend([lib, native.clone()]);
-                native_libs.push(native);
-            });
-
-        let mut classpath = classpath.into_iter().flatten().collect::<Vec<_>>();
+            .filter(|lib| environment.is_library_allowed(lib))
+        {
+            if let Some(path) = lib
+                .downloads
+                .artifact
+                .as_ref()
+                .and_then(|artifact| artifact.path.as_ref())
+            {
+                classpath.push(self.instance.settings.libraries_dir.join(path));
+            }
 
-        let native_libs = native_libs.into_iter().flatten().collect::<Vec<_>>();
+            if let Some(path) = native_download(&environment, lib)?
+                .and_then(|native_lib| native_lib.path.as_ref())
+                .map(|path| self.instance.settings.libraries_dir.join(path))
+            {
+                classpath.push(path.clone());
+                native_libs.push(NativeLibrary {
+                    path,
+                    exclude: lib.extract.as_ref().map(|extract| extract.exclude.clone()),
+                });
+            }
+        }
 
         if let Some(libs) = self
             .instance
@@ -295,8 +388,62 @@ impl<'a, S, U> ArgumentsBuilder<'a, S, U> {
             classpath.extend(libs);
         }
 
-        let classpath = classpath.iter().cloned().collect_vec();
+        Ok((classpath, native_libs))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        instance::launch::{LaunchInstanceBuilder, LaunchSettings},
+        NOMI_NAME,
+    };
+
+    use super::*;
+
+    fn old_manifest() -> Manifest {
+        serde_json::from_value(serde_json::json!({
+            "minecraftArguments": "--username ${auth_player_name} --gameDir ${game_directory}",
+            "assetIndex": { "id": "1.7.10", "sha1": "", "size": 0, "totalSize": 0, "url": "" },
+            "assets": "1.7.10",
+            "complianceLevel": 0,
+            "downloads": { "client": { "sha1": "", "size": 0, "url": "" } },
+            "id": "1.7.10",
+            "javaVersion": { "component": "jre-legacy", "majorVersion": 8 },
+            "libraries": [],
+            "mainClass": "net.minecraft.client.main.Main",
+            "minimumLauncherVersion": 0,
+            "releaseTime": "",
+            "time": "",
+            "type": "release"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn old_manifest_arguments_test() {
+        let manifest = old_manifest();
+        let instance = LaunchInstanceBuilder::new()
+            .settings(LaunchSettings {
+                game_dir: "minecraft".into(),
+                version: "1.7.10".into(),
+                ..Default::default()
+            })
+            .build();
 
-        (classpath, native_libs)
+        let builder = ArgumentsBuilder::new(&instance, &manifest)
+            .with_classpath()
+            .unwrap()
+            .with_userdata(UserData::default());
+
+        let jvm_arguments = builder.manifest_jvm_arguments();
+        let game_arguments = builder.manifest_game_arguments();
+
+        assert!(jvm_arguments
+            .iter()
+            .chain(&game_arguments)
+            .all(|arg| !arg.contains("${")));
+        assert!(jvm_arguments.contains(&format!("-Dminecraft.launcher.brand={NOMI_NAME}")));
+        assert!(game_arguments.contains(&"minecraft".to_owned()));
     }
 }
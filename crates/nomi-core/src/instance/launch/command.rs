@@ -0,0 +1,254 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::fs::{write_json_config, write_to_file};
+
+use super::hooks::{is_valid_env_key, InvalidEnvKey};
+
+/// Replaces secrets in the redacted commands.
+pub const REDACTED: &str = "<redacted>";
+
+/// Fully resolved command line of the game.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LaunchCommand {
+    /// Directory the relative paths of the arguments are resolved against.
+    pub working_dir: PathBuf,
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Command the program is started with, e.g. `gamemoderun`.
+    #[serde(default)]
+    pub wrapper: Vec<String>,
+    pub program: String,
+    pub jvm_arguments: Vec<String>,
+    pub main_class: String,
+    pub game_arguments: Vec<String>,
+}
+
+impl LaunchCommand {
+    /// The program and all its arguments in order, starting with the wrapper.
+    pub fn command_line(&self) -> impl Iterator<Item = &String> {
+        self.wrapper
+            .iter()
+            .chain(std::iter::once(&self.program))
+            .chain(self.arguments())
+    }
+
+    /// All arguments passed to the program in order.
+    pub fn arguments(&self) -> impl Iterator<Item = &String> {
+        self.jvm_arguments
+            .iter()
+            .chain(std::iter::once(&self.main_class))
+            .chain(&self.game_arguments)
+    }
+
+    /// Replaces every occurrence of `secret` in the arguments with [`REDACTED`].
+    pub fn redact(&mut self, secret: &str) {
+        if secret.is_empty() {
+            return;
+        }
+
+        for argument in self
+            .jvm_arguments
+            .iter_mut()
+            .chain(&mut self.game_arguments)
+            .chain(self.env.values_mut())
+        {
+            if argument.contains(secret) {
+                *argument = argument.replace(secret, REDACTED);
+            }
+        }
+    }
+
+    pub fn to_command(&self) -> Command {
+        let mut command = match self.wrapper.split_first() {
+            Some((wrapper, wrapper_arguments)) => {
+                let mut command = Command::new(wrapper);
+                command.args(wrapper_arguments).arg(&self.program);
+                command
+            }
+            None => Command::new(&self.program),
+        };
+
+        command
+            .current_dir(&self.working_dir)
+            .envs(&self.env)
+            .args(self.arguments());
+        command
+    }
+
+    /// Fails if an environment variable name can not be exported, see [`is_valid_env_key`].
+    pub fn to_shell_script(&self) -> Result<String, InvalidEnvKey> {
+        let mut script = format!(
+            "#!/bin/sh\n# Generated by Nomi\n\ncd {} || exit 1\n\n",
+            shell_quote(&self.working_dir.to_string_lossy()),
+        );
+
+        for (key, value) in &self.env {
+            if !is_valid_env_key(key) {
+                return Err(InvalidEnvKey(key.clone()));
+            }
+            let _ = writeln!(script, "export {key}={}", shell_quote(value));
+        }
+
+        if !self.env.is_empty() {
+            script.push('\n');
+        }
+
+        script.push_str("exec");
+        for (index, word) in self.command_line().enumerate() {
+            script.push_str(if index <= self.wrapper.len() {
+                " "
+            } else {
+                " \\\n    "
+            });
+            script.push_str(&shell_quote(word));
+        }
+
+        script.push('\n');
+        Ok(script)
+    }
+
+    pub fn to_json(&self) -> anyhow::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Writes an executable POSIX shell script that runs the command.
+    pub async fn write_shell_script(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        let path = path.as_ref();
+
+        write_to_file(self.to_shell_script()?.as_bytes(), path).await?;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            tokio::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755)).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn write_json(&self, path: impl AsRef<Path>) -> anyhow::Result<()> {
+        write_json_config(self, path).await
+    }
+}
+
+/// Quotes the word for a POSIX shell unless it consists of safe characters only.
+fn shell_quote(word: &str) -> String {
+    let is_safe = !word.is_empty()
+        && word
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-+=@%:,./".contains(c));
+
+    if is_safe {
+        word.to_owned()
+    } else {
+        format!("'{}'", word.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn command() -> LaunchCommand {
+        LaunchCommand {
+            working_dir: PathBuf::from("/home/user/nomi"),
+            env: BTreeMap::new(),
+            wrapper: Vec::new(),
+            program: "java".into(),
+            jvm_arguments: vec!["-Xmx2G".into(), "-Dos.name=Windows 10".into()],
+            main_class: "net.minecraft.client.main.Main".into(),
+            game_arguments: vec![
+                "--username".into(),
+                "Player's".into(),
+                "--accessToken".into(),
+                "secret-token".into(),
+            ],
+        }
+    }
+
+    #[test]
+    fn shell_quote_test() {
+        assert_eq!(shell_quote("--version"), "--version");
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+        assert_eq!(shell_quote("$HOME"), "'$HOME'");
+        assert_eq!(shell_quote(""), "''");
+    }
+
+    #[test]
+    fn shell_script_test() {
+        let script = command().to_shell_script().unwrap();
+
+        assert!(script.starts_with("#!/bin/sh\n"));
+        assert!(script.contains("cd /home/user/nomi || exit 1"));
+        assert!(script.contains("exec java \\\n    -Xmx2G \\\n    '-Dos.name=Windows 10'"));
+        assert!(script.contains(r"'Player'\''s'"));
+        assert!(script.ends_with("secret-token\n"));
+    }
+
+    #[test]
+    fn wrapper_test() {
+        let command = LaunchCommand {
+            env: BTreeMap::from([("__GL_THREADED_OPTIMIZATIONS".into(), "1".into())]),
+            wrapper: vec!["gamemoderun".into(), "mangohud".into()],
+            ..command()
+        };
+
+        let script = command.to_shell_script().unwrap();
+        assert!(script.contains("export __GL_THREADED_OPTIMIZATIONS=1\n"));
+        assert!(script.contains("exec gamemoderun mangohud java \\\n    -Xmx2G"));
+
+        assert_eq!(
+            command.command_line().take(3).collect::<Vec<_>>(),
+            ["gamemoderun", "mangohud", "java"]
+        );
+
+        let command = LaunchCommand {
+            env: BTreeMap::from([("X=$(reboot)".into(), "1".into())]),
+            ..command
+        };
+        assert_eq!(command.to_shell_script().unwrap_err().0, "X=$(reboot)");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn to_command_test() {
+        let command = LaunchCommand {
+            working_dir: std::env::temp_dir(),
+            env: BTreeMap::from([("BAR".into(), "baz".into())]),
+            wrapper: vec!["env".into(), "FOO=bar".into()],
+            program: "sh".into(),
+            jvm_arguments: vec!["-c".into()],
+            main_class: "echo $FOO $BAR".into(),
+            game_arguments: Vec::new(),
+        };
+
+        let output = command.to_command().output().await.unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "bar baz\n");
+    }
+
+    #[test]
+    fn redact_test() {
+        let mut command = command();
+        command.redact("secret-token");
+
+        assert_eq!(command.game_arguments[3], REDACTED);
+        assert!(!command.to_shell_script().unwrap().contains("secret-token"));
+        assert!(!command.to_json().unwrap().contains("secret-token"));
+
+        let json = command.to_json().unwrap();
+        assert_eq!(
+            serde_json::from_str::<LaunchCommand>(&json).unwrap(),
+            command
+        );
+    }
+}
@@ -0,0 +1,379 @@
+use std::{borrow::Cow, collections::BTreeMap, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+use tracing::{info, warn};
+
+use super::placeholders::substitute;
+
+#[derive(Debug, thiserror::Error)]
+#[error("`{0}` is not a valid environment variable name, use letters, digits and `_` and do not start with a digit")]
+pub struct InvalidEnvKey(pub String);
+
+/// Whether the name matches `[A-Za-z_][A-Za-z0-9_]*`, which every shell accepts.
+pub fn is_valid_env_key(key: &str) -> bool {
+    let mut chars = key.chars();
+
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Wrapper command, environment variables and shell commands run around the game.
+///
+/// Every value can contain the placeholders of [`HookContext`].
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq, Eq)]
+pub struct LaunchHooks {
+    /// Command put in front of java, e.g. `gamemoderun mangohud`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrapper: Option<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Shell command run before the game starts. The launch is aborted if it fails.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pre_launch: Option<String>,
+    /// Shell command run after the game exits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_exit: Option<String>,
+}
+
+impl LaunchHooks {
+    /// Combines the profile's hooks with the global ones.
+    ///
+    /// Values set in the profile take precedence, environment variables are merged.
+    #[must_use]
+    pub fn merged(&self, global: &LaunchHooks) -> LaunchHooks {
+        let mut env = global.env.clone();
+        env.extend(self.env.clone());
+
+        LaunchHooks {
+            wrapper: self.wrapper.clone().or_else(|| global.wrapper.clone()),
+            env,
+            pre_launch: self
+                .pre_launch
+                .clone()
+                .or_else(|| global.pre_launch.clone()),
+            post_exit: self.post_exit.clone().or_else(|| global.post_exit.clone()),
+        }
+    }
+
+    /// Words of the wrapper with the placeholders resolved.
+    pub fn wrapper(&self, context: &HookContext) -> Vec<String> {
+        self.wrapper
+            .as_deref()
+            .map(split_words)
+            .unwrap_or_default()
+            .iter()
+            .map(|word| context.substitute(word))
+            .collect()
+    }
+
+    /// Environment variables with the placeholders resolved.
+    ///
+    /// Fails if a name is not valid, see [`is_valid_env_key`].
+    pub fn env(&self, context: &HookContext) -> Result<BTreeMap<String, String>, InvalidEnvKey> {
+        self.env
+            .iter()
+            .map(|(key, value)| {
+                if is_valid_env_key(key) {
+                    Ok((key.clone(), context.substitute(value)))
+                } else {
+                    Err(InvalidEnvKey(key.clone()))
+                }
+            })
+            .collect()
+    }
+
+    pub async fn run_pre_launch(&self, context: &HookContext) -> anyhow::Result<()> {
+        let Some(hook) = &self.pre_launch else {
+            return Ok(());
+        };
+
+        self.run(hook, context).await.map_err(|e| {
+            anyhow::anyhow!("Pre-launch command `{hook}` failed, the game is not started. {e}")
+        })
+    }
+
+    /// Runs the post-exit command. Failures are only logged since the game has already exited.
+    pub async fn run_post_exit(&self, context: &HookContext) {
+        let Some(hook) = &self.post_exit else {
+            return;
+        };
+
+        if let Err(e) = self.run(hook, context).await {
+            warn!("Post-exit command `{hook}` failed. {e}");
+        }
+    }
+
+    async fn run(&self, hook: &str, context: &HookContext) -> anyhow::Result<()> {
+        let hook = context.substitute(hook);
+
+        info!("Running `{hook}`");
+
+        let status = shell(&hook)
+            .current_dir(&context.game_dir)
+            .envs(self.env(context)?)
+            .envs(context.variables())
+            .status()
+            .await?;
+
+        if status.success() {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("Exit status: {status}"))
+        }
+    }
+}
+
+/// Values available to the hooks.
+///
+/// They are substituted for `${name}` placeholders and passed to the hook commands
+/// as `NOMI_*` environment variables, which are safer to use for paths with spaces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookContext {
+    pub profile_name: String,
+    pub version: String,
+    pub game_dir: PathBuf,
+    pub natives_dir: PathBuf,
+    /// Only known in the post-exit command. `None` if the game was terminated by a signal.
+    pub exit_code: Option<i32>,
+}
+
+impl HookContext {
+    #[must_use]
+    pub fn with_exit_code(mut self, exit_code: Option<i32>) -> Self {
+        self.exit_code = exit_code;
+        self
+    }
+
+    /// Unknown placeholders are kept as is since they can be shell variables.
+    pub fn substitute(&self, text: &str) -> String {
+        let values = self.values();
+
+        substitute(text, |name| {
+            values
+                .iter()
+                .find(|(placeholder, _, _)| *placeholder == name)
+                .map(|(_, _, value)| Cow::Owned(value.clone()))
+        })
+        .value
+    }
+
+    /// Environment variables passed to the hook commands.
+    pub fn variables(&self) -> impl Iterator<Item = (&'static str, String)> {
+        self.values()
+            .into_iter()
+            .map(|(_, variable, value)| (variable, value))
+    }
+
+    fn values(&self) -> [(&'static str, &'static str, String); 5] {
+        [
+            (
+                "profile_name",
+                "NOMI_PROFILE_NAME",
+                self.profile_name.clone(),
+            ),
+            ("version_name", "NOMI_VERSION_NAME", self.version.clone()),
+            (
+                "game_directory",
+                "NOMI_GAME_DIR",
+                self.game_dir.to_string_lossy().into_owned(),
+            ),
+            (
+                "natives_directory",
+                "NOMI_NATIVES_DIR",
+                self.natives_dir.to_string_lossy().into_owned(),
+            ),
+            (
+                "exit_code",
+                "NOMI_EXIT_CODE",
+                self.exit_code
+                    .map(|code| code.to_string())
+                    .unwrap_or_default(),
+            ),
+        ]
+    }
+}
+
+/// Splits the command into words the way a shell does for simple cases.
+///
+/// Supports single and double quotes and backslash escapes outside of single quotes.
+pub fn split_words(command: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut has_word = false;
+    let mut quote = None;
+    let mut chars = command.chars();
+
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (None | Some('"'), '\\') => {
+                if let Some(next) = chars.next() {
+                    word.push(next);
+                }
+                has_word = true;
+            }
+            (Some(_), c) => word.push(c),
+            (None, '\'' | '"') => {
+                quote = Some(c);
+                has_word = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if has_word {
+                    words.push(std::mem::take(&mut word));
+                    has_word = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                has_word = true;
+            }
+        }
+    }
+
+    if has_word {
+        words.push(word);
+    }
+
+    words
+}
+
+#[cfg(windows)]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.args(["/C", command]);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.args(["-c", command]);
+    shell
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> HookContext {
+        HookContext {
+            profile_name: "Survival".into(),
+            version: "1.20.4".into(),
+            game_dir: PathBuf::from("/home/user/My Games/minecraft"),
+            natives_dir: PathBuf::from("/home/user/natives"),
+            exit_code: None,
+        }
+    }
+
+    #[test]
+    fn split_words_test() {
+        assert_eq!(
+            split_words("gamemoderun  mangohud"),
+            ["gamemoderun", "mangohud"]
+        );
+        assert_eq!(
+            split_words(r#"env "A=b c" 'd "e"' f\ g"#),
+            ["env", "A=b c", r#"d "e""#, "f g"]
+        );
+        assert_eq!(split_words("a '' b"), ["a", "", "b"]);
+        assert!(split_words("   ").is_empty());
+    }
+
+    #[test]
+    fn merge_test() {
+        let global = LaunchHooks {
+            wrapper: Some("gamemoderun".into()),
+            env: BTreeMap::from([("A".into(), "global".into()), ("B".into(), "global".into())]),
+            pre_launch: Some("echo global".into()),
+            post_exit: None,
+        };
+        let profile = LaunchHooks {
+            wrapper: Some("prime-run".into()),
+            env: BTreeMap::from([("B".into(), "profile".into())]),
+            ..Default::default()
+        };
+
+        let hooks = profile.merged(&global);
+        assert_eq!(hooks.wrapper.as_deref(), Some("prime-run"));
+        assert_eq!(hooks.env["A"], "global");
+        assert_eq!(hooks.env["B"], "profile");
+        assert_eq!(hooks.pre_launch.as_deref(), Some("echo global"));
+        assert!(hooks.post_exit.is_none());
+    }
+
+    #[test]
+    fn placeholders_test() {
+        let hooks = LaunchHooks {
+            wrapper: Some("obs-gamecapture --log ${game_directory}/obs.log".into()),
+            env: BTreeMap::from([("PROFILE".into(), "${profile_name}".into())]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            hooks.wrapper(&context()),
+            [
+                "obs-gamecapture",
+                "--log",
+                "/home/user/My Games/minecraft/obs.log"
+            ]
+        );
+        assert_eq!(hooks.env(&context()).unwrap()["PROFILE"], "Survival");
+
+        let context = context().with_exit_code(Some(1));
+        assert_eq!(
+            context.substitute("${version_name} exited with ${exit_code}"),
+            "1.20.4 exited with 1"
+        );
+    }
+
+    #[test]
+    fn env_key_test() {
+        assert!(is_valid_env_key("__GL_THREADED_OPTIMIZATIONS"));
+        assert!(is_valid_env_key("_JAVA_OPTIONS2"));
+        assert!(!is_valid_env_key(""));
+        assert!(!is_valid_env_key("2D"));
+        assert!(!is_valid_env_key("A B"));
+        assert!(!is_valid_env_key("X=$(reboot)"));
+
+        let hooks = LaunchHooks {
+            env: BTreeMap::from([("OK".into(), "1".into()), ("NOT-OK".into(), "2".into())]),
+            ..Default::default()
+        };
+        let error = hooks.env(&context()).unwrap_err();
+        assert_eq!(error.0, "NOT-OK");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn run_test() {
+        let dir = std::env::temp_dir().join("nomi_hooks_test");
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let context = HookContext {
+            game_dir: dir.clone(),
+            ..context()
+        };
+
+        let hooks = LaunchHooks {
+            env: BTreeMap::from([("GREETING".into(), "hello ${profile_name}".into())]),
+            pre_launch: Some("echo \"$GREETING $NOMI_VERSION_NAME\" > pre.txt".into()),
+            ..Default::default()
+        };
+        hooks.run_pre_launch(&context).await.unwrap();
+        assert_eq!(
+            std::fs::read_to_string(dir.join("pre.txt")).unwrap(),
+            "hello Survival 1.20.4\n"
+        );
+
+        let failing = LaunchHooks {
+            pre_launch: Some("exit 3".into()),
+            ..Default::default()
+        };
+        assert!(failing.run_pre_launch(&context).await.is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
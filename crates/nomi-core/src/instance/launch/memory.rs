@@ -0,0 +1,285 @@
+use std::{fmt::Write, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::configs::profile::Loader;
+
+const MIB: u64 = 1024 * 1024;
+
+/// The smallest `-Xmx` value the auto mode will ever pick (in MiB).
+const MIN_HEAP: u64 = 1024;
+/// The biggest `-Xmx` value the auto mode will ever pick (in MiB).
+const MAX_HEAP: u64 = 16 * 1024;
+/// Memory left to the system and the JVM's off-heap allocations (in MiB).
+const SYSTEM_RESERVE: u64 = 1536;
+/// Additional heap per installed mod (in MiB).
+const PER_MOD: u64 = 40;
+
+#[derive(Serialize, Deserialize, Default, PartialEq, Eq, Debug, Clone, Copy)]
+pub enum MemoryMode {
+    /// Heap size is taken from the custom JVM arguments as is.
+    #[default]
+    Manual,
+    /// Heap size is picked based on the system memory and the profile contents.
+    Auto,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SystemMemory {
+    /// Total memory in bytes
+    pub total: u64,
+    /// Available memory in bytes
+    pub available: u64,
+}
+
+impl SystemMemory {
+    #[cfg(target_os = "linux")]
+    pub fn read() -> anyhow::Result<Self> {
+        let meminfo = std::fs::read_to_string("/proc/meminfo")?;
+        Self::from_meminfo(&meminfo).ok_or_else(|| anyhow::anyhow!("Cannot parse `/proc/meminfo`"))
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    pub fn read() -> anyhow::Result<Self> {
+        Err(anyhow::anyhow!(
+            "Reading the system memory is not supported on this platform"
+        ))
+    }
+
+    /// Parses the content of `/proc/meminfo`.
+    pub fn from_meminfo(meminfo: &str) -> Option<Self> {
+        let field = |name: &str| {
+            meminfo
+                .lines()
+                .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+                .and_then(|value| value.split_whitespace().next())
+                .and_then(|kb| kb.parse::<u64>().ok())
+                .map(|kb| kb * 1024)
+        };
+
+        let total = field("MemTotal")?;
+        // `MemAvailable` is missing on kernels older than 3.14
+        let available = field("MemAvailable").or_else(|| field("MemFree"))?;
+
+        Some(Self { total, available })
+    }
+}
+
+/// Information about the profile used to pick the heap size.
+#[derive(Debug, Clone)]
+pub struct ProfileInfo<'a> {
+    pub loader: &'a Loader,
+    pub mods: usize,
+    pub game_version: &'a str,
+}
+
+impl<'a> ProfileInfo<'a> {
+    pub fn new(loader: &'a Loader, game_dir: &Path, game_version: &'a str) -> Self {
+        Self {
+            loader,
+            mods: count_mods(game_dir),
+            game_version,
+        }
+    }
+}
+
+/// Counts `.jar` files inside the `mods` directory.
+pub fn count_mods(game_dir: &Path) -> usize {
+    std::fs::read_dir(game_dir.join("mods")).map_or(0, |dir| {
+        dir.filter_map(Result::ok)
+            .filter(|entry| {
+                entry
+                    .path()
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("jar"))
+            })
+            .count()
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeapSize {
+    /// Initial heap size in MiB
+    pub min: u64,
+    /// Maximum heap size in MiB
+    pub max: u64,
+    /// Human-readable explanation of the chosen values
+    pub reason: String,
+}
+
+impl HeapSize {
+    pub fn pick(memory: SystemMemory, profile: &ProfileInfo<'_>) -> Self {
+        let total = memory.total / MIB;
+        let available = memory.available / MIB;
+
+        let (base, base_reason) = if is_legacy_version(profile.game_version) {
+            (1024, "pre-1.13 version")
+        } else {
+            (2048, "1.13+ version")
+        };
+
+        let loader_extra = match profile.loader {
+            Loader::Vanilla => 0,
+            Loader::Fabric { .. } => 512,
+        };
+
+        let mods_extra = profile.mods as u64 * PER_MOD;
+
+        let wanted = base + loader_extra + mods_extra;
+
+        let upper = total
+            .saturating_sub(SYSTEM_RESERVE)
+            .min(total / 4 * 3)
+            .clamp(MIN_HEAP, MAX_HEAP);
+
+        let mut max = wanted.clamp(MIN_HEAP, upper);
+
+        let limited_by_available = max > available && available >= MIN_HEAP;
+        if limited_by_available {
+            max = available;
+        }
+
+        let max = round_down(max, 256).max(MIN_HEAP);
+        let min = round_down(max / 2, 256).max(512);
+
+        let mut reason = format!(
+            "{base_reason} ({base} MiB), {} loader (+{loader_extra} MiB), {} mods (+{mods_extra} MiB); \
+             system has {total} MiB total, {available} MiB available",
+            profile.loader, profile.mods,
+        );
+
+        if wanted > upper {
+            let _ = write!(reason, "; capped at {upper} MiB");
+        }
+
+        if limited_by_available {
+            reason.push_str("; limited by available memory");
+        }
+
+        Self { min, max, reason }
+    }
+
+    pub fn to_arguments(&self) -> [String; 2] {
+        [format!("-Xms{}M", self.min), format!("-Xmx{}M", self.max)]
+    }
+}
+
+fn round_down(value: u64, step: u64) -> u64 {
+    value / step * step
+}
+
+/// Returns `true` for release versions older than 1.13.
+/// Snapshots and unknown versions are treated as modern ones.
+fn is_legacy_version(version: &str) -> bool {
+    let mut parts = version.split('.');
+
+    let (Some(major), Some(minor)) = (parts.next(), parts.next()) else {
+        return false;
+    };
+
+    let minor = minor
+        .split(|c: char| !c.is_ascii_digit())
+        .next()
+        .and_then(|minor| minor.parse::<u32>().ok());
+
+    major == "1" && minor.is_some_and(|minor| minor < 13)
+}
+
+/// Returns `true` if the argument sets the initial or maximum heap size.
+pub fn is_heap_argument(argument: &str) -> bool {
+    argument.starts_with("-Xms") || argument.starts_with("-Xmx")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GIB: u64 = 1024 * MIB;
+
+    const MEMINFO: &str = "MemTotal:       16314536 kB
+MemFree:         1198484 kB
+MemAvailable:    9613324 kB
+Buffers:          602080 kB
+Cached:          7562352 kB";
+
+    #[test]
+    fn meminfo_test() {
+        let memory = SystemMemory::from_meminfo(MEMINFO).unwrap();
+        assert_eq!(memory.total, 16_314_536 * 1024);
+        assert_eq!(memory.available, 9_613_324 * 1024);
+
+        let memory = SystemMemory::from_meminfo("MemTotal: 2048 kB\nMemFree: 1024 kB").unwrap();
+        assert_eq!(memory.available, 1024 * 1024);
+
+        assert!(SystemMemory::from_meminfo("").is_none());
+    }
+
+    #[test]
+    fn legacy_version_test() {
+        assert!(is_legacy_version("1.8.9"));
+        assert!(is_legacy_version("1.12.2"));
+        assert!(!is_legacy_version("1.13"));
+        assert!(!is_legacy_version("1.20.6"));
+        assert!(!is_legacy_version("24w14a"));
+        assert!(!is_legacy_version("1.21-pre1"));
+    }
+
+    #[test]
+    fn pick_test() {
+        let vanilla = Loader::Vanilla;
+        let fabric = Loader::Fabric { version: None };
+
+        let laptop = SystemMemory {
+            total: 8 * GIB,
+            available: 5 * GIB,
+        };
+
+        let heap = HeapSize::pick(
+            laptop,
+            &ProfileInfo {
+                loader: &vanilla,
+                mods: 0,
+                game_version: "1.20.1",
+            },
+        );
+        assert_eq!(heap.max, 2048);
+        assert_eq!(heap.min, 1024);
+
+        let heap = HeapSize::pick(
+            laptop,
+            &ProfileInfo {
+                loader: &fabric,
+                mods: 200,
+                game_version: "1.20.1",
+            },
+        );
+        assert_eq!(heap.max, 5 * 1024);
+        assert!(heap.reason.contains("limited by available memory"));
+
+        let workstation = SystemMemory {
+            total: 64 * GIB,
+            available: 60 * GIB,
+        };
+
+        let heap = HeapSize::pick(
+            workstation,
+            &ProfileInfo {
+                loader: &fabric,
+                mods: 64,
+                game_version: "1.20.1",
+            },
+        );
+        assert_eq!(heap.max, 2048 + 512 + 64 * PER_MOD);
+
+        let heap = HeapSize::pick(
+            workstation,
+            &ProfileInfo {
+                loader: &vanilla,
+                mods: 0,
+                game_version: "1.8.9",
+            },
+        );
+        assert_eq!(heap.max, 1024);
+        assert_eq!(heap.min, 512);
+    }
+}
@@ -0,0 +1,178 @@
+use std::path::PathBuf;
+
+use crate::repository::manifest::{DownloadFile, Library};
+
+use super::rules::Environment;
+
+/// OS names that have native libraries in the manifests.
+const SUPPORTED_OS: [&str; 3] = ["windows", "osx", "linux"];
+
+#[derive(Debug, thiserror::Error)]
+pub enum NativesError {
+    #[error("Native libraries are not available for {os} ({arch})")]
+    UnsupportedPlatform { os: String, arch: String },
+}
+
+/// Native library archive that has to be extracted before the launch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NativeLibrary {
+    pub path: PathBuf,
+    /// Path prefixes inside the archive that must not be extracted.
+    /// `None` means only the native libraries themselves are extracted.
+    pub exclude: Option<Vec<String>>,
+}
+
+pub fn ensure_supported(environment: &Environment) -> Result<(), NativesError> {
+    if SUPPORTED_OS.contains(&environment.os_name.as_str()) {
+        Ok(())
+    } else {
+        Err(NativesError::UnsupportedPlatform {
+            os: environment.os_name.clone(),
+            arch: environment.arch.clone(),
+        })
+    }
+}
+
+/// Returns the classifier of the library's natives for the environment.
+///
+/// Used by libraries that ship their natives inside the `classifiers` (LWJGL 2 and older LWJGL 3).
+pub fn native_classifier(
+    environment: &Environment,
+    library: &Library,
+) -> Result<Option<String>, NativesError> {
+    let Some(natives) = &library.natives else {
+        return Ok(None);
+    };
+
+    ensure_supported(environment)?;
+
+    Ok(natives
+        .get(&environment.os_name)
+        .map(|classifier| classifier.replace("${arch}", bitness(&environment.arch))))
+}
+
+/// Returns the file with the library's natives for the environment.
+pub fn native_download<'a>(
+    environment: &Environment,
+    library: &'a Library,
+) -> Result<Option<&'a DownloadFile>, NativesError> {
+    Ok(
+        native_classifier(environment, library)?.and_then(|classifier| {
+            library
+                .downloads
+                .classifiers
+                .as_ref()
+                .and_then(|classifiers| classifiers.get(&classifier))
+        }),
+    )
+}
+
+/// Returns `false` for native artifacts built for another architecture.
+///
+/// Since LWJGL 3.3 natives are separate libraries (e.g. `org.lwjgl:lwjgl:3.3.1:natives-linux-arm64`)
+/// whose rules only check the OS name.
+/// Artifacts without an architecture suffix are built for `x86_64` but are kept on every architecture
+/// so that the game can still use them through emulation.
+pub fn is_for_arch(environment: &Environment, library: &Library) -> bool {
+    let Some(classifier) = library.name.split(':').nth(3) else {
+        return true;
+    };
+
+    let Some(platform) = classifier.strip_prefix("natives-") else {
+        return true;
+    };
+
+    platform
+        .split_once('-')
+        .is_none_or(|(_, arch)| Environment::normalize_arch(arch) == environment.arch)
+}
+
+/// Value of `${arch}` in the classifiers.
+fn bitness(arch: &str) -> &'static str {
+    match arch {
+        "x86" | "arm32" => "32",
+        _ => "64",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::instance::launch::rules::EnabledFeatures;
+
+    use super::*;
+
+    fn environment(os_name: &str, arch: &str) -> Environment {
+        Environment {
+            os_name: os_name.to_owned(),
+            os_version: String::new(),
+            arch: arch.to_owned(),
+            features: EnabledFeatures::default(),
+        }
+    }
+
+    fn library(json: &str) -> Library {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn classifier_test() {
+        // lwjgl-platform of 1.8.9
+        let library = library(
+            r#"{
+                "downloads": {
+                    "classifiers": {
+                        "natives-linux": {"path": "linux.jar", "sha1": "", "size": 0, "url": ""},
+                        "natives-osx": {"path": "osx.jar", "sha1": "", "size": 0, "url": ""},
+                        "natives-windows-64": {"path": "windows-64.jar", "sha1": "", "size": 0, "url": ""}
+                    }
+                },
+                "extract": {"exclude": ["META-INF/"]},
+                "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.4-nightly-20150209",
+                "natives": {"linux": "natives-linux", "osx": "natives-osx", "windows": "natives-windows-${arch}"}
+            }"#,
+        );
+
+        let path = |environment: &Environment| {
+            native_download(environment, &library)
+                .unwrap()
+                .and_then(|file| file.path.clone())
+        };
+
+        assert_eq!(path(&environment("linux", "x86_64")).unwrap(), "linux.jar");
+        assert_eq!(path(&environment("osx", "arm64")).unwrap(), "osx.jar");
+        assert_eq!(
+            path(&environment("windows", "x86_64")).unwrap(),
+            "windows-64.jar"
+        );
+        assert!(path(&environment("windows", "x86")).is_none());
+
+        assert!(matches!(
+            native_download(&environment("freebsd", "x86_64"), &library),
+            Err(NativesError::UnsupportedPlatform { .. })
+        ));
+
+        assert_eq!(
+            library.extract.unwrap().exclude,
+            vec!["META-INF/".to_owned()]
+        );
+    }
+
+    #[test]
+    fn arch_test() {
+        let library =
+            |name: &str| self::library(&format!(r#"{{"downloads": {{}}, "name": "{name}"}}"#));
+
+        let linux_x64 = environment("linux", "x86_64");
+        let linux_arm64 = environment("linux", "arm64");
+
+        let arm64 = library("org.lwjgl:lwjgl:3.3.1:natives-linux-arm64");
+        let default = library("org.lwjgl:lwjgl:3.3.1:natives-linux");
+        let regular = library("org.lwjgl:lwjgl:3.3.1");
+
+        assert!(!is_for_arch(&linux_x64, &arm64));
+        assert!(is_for_arch(&linux_arm64, &arm64));
+        assert!(is_for_arch(&linux_x64, &default));
+        assert!(is_for_arch(&linux_arm64, &default));
+        assert!(is_for_arch(&linux_x64, &regular));
+    }
+}
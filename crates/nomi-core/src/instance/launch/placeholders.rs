@@ -0,0 +1,298 @@
+use std::{borrow::Cow, path::Path};
+
+use crate::{NOMI_NAME, NOMI_VERSION};
+
+use super::{quick_play::QuickPlay, Resolution, CLASSPATH_SEPARATOR};
+
+/// Part of a string with `${name}` placeholders.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token<'a> {
+    Text(&'a str),
+    /// Name of the placeholder without `${` and `}`.
+    Placeholder(&'a str),
+}
+
+/// Splits the source into text and placeholders.
+///
+/// An unterminated `${` is kept as text.
+pub fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut tokens = Vec::new();
+    let mut rest = source;
+
+    while let Some(start) = rest.find("${") {
+        let Some(length) = rest[start + 2..].find('}') else {
+            break;
+        };
+
+        if start > 0 {
+            tokens.push(Token::Text(&rest[..start]));
+        }
+        tokens.push(Token::Placeholder(&rest[start + 2..start + 2 + length]));
+
+        rest = &rest[start + 2 + length + 1..];
+    }
+
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest));
+    }
+
+    tokens
+}
+
+/// Result of [`substitute`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Substitution {
+    pub value: String,
+    /// Names of the placeholders `resolve` knows nothing about. They are kept in the value as is.
+    pub unknown: Vec<String>,
+}
+
+/// Replaces every placeholder `resolve` returns a value for.
+pub fn substitute<'a>(
+    source: &str,
+    resolve: impl Fn(&str) -> Option<Cow<'a, str>>,
+) -> Substitution {
+    let mut value = String::with_capacity(source.len());
+    let mut unknown = Vec::new();
+
+    for token in tokenize(source) {
+        match token {
+            Token::Text(text) => value.push_str(text),
+            Token::Placeholder(name) => {
+                if let Some(resolved) = resolve(name) {
+                    value.push_str(&resolved);
+                } else {
+                    value.push_str("${");
+                    value.push_str(name);
+                    value.push('}');
+                    unknown.push(name.to_owned());
+                }
+            }
+        }
+    }
+
+    Substitution { value, unknown }
+}
+
+/// Placeholders of the launch arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Variable {
+    AuthPlayerName,
+    AuthUuid,
+    AuthAccessToken,
+    AuthSession,
+    AuthXuid,
+    ClientId,
+    UserType,
+    UserProperties,
+    VersionName,
+    VersionType,
+    GameDirectory,
+    AssetsRoot,
+    GameAssets,
+    AssetsIndexName,
+    NativesDirectory,
+    LibraryDirectory,
+    ClasspathSeparator,
+    Classpath,
+    LauncherName,
+    LauncherVersion,
+    ResolutionWidth,
+    ResolutionHeight,
+    QuickPlaySingleplayer,
+    QuickPlayMultiplayer,
+    QuickPlayRealms,
+}
+
+impl Variable {
+    pub fn from_name(name: &str) -> Option<Self> {
+        let variable = match name {
+            "auth_player_name" => Self::AuthPlayerName,
+            "auth_uuid" => Self::AuthUuid,
+            "auth_access_token" => Self::AuthAccessToken,
+            "auth_session" => Self::AuthSession,
+            "auth_xuid" => Self::AuthXuid,
+            "clientid" => Self::ClientId,
+            "user_type" => Self::UserType,
+            "user_properties" => Self::UserProperties,
+            "version_name" => Self::VersionName,
+            "version_type" => Self::VersionType,
+            "game_directory" => Self::GameDirectory,
+            "assets_root" => Self::AssetsRoot,
+            "game_assets" => Self::GameAssets,
+            "assets_index_name" => Self::AssetsIndexName,
+            "natives_directory" => Self::NativesDirectory,
+            "library_directory" => Self::LibraryDirectory,
+            "classpath_separator" => Self::ClasspathSeparator,
+            "classpath" => Self::Classpath,
+            "launcher_name" => Self::LauncherName,
+            "launcher_version" => Self::LauncherVersion,
+            "resolution_width" => Self::ResolutionWidth,
+            "resolution_height" => Self::ResolutionHeight,
+            "quickPlaySingleplayer" => Self::QuickPlaySingleplayer,
+            "quickPlayMultiplayer" => Self::QuickPlayMultiplayer,
+            "quickPlayRealms" => Self::QuickPlayRealms,
+            _ => return None,
+        };
+
+        Some(variable)
+    }
+}
+
+/// Values of the launch argument placeholders.
+#[derive(Debug, Clone)]
+pub struct Variables<'a> {
+    pub username: &'a str,
+    pub uuid: &'a str,
+    /// `None` for offline accounts.
+    pub access_token: Option<&'a str>,
+    pub xuid: Option<&'a str>,
+    pub client_id: Option<&'a str>,
+    pub version_name: &'a str,
+    pub version_type: &'a str,
+    pub game_dir: &'a Path,
+    pub assets_dir: &'a Path,
+    pub assets_index_name: &'a str,
+    pub natives_dir: &'a Path,
+    pub libraries_dir: &'a Path,
+    pub classpath: &'a str,
+    pub resolution: Resolution,
+    pub quick_play: Option<&'a QuickPlay>,
+}
+
+impl<'a> Variables<'a> {
+    /// Value the game gets for the missing authentication data.
+    const MISSING: &'static str = "null";
+
+    pub fn value(&self, variable: Variable) -> Cow<'a, str> {
+        let quick_play = |target: fn(&QuickPlay) -> Option<&str>| {
+            Cow::Owned(
+                self.quick_play
+                    .and_then(target)
+                    .unwrap_or_default()
+                    .to_owned(),
+            )
+        };
+
+        match variable {
+            Variable::AuthPlayerName => self.username.into(),
+            Variable::AuthUuid => self.uuid.into(),
+            Variable::AuthAccessToken => self.access_token.unwrap_or(Self::MISSING).into(),
+            Variable::AuthSession => Self::MISSING.into(),
+            Variable::UserProperties => "{}".into(),
+            Variable::AuthXuid => self.xuid.unwrap_or(Self::MISSING).into(),
+            Variable::ClientId => self.client_id.unwrap_or(Self::MISSING).into(),
+            Variable::UserType => if self.access_token.is_some() {
+                "msa"
+            } else {
+                "legacy"
+            }
+            .into(),
+            Variable::VersionName => self.version_name.into(),
+            Variable::VersionType => self.version_type.into(),
+            Variable::GameDirectory => self.game_dir.to_string_lossy(),
+            Variable::AssetsRoot | Variable::GameAssets => self.assets_dir.to_string_lossy(),
+            Variable::AssetsIndexName => self.assets_index_name.into(),
+            Variable::NativesDirectory => self.natives_dir.to_string_lossy(),
+            Variable::LibraryDirectory => self.libraries_dir.to_string_lossy(),
+            Variable::ClasspathSeparator => CLASSPATH_SEPARATOR.into(),
+            Variable::Classpath => self.classpath.into(),
+            Variable::LauncherName => NOMI_NAME.into(),
+            Variable::LauncherVersion => NOMI_VERSION.into(),
+            Variable::ResolutionWidth => self.resolution.width.to_string().into(),
+            Variable::ResolutionHeight => self.resolution.height.to_string().into(),
+            Variable::QuickPlaySingleplayer => quick_play(QuickPlay::world),
+            Variable::QuickPlayMultiplayer => quick_play(QuickPlay::address),
+            Variable::QuickPlayRealms => quick_play(QuickPlay::realm),
+        }
+    }
+
+    pub fn substitute(&self, source: &str) -> Substitution {
+        substitute(source, |name| {
+            Variable::from_name(name).map(|variable| self.value(variable))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn tokenize_test() {
+        assert_eq!(
+            tokenize("-Djava.library.path=${natives_directory}"),
+            [
+                Token::Text("-Djava.library.path="),
+                Token::Placeholder("natives_directory")
+            ]
+        );
+        assert_eq!(
+            tokenize("${a}${b}c"),
+            [
+                Token::Placeholder("a"),
+                Token::Placeholder("b"),
+                Token::Text("c")
+            ]
+        );
+        assert_eq!(
+            tokenize("price: $5 ${unterminated"),
+            [Token::Text("price: $5 ${unterminated")]
+        );
+        assert!(tokenize("").is_empty());
+    }
+
+    #[test]
+    fn substitute_test() {
+        let game_dir = PathBuf::from("/games/minecraft");
+        let libraries_dir = PathBuf::from("/games/libraries");
+        let quick_play = QuickPlay::Multiplayer("localhost".into());
+
+        let variables = Variables {
+            username: "Nomi",
+            uuid: "uuid",
+            access_token: None,
+            xuid: None,
+            client_id: None,
+            version_name: "1.20.4",
+            version_type: "release",
+            game_dir: &game_dir,
+            assets_dir: &game_dir,
+            assets_index_name: "12",
+            natives_dir: &game_dir,
+            libraries_dir: &libraries_dir,
+            classpath: "a.jar",
+            resolution: Resolution::default(),
+            quick_play: Some(&quick_play),
+        };
+
+        let substitution = variables.substitute("-DlibraryDirectory=${library_directory}");
+        assert_eq!(substitution.value, "-DlibraryDirectory=/games/libraries");
+        assert!(substitution.unknown.is_empty());
+
+        assert_eq!(
+            variables
+                .substitute(
+                    "${version_name}${classpath_separator}${resolution_width}x${resolution_height}"
+                )
+                .value,
+            format!("1.20.4{CLASSPATH_SEPARATOR}854x480")
+        );
+        assert_eq!(variables.substitute("${user_type}").value, "legacy");
+        assert_eq!(variables.substitute("${auth_xuid}").value, "null");
+        assert_eq!(
+            variables.substitute("${quickPlayMultiplayer}").value,
+            "localhost"
+        );
+        assert_eq!(variables.substitute("${quickPlaySingleplayer}").value, "");
+
+        let substitution = variables.substitute("--foo ${unknown_thing} ${game_directory}");
+        assert_eq!(
+            substitution.value,
+            "--foo ${unknown_thing} /games/minecraft"
+        );
+        assert_eq!(substitution.unknown, ["unknown_thing"]);
+    }
+}
@@ -0,0 +1,144 @@
+use std::fmt::Display;
+
+use serde::{Deserialize, Serialize};
+
+use crate::repository::manifest::{Argument, Arguments, Manifest, Value};
+
+pub const DEFAULT_SERVER_PORT: u16 = 25565;
+
+/// Where the game goes right after it starts.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum QuickPlay {
+    /// Name of the world's directory inside `saves`.
+    Singleplayer(String),
+    /// Server address in the `host[:port]` form.
+    Multiplayer(String),
+    /// Realm id.
+    Realm(String),
+}
+
+impl QuickPlay {
+    pub fn world(&self) -> Option<&str> {
+        match self {
+            Self::Singleplayer(world) => Some(world),
+            _ => None,
+        }
+    }
+
+    pub fn address(&self) -> Option<&str> {
+        match self {
+            Self::Multiplayer(address) => Some(address),
+            _ => None,
+        }
+    }
+
+    pub fn realm(&self) -> Option<&str> {
+        match self {
+            Self::Realm(realm) => Some(realm),
+            _ => None,
+        }
+    }
+
+    /// Splits the multiplayer address into the host and the port.
+    ///
+    /// Returns `None` for other targets.
+    pub fn server(&self) -> Option<(&str, u16)> {
+        let address = self.address()?;
+
+        // IPv6 addresses are written as `[::1]:25565`
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) if !host.contains(':') || host.ends_with(']') => {
+                (host, port.parse().ok())
+            }
+            _ => (address, None),
+        };
+
+        Some((
+            host.trim_start_matches('[').trim_end_matches(']'),
+            port.unwrap_or(DEFAULT_SERVER_PORT),
+        ))
+    }
+
+    /// Arguments for versions without Quick Play support (before 1.20).
+    ///
+    /// Only servers are supported by them.
+    pub fn legacy_arguments(&self) -> Option<[String; 4]> {
+        self.server().map(|(host, port)| {
+            [
+                "--server".to_owned(),
+                host.to_owned(),
+                "--port".to_owned(),
+                port.to_string(),
+            ]
+        })
+    }
+}
+
+impl Display for QuickPlay {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            QuickPlay::Singleplayer(world) => write!(f, "world {world}"),
+            QuickPlay::Multiplayer(address) => write!(f, "server {address}"),
+            QuickPlay::Realm(realm) => write!(f, "realm {realm}"),
+        }
+    }
+}
+
+/// Returns `true` if the version's game arguments contain Quick Play options (1.20+).
+pub fn is_quick_play_supported(manifest: &Manifest) -> bool {
+    let Arguments::New { game, .. } = &manifest.arguments else {
+        return false;
+    };
+
+    game.iter().any(|argument| match argument {
+        Argument::Struct {
+            value: Value::String(value),
+            ..
+        }
+        | Argument::String(value) => value.contains("${quickPlay"),
+        Argument::Struct {
+            value: Value::Array(values),
+            ..
+        } => values.iter().any(|value| value.contains("${quickPlay")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn server_test() {
+        fn server(address: &str) -> (String, u16) {
+            let quick_play = QuickPlay::Multiplayer(address.into());
+            let (host, port) = quick_play.server().unwrap();
+            (host.to_owned(), port)
+        }
+
+        assert_eq!(
+            server("play.example.com"),
+            ("play.example.com".into(), 25565)
+        );
+        assert_eq!(
+            server("play.example.com:25577"),
+            ("play.example.com".into(), 25577)
+        );
+        assert_eq!(server("[::1]:25570"), ("::1".into(), 25570));
+        assert_eq!(server("::1"), ("::1".into(), 25565));
+
+        assert!(QuickPlay::Realm("123".into()).server().is_none());
+    }
+
+    #[test]
+    fn legacy_arguments_test() {
+        assert_eq!(
+            QuickPlay::Multiplayer("localhost:25570".into())
+                .legacy_arguments()
+                .unwrap(),
+            ["--server", "localhost", "--port", "25570"]
+        );
+        assert!(QuickPlay::Singleplayer("New World".into())
+            .legacy_arguments()
+            .is_none());
+    }
+}
@@ -1,50 +1,336 @@
-use std::env;
+use std::sync::LazyLock;
 
-use crate::repository::manifest::{Action, Library, Rule, RuleKind};
+use crate::repository::manifest::{Action, Features, Library, Os, Rule, RuleKind};
 
-pub fn is_rule_passes(rule: &Rule) -> bool {
-    match rule.action {
-        Action::Allow => match rule.rule_kind.as_ref() {
-            Some(RuleKind::GameRule(features)) => {
-                // let is_demo = features.is_demo_user.map_or(true, |demo| )
+use super::natives;
 
-                // TODO: Make this check based on the settings
-                let custom_res = features.has_custom_resolution.unwrap_or(false);
-                let demo = features.is_demo_user.unwrap_or(false);
-                // let quick_realms = features.is_quick_play_realms.unwrap_or(false);
+/// Features of the launch that game arguments' rules are checked against.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct EnabledFeatures {
+    pub is_demo_user: bool,
+    pub has_custom_resolution: bool,
+    pub has_quick_plays_support: bool,
+    pub is_quick_play_singleplayer: bool,
+    pub is_quick_play_multiplayer: bool,
+    pub is_quick_play_realms: bool,
+}
+
+impl EnabledFeatures {
+    /// Returns `true` if every feature required by the rule has the same state.
+    pub fn matches(&self, features: &Features) -> bool {
+        [
+            (features.is_demo_user, self.is_demo_user),
+            (features.has_custom_resolution, self.has_custom_resolution),
+            (
+                features.has_quick_plays_support,
+                self.has_quick_plays_support,
+            ),
+            (
+                features.is_quick_play_singleplayer,
+                self.is_quick_play_singleplayer,
+            ),
+            (
+                features.is_quick_play_multiplayer,
+                self.is_quick_play_multiplayer,
+            ),
+            (features.is_quick_play_realms, self.is_quick_play_realms),
+        ]
+        .into_iter()
+        .all(|(required, enabled)| required.is_none_or(|required| required == enabled))
+    }
+}
+
+static CURRENT_OS_VERSION: LazyLock<String> = LazyLock::new(os_version);
+
+/// Everything manifest rules are checked against.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Environment {
+    /// OS name as used by the manifests: `windows`, `osx` or `linux`.
+    pub os_name: String,
+    /// Matched against the `version` regex of the rule.
+    pub os_version: String,
+    /// Architecture as used by the manifests: `x86`, `x86_64` or `arm64`.
+    pub arch: String,
+    pub features: EnabledFeatures,
+}
+
+impl Environment {
+    /// Environment of the running system with all features disabled.
+    pub fn current() -> Self {
+        Self {
+            os_name: os_name(std::env::consts::OS).to_owned(),
+            os_version: CURRENT_OS_VERSION.clone(),
+            arch: Self::normalize_arch(std::env::consts::ARCH).to_owned(),
+            features: EnabledFeatures::default(),
+        }
+    }
+
+    #[must_use]
+    pub fn with_features(mut self, features: EnabledFeatures) -> Self {
+        self.features = features;
+        self
+    }
+
+    /// Evaluates the rules the same way the official launcher does.
+    ///
+    /// An empty list allows everything. Otherwise everything is disallowed
+    /// unless a rule matches, and the last matching rule decides.
+    pub fn is_allowed(&self, rules: &[Rule]) -> bool {
+        if rules.is_empty() {
+            return true;
+        }
 
-                // It turns off the quick play
-                let quick_realms = true;
+        rules
+            .iter()
+            .rev()
+            .find(|rule| self.matches(rule))
+            .is_some_and(|rule| matches!(rule.action, Action::Allow))
+    }
 
-                !(custom_res || demo || quick_realms)
-            }
-            Some(RuleKind::JvmRule(os)) => os
-                .name
-                .as_ref()
-                .map_or(true, |target_os| dbg!(env::consts::OS == target_os)),
+    /// Checks the library's rules and skips native artifacts built for another architecture.
+    pub fn is_library_allowed(&self, library: &Library) -> bool {
+        library
+            .rules
+            .as_ref()
+            .is_none_or(|rules| self.is_allowed(rules))
+            && natives::is_for_arch(self, library)
+    }
 
+    /// Returns `true` if all conditions of the rule are met regardless of its action.
+    pub fn matches(&self, rule: &Rule) -> bool {
+        match &rule.rule_kind {
+            Some(RuleKind::GameRule(features)) => self.features.matches(features),
+            Some(RuleKind::JvmRule(os)) => self.matches_os(os),
             None => true,
-        },
-        Action::Disallow => false,
+        }
+    }
+
+    /// Converts Rust's and the manifests' names of the architecture to the same form.
+    pub fn normalize_arch(arch: &str) -> &str {
+        match arch {
+            "x86_64" | "amd64" | "x64" => "x86_64",
+            "aarch64" | "aarch_64" | "arm64" => "arm64",
+            "x86" | "i386" | "i686" => "x86",
+            "arm" | "arm32" => "arm32",
+            arch => arch,
+        }
+    }
+
+    fn matches_os(&self, os: &Os) -> bool {
+        let name = os.name.as_ref().is_none_or(|name| *name == self.os_name);
+        let arch = os
+            .arch
+            .as_ref()
+            .is_none_or(|target| Self::normalize_arch(target) == self.arch);
+        let version = os
+            .version
+            .as_ref()
+            .is_none_or(|version| version.is_match(&self.os_version));
+
+        name && arch && version
     }
 }
 
-pub fn is_all_rules_passed(rules: &[Rule]) -> bool {
-    for rule in rules {
-        let satisfied = is_rule_passes(rule);
-        let use_lib = matches!(rule.action, Action::Allow);
+fn os_name(os: &str) -> &str {
+    match os {
+        "macos" => "osx",
+        os => os,
+    }
+}
 
-        if satisfied && !use_lib || !satisfied && use_lib {
-            return false;
+#[cfg(target_os = "linux")]
+fn os_version() -> String {
+    std::fs::read_to_string("/proc/sys/kernel/osrelease")
+        .map(|version| version.trim().to_owned())
+        .unwrap_or_default()
+}
+
+#[cfg(target_os = "macos")]
+fn os_version() -> String {
+    std::process::Command::new("sw_vers")
+        .arg("-productVersion")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+        .unwrap_or_default()
+}
+
+/// `Microsoft Windows [Version 10.0.19045.3570]` -> `10.0.19045.3570`
+#[cfg(target_os = "windows")]
+fn os_version() -> String {
+    std::process::Command::new("cmd")
+        .args(["/C", "ver"])
+        .output()
+        .ok()
+        .and_then(|output| {
+            let output = String::from_utf8_lossy(&output.stdout).into_owned();
+            output
+                .split_whitespace()
+                .last()
+                .map(|version| version.trim_end_matches(']').to_owned())
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn os_version() -> String {
+    String::new()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::repository::manifest::Argument;
+
+    fn environment(os_name: &str, os_version: &str, arch: &str) -> Environment {
+        Environment {
+            os_name: os_name.to_owned(),
+            os_version: os_version.to_owned(),
+            arch: arch.to_owned(),
+            features: EnabledFeatures::default(),
         }
     }
 
-    true
-}
+    fn rules(json: &str) -> Vec<Rule> {
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn library_rules_test() {
+        // lwjgl 2 libraries of 1.8.9
+        let except_osx =
+            rules(r#"[{"action": "allow"}, {"action": "disallow", "os": {"name": "osx"}}]"#);
+        let only_osx = rules(r#"[{"action": "allow", "os": {"name": "osx"}}]"#);
+
+        let linux = environment("linux", "6.1.0", "x86_64");
+        let osx = environment("osx", "14.4", "arm64");
+
+        assert!(linux.is_allowed(&except_osx));
+        assert!(!osx.is_allowed(&except_osx));
+        assert!(!linux.is_allowed(&only_osx));
+        assert!(osx.is_allowed(&only_osx));
+        assert!(linux.is_allowed(&[]));
+    }
+
+    #[test]
+    fn jvm_arguments_rules_test() {
+        let windows_10 =
+            rules(r#"[{"action": "allow", "os": {"name": "windows", "version": "^10\\."}}]"#);
+        let x86 = rules(r#"[{"action": "allow", "os": {"arch": "x86"}}]"#);
+
+        assert!(environment("windows", "10.0.19045", "x86_64").is_allowed(&windows_10));
+        assert!(!environment("windows", "6.1.7601", "x86_64").is_allowed(&windows_10));
+        assert!(!environment("linux", "10.0", "x86_64").is_allowed(&windows_10));
+
+        assert!(environment("windows", "10.0", "x86").is_allowed(&x86));
+        assert!(!environment("windows", "10.0", "x86_64").is_allowed(&x86));
+    }
+
+    #[test]
+    fn manifest_fixture_test() {
+        // LWJGL libraries of 1.7.10, trimmed to the name, natives and rules
+        let libraries: Vec<Library> = serde_json::from_str(
+            r#"[
+                {
+                    "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.0",
+                    "downloads": {},
+                    "natives": {"linux": "natives-linux", "osx": "natives-osx", "windows": "natives-windows"},
+                    "extract": {"exclude": ["META-INF/"]},
+                    "rules": [
+                        {"action": "allow", "os": {"name": "osx", "version": "^10\\.5\\.\\d$"}}
+                    ]
+                },
+                {
+                    "name": "org.lwjgl.lwjgl:lwjgl-platform:2.9.1-nightly-20140722",
+                    "downloads": {},
+                    "natives": {"linux": "natives-linux", "osx": "natives-osx", "windows": "natives-windows"},
+                    "extract": {"exclude": ["META-INF/"]},
+                    "rules": [
+                        {"action": "allow"},
+                        {"action": "disallow", "os": {"name": "osx", "version": "^10\\.5\\.\\d$"}}
+                    ]
+                }
+            ]"#,
+        )
+        .unwrap();
+
+        let allowed = |environment: &Environment| {
+            libraries
+                .iter()
+                .filter(|library| environment.is_library_allowed(library))
+                .map(|library| library.name.as_str())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            allowed(&environment("osx", "10.5.8", "x86_64")),
+            ["org.lwjgl.lwjgl:lwjgl-platform:2.9.0"]
+        );
+        assert_eq!(
+            allowed(&environment("osx", "10.15.7", "x86_64")),
+            ["org.lwjgl.lwjgl:lwjgl-platform:2.9.1-nightly-20140722"]
+        );
+        assert_eq!(
+            allowed(&environment("linux", "10.5.1", "x86_64")),
+            ["org.lwjgl.lwjgl:lwjgl-platform:2.9.1-nightly-20140722"]
+        );
+
+        // JVM arguments of 1.19.4
+        let arguments: Vec<Argument> = serde_json::from_str(
+            r#"[
+                {"rules": [{"action": "allow", "os": {"name": "osx"}}], "value": ["-XstartOnFirstThread"]},
+                {"rules": [{"action": "allow", "os": {"name": "windows"}}], "value": "-XX:HeapDumpPath=MojangTricksIntelDriversForPerformance_javaw.exe_minecraft.exe.heapdump"},
+                {"rules": [{"action": "allow", "os": {"name": "windows", "version": "^10\\."}}], "value": ["-Dos.name=Windows 10", "-Dos.version=10.0"]},
+                {"rules": [{"action": "allow", "os": {"arch": "x86"}}], "value": "-Xss1M"},
+                "-Djava.library.path=${natives_directory}"
+            ]"#,
+        )
+        .unwrap();
+
+        let allowed = |environment: &Environment| {
+            arguments
+                .iter()
+                .filter(|argument| match argument {
+                    Argument::Struct { rules, .. } => environment.is_allowed(rules),
+                    Argument::String(_) => true,
+                })
+                .count()
+        };
+
+        assert_eq!(
+            allowed(&environment("windows", "10.0.19045.3570", "x86_64")),
+            3
+        );
+        assert_eq!(allowed(&environment("windows", "6.1.7601", "x86")), 3);
+        assert_eq!(allowed(&environment("osx", "14.4", "arm64")), 2);
+        assert_eq!(allowed(&environment("linux", "6.1.0", "x86_64")), 1);
+    }
+
+    #[test]
+    fn game_arguments_rules_test() {
+        let custom_resolution =
+            rules(r#"[{"action": "allow", "features": {"has_custom_resolution": true}}]"#);
+        let demo = rules(r#"[{"action": "allow", "features": {"is_demo_user": true}}]"#);
+
+        let environment = environment("linux", "6.1.0", "x86_64");
+        assert!(!environment.is_allowed(&custom_resolution));
+        assert!(!environment.is_allowed(&demo));
+
+        let environment = environment.with_features(EnabledFeatures {
+            has_custom_resolution: true,
+            ..Default::default()
+        });
+        assert!(environment.is_allowed(&custom_resolution));
+        assert!(!environment.is_allowed(&demo));
+    }
 
-pub fn is_library_passes(lib: &Library) -> bool {
-    match lib.rules.as_ref() {
-        Some(rules) => dbg!(is_all_rules_passed(rules)),
-        None => true,
+    #[test]
+    fn arch_test() {
+        assert_eq!(
+            Environment::normalize_arch("aarch64"),
+            Environment::normalize_arch("arm64")
+        );
+        assert_eq!(Environment::normalize_arch("amd64"), "x86_64");
+        assert_eq!(os_name("macos"), "osx");
     }
 }
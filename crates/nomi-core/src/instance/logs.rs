@@ -0,0 +1,463 @@
+use std::{fmt::Display, path::Path, sync::LazyLock};
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::error::{RecvError, TryRecvError};
+
+use super::supervisor::{GameHandle, GameOutput};
+
+const EVENT_START: &str = "<log4j:Event";
+const EVENT_END: &str = "</log4j:Event>";
+
+/// `[12:34:56] [Render thread/INFO]: message` and Fabric's
+/// `[12:34:56] [main/INFO] (FabricLoader) message`
+static PLAIN_HEADER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"^\[(\d{2}):(\d{2}):(\d{2})(?:\.\d+)?\] \[([^\]]+)/([A-Za-z]+)\](?: \(([^)]*)\))?:? ?(.*)$",
+    )
+    .expect("the regex is valid")
+});
+
+/// `java.lang.IllegalStateException: message`
+static THROWABLE_HEADER: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[\w$]+(?:\.[\w$]+)+(?:Exception|Error|Throwable)\b").expect("the regex is valid")
+});
+
+static XML_ATTRIBUTE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(\w+)="([^"]*)""#).expect("the regex is valid"));
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LogLevel {
+    Trace,
+    Debug,
+    Info,
+    Warn,
+    Error,
+    Fatal,
+}
+
+impl LogLevel {
+    pub const ALL: [LogLevel; 6] = [
+        LogLevel::Trace,
+        LogLevel::Debug,
+        LogLevel::Info,
+        LogLevel::Warn,
+        LogLevel::Error,
+        LogLevel::Fatal,
+    ];
+
+    pub fn parse(level: &str) -> Option<Self> {
+        match level.to_ascii_uppercase().as_str() {
+            "TRACE" => Some(Self::Trace),
+            "DEBUG" => Some(Self::Debug),
+            "INFO" => Some(Self::Info),
+            "WARN" | "WARNING" => Some(Self::Warn),
+            "ERROR" => Some(Self::Error),
+            "FATAL" => Some(Self::Fatal),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogLevel::Trace => "TRACE",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+            LogLevel::Fatal => "FATAL",
+        }
+    }
+}
+
+impl Display for LogLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Timestamp {
+    /// Milliseconds since the unix epoch. Provided by the XML layout.
+    UnixMillis(u64),
+    /// Time of the day. Provided by the plain-text layout.
+    TimeOfDay { hour: u8, minute: u8, second: u8 },
+}
+
+impl Display for Timestamp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Timestamp::UnixMillis(millis) => {
+                let seconds = millis / 1000 % (24 * 60 * 60);
+                write!(
+                    f,
+                    "{:02}:{:02}:{:02} UTC",
+                    seconds / 3600,
+                    seconds / 60 % 60,
+                    seconds % 60
+                )
+            }
+            Timestamp::TimeOfDay {
+                hour,
+                minute,
+                second,
+            } => write!(f, "{hour:02}:{minute:02}:{second:02}"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Default)]
+pub struct LogRecord {
+    pub timestamp: Option<Timestamp>,
+    /// `None` if the line is not produced by the game's logger.
+    pub level: Option<LogLevel>,
+    pub thread: Option<String>,
+    pub logger: Option<String>,
+    pub message: String,
+    pub throwable: Option<String>,
+}
+
+impl LogRecord {
+    fn raw(line: &str) -> Self {
+        Self {
+            message: line.to_owned(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Incremental parser of the game's output.
+///
+/// Supports both Mojang's log4j XML layout and the plain-text layout.
+/// Plain-text records may span several lines (e.g. stack traces)
+/// so they are returned when the next record starts or [`LogParser::flush`] is called.
+#[derive(Debug, Default)]
+pub struct LogParser {
+    xml_buffer: Option<String>,
+    pending: Option<LogRecord>,
+}
+
+impl LogParser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feeds a single line and returns the records completed by it.
+    pub fn feed(&mut self, line: &str) -> Vec<LogRecord> {
+        let mut records = vec![];
+
+        if let Some(buffer) = self.xml_buffer.as_mut() {
+            buffer.push_str(line);
+            buffer.push('\n');
+        } else if line.trim_start().starts_with(EVENT_START) {
+            records.extend(self.flush());
+            self.xml_buffer = Some(format!("{line}\n"));
+        } else if !line.trim().is_empty() {
+            records.extend(self.feed_plain(line));
+        }
+
+        if self
+            .xml_buffer
+            .as_ref()
+            .is_some_and(|buffer| buffer.contains(EVENT_END))
+        {
+            let buffer = self.xml_buffer.take().unwrap_or_default();
+            let (event, rest) = buffer
+                .split_once(EVENT_END)
+                .unwrap_or((buffer.as_str(), ""));
+
+            records.extend(parse_xml_event(event));
+
+            for line in rest.lines().filter(|line| !line.trim().is_empty()) {
+                records.extend(self.feed(line));
+            }
+        }
+
+        records
+    }
+
+    /// Returns the record that is still waiting for continuation lines.
+    pub fn flush(&mut self) -> Option<LogRecord> {
+        self.pending.take()
+    }
+
+    fn feed_plain(&mut self, line: &str) -> Option<LogRecord> {
+        if let Some(record) = parse_plain_header(line) {
+            return self.pending.replace(record);
+        }
+
+        match self.pending.as_mut() {
+            Some(pending) if is_throwable_line(line) || pending.throwable.is_some() => {
+                let throwable = pending.throwable.get_or_insert_with(String::new);
+                if !throwable.is_empty() {
+                    throwable.push('\n');
+                }
+                throwable.push_str(line);
+                None
+            }
+            Some(pending) => {
+                pending.message.push('\n');
+                pending.message.push_str(line);
+                None
+            }
+            None => Some(LogRecord::raw(line)),
+        }
+    }
+
+    /// Parses all lines at once.
+    pub fn parse_str(content: &str) -> Vec<LogRecord> {
+        let mut parser = Self::new();
+        let mut records = content
+            .lines()
+            .flat_map(|line| parser.feed(line))
+            .collect::<Vec<_>>();
+        records.extend(parser.flush());
+        records
+    }
+
+    /// Parses the game's output until it exits and calls `on_record` for each record.
+    pub async fn parse_output(mut self, game: &GameHandle, mut on_record: impl FnMut(LogRecord)) {
+        let mut output = game.subscribe();
+
+        let mut on_output = |parser: &mut Self, output: Result<GameOutput, RecvError>| match output
+        {
+            Ok(output) => parser
+                .feed(&output.line)
+                .into_iter()
+                .for_each(&mut on_record),
+            Err(RecvError::Lagged(skipped)) => on_record(LogRecord::raw(&format!(
+                "... {skipped} lines were skipped ..."
+            ))),
+            Err(RecvError::Closed) => {}
+        };
+
+        loop {
+            tokio::select! {
+                biased;
+                output = output.recv() => on_output(&mut self, output),
+                _ = game.wait() => break,
+            }
+        }
+
+        // The whole output is sent before the exit is published
+        loop {
+            match output.try_recv() {
+                Ok(line) => on_output(&mut self, Ok(line)),
+                Err(TryRecvError::Lagged(skipped)) => {
+                    on_output(&mut self, Err(RecvError::Lagged(skipped)));
+                }
+                Err(TryRecvError::Empty | TryRecvError::Closed) => break,
+            }
+        }
+
+        self.flush().into_iter().for_each(on_record);
+    }
+}
+
+/// Reads and parses a log file, e.g. `logs/latest.log`.
+pub async fn read_log_file(path: impl AsRef<Path>) -> anyhow::Result<Vec<LogRecord>> {
+    let bytes = tokio::fs::read(path).await?;
+    Ok(LogParser::parse_str(&String::from_utf8_lossy(&bytes)))
+}
+
+fn is_throwable_line(line: &str) -> bool {
+    let trimmed = line.trim_start();
+
+    trimmed.starts_with("at ")
+        || trimmed.starts_with("Caused by: ")
+        || trimmed.starts_with("Suppressed: ")
+        || (trimmed.starts_with("... ") && trimmed.ends_with(" more"))
+        || THROWABLE_HEADER.is_match(trimmed)
+}
+
+fn parse_plain_header(line: &str) -> Option<LogRecord> {
+    let captures = PLAIN_HEADER.captures(line)?;
+    let number = |index| captures.get(index)?.as_str().parse::<u8>().ok();
+
+    Some(LogRecord {
+        timestamp: Some(Timestamp::TimeOfDay {
+            hour: number(1)?,
+            minute: number(2)?,
+            second: number(3)?,
+        }),
+        level: LogLevel::parse(&captures[5]),
+        thread: Some(captures[4].to_owned()),
+        logger: captures.get(6).map(|logger| logger.as_str().to_owned()),
+        message: captures[7].to_owned(),
+        throwable: None,
+    })
+}
+
+fn parse_xml_event(event: &str) -> Option<LogRecord> {
+    let start = event.find(EVENT_START)?;
+    let event = &event[start..];
+    let header_end = event.find('>')?;
+    let header = &event[..header_end];
+
+    let attribute = |name: &str| {
+        XML_ATTRIBUTE
+            .captures_iter(header)
+            .find(|captures| &captures[1] == name)
+            .map(|captures| unescape_xml(&captures[2]))
+    };
+
+    Some(LogRecord {
+        timestamp: attribute("timestamp")
+            .and_then(|timestamp| timestamp.parse().ok())
+            .map(Timestamp::UnixMillis),
+        level: attribute("level").and_then(|level| LogLevel::parse(&level)),
+        thread: attribute("thread"),
+        logger: attribute("logger"),
+        message: xml_element(event, "log4j:Message").unwrap_or_default(),
+        throwable: xml_element(event, "log4j:Throwable"),
+    })
+}
+
+fn xml_element(event: &str, name: &str) -> Option<String> {
+    let open = format!("<{name}>");
+    let close = format!("</{name}>");
+
+    let start = event.find(&open)? + open.len();
+    let end = start + event[start..].find(&close)?;
+    let content = &event[start..end];
+
+    let content = match content
+        .trim()
+        .strip_prefix("<![CDATA[")
+        .and_then(|content| content.strip_suffix("]]>"))
+    {
+        Some(cdata) => cdata.to_owned(),
+        None => unescape_xml(content),
+    };
+
+    Some(content.trim_end().to_owned())
+}
+
+fn unescape_xml(s: &str) -> String {
+    s.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#10;", "\n")
+        .replace("&#13;", "\r")
+        .replace("&amp;", "&")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const XML: &str = r#"<log4j:Event logger="net.minecraft.client.Minecraft" timestamp="1718000000000" level="INFO" thread="Render thread">
+  <log4j:Message><![CDATA[Setting user: Nomi]]></log4j:Message>
+</log4j:Event>
+
+<log4j:Event logger="net.minecraft.server.Main" timestamp="1718000001000" level="ERROR" thread="Server thread">
+  <log4j:Message><![CDATA[Encountered an unexpected exception]]></log4j:Message>
+  <log4j:Throwable><![CDATA[java.lang.NullPointerException: null
+	at net.minecraft.server.Main.main(Main.java:42)
+]]></log4j:Throwable>
+</log4j:Event>
+"#;
+
+    const PLAIN: &str = "[12:34:56] [Render thread/INFO]: Setting user: Nomi
+[12:34:57] [main/INFO] (FabricLoader/GameProvider) Loading Minecraft 1.20.1 with Fabric Loader 0.15.11
+[12:34:58] [Render thread/ERROR]: Unreported exception thrown!
+java.lang.IllegalStateException: Something went wrong
+	at net.minecraft.client.Minecraft.run(Minecraft.java:100)
+	... 3 more
+[12:34:59] [Render thread/WARN]: Multi-line
+message";
+
+    #[test]
+    fn xml_test() {
+        let records = LogParser::parse_str(XML);
+        assert_eq!(records.len(), 2);
+
+        assert_eq!(
+            records[0],
+            LogRecord {
+                timestamp: Some(Timestamp::UnixMillis(1_718_000_000_000)),
+                level: Some(LogLevel::Info),
+                thread: Some("Render thread".into()),
+                logger: Some("net.minecraft.client.Minecraft".into()),
+                message: "Setting user: Nomi".into(),
+                throwable: None,
+            }
+        );
+
+        assert_eq!(records[1].level, Some(LogLevel::Error));
+        assert!(records[1]
+            .throwable
+            .as_ref()
+            .unwrap()
+            .starts_with("java.lang.NullPointerException"));
+    }
+
+    #[test]
+    fn plain_test() {
+        let records = LogParser::parse_str(PLAIN);
+        assert_eq!(records.len(), 4);
+
+        assert_eq!(
+            records[0].timestamp,
+            Some(Timestamp::TimeOfDay {
+                hour: 12,
+                minute: 34,
+                second: 56
+            })
+        );
+        assert_eq!(records[0].thread.as_deref(), Some("Render thread"));
+        assert_eq!(records[0].message, "Setting user: Nomi");
+
+        assert_eq!(
+            records[1].logger.as_deref(),
+            Some("FabricLoader/GameProvider")
+        );
+        assert!(records[1].message.starts_with("Loading Minecraft"));
+
+        assert_eq!(records[2].level, Some(LogLevel::Error));
+        assert_eq!(records[2].message, "Unreported exception thrown!");
+        assert_eq!(records[2].throwable.as_ref().unwrap().lines().count(), 3);
+
+        assert_eq!(records[3].message, "Multi-line\nmessage");
+        assert_eq!(records[3].throwable, None);
+    }
+
+    #[test]
+    fn mixed_test() {
+        let mut parser = LogParser::new();
+
+        let records = parser.feed("Picked up _JAVA_OPTIONS: -Dawt.useSystemAAFontSettings=on");
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].level, None);
+
+        let records = XML
+            .lines()
+            .flat_map(|line| parser.feed(line))
+            .collect::<Vec<_>>();
+        assert_eq!(records.len(), 2);
+        assert!(parser.flush().is_none());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn parse_output_test() {
+        use crate::instance::supervisor::Supervisor;
+
+        let mut command = tokio::process::Command::new("sh");
+        command.arg("-c").arg(format!(
+            "sleep 0.2; printf '%s' '{}'",
+            PLAIN.replace('\t', "    ")
+        ));
+
+        let game = Supervisor::new().spawn(0, command).unwrap();
+
+        let mut records = vec![];
+        LogParser::new()
+            .parse_output(&game, |record| records.push(record))
+            .await;
+
+        assert_eq!(records.len(), 4);
+        assert_eq!(records[3].message, "Multi-line\nmessage");
+    }
+}
@@ -1,9 +1,19 @@
 use typed_builder::TypedBuilder;
 
 pub mod builder_ext;
+pub mod crash;
+pub mod history;
 pub mod launch;
+pub mod logs;
+pub mod options;
 pub mod profile;
+pub mod resources;
+pub mod servers;
+pub mod session_logs;
+pub mod shared_folders;
+pub mod supervisor;
 pub mod version_marker;
+pub mod worlds;
 
 use crate::{
     downloads::downloaders::assets::AssetsDownloader, game_paths::GamePaths,
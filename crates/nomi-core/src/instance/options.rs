@@ -0,0 +1,380 @@
+use std::{collections::BTreeMap, fmt::Display, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    fs::{read_toml_config, write_to_file, write_toml_config},
+    DOT_NOMI_DEFAULT_OPTIONS_CONFIG,
+};
+
+/// Options of the game inside the game directory.
+pub const OPTIONS_FILE: &str = "options.txt";
+
+/// Data version of the file. The game upgrades older options using it, so it is never copied.
+const VERSION_KEY: &str = "version";
+const LANGUAGE_KEY: &str = "lang";
+const KEY_BINDING_PREFIX: &str = "key_";
+
+/// LWJGL 2 key codes used before 1.13 and the names used since.
+#[rustfmt::skip]
+const KEY_CODES: [(i32, &str); 106] = [
+    (0, "key.keyboard.unknown"), (1, "key.keyboard.escape"),
+    (2, "key.keyboard.1"), (3, "key.keyboard.2"), (4, "key.keyboard.3"), (5, "key.keyboard.4"),
+    (6, "key.keyboard.5"), (7, "key.keyboard.6"), (8, "key.keyboard.7"), (9, "key.keyboard.8"),
+    (10, "key.keyboard.9"), (11, "key.keyboard.0"), (12, "key.keyboard.minus"),
+    (13, "key.keyboard.equal"), (14, "key.keyboard.backspace"), (15, "key.keyboard.tab"),
+    (16, "key.keyboard.q"), (17, "key.keyboard.w"), (18, "key.keyboard.e"), (19, "key.keyboard.r"),
+    (20, "key.keyboard.t"), (21, "key.keyboard.y"), (22, "key.keyboard.u"), (23, "key.keyboard.i"),
+    (24, "key.keyboard.o"), (25, "key.keyboard.p"), (26, "key.keyboard.left.bracket"),
+    (27, "key.keyboard.right.bracket"), (28, "key.keyboard.enter"),
+    (29, "key.keyboard.left.control"), (30, "key.keyboard.a"), (31, "key.keyboard.s"),
+    (32, "key.keyboard.d"), (33, "key.keyboard.f"), (34, "key.keyboard.g"), (35, "key.keyboard.h"),
+    (36, "key.keyboard.j"), (37, "key.keyboard.k"), (38, "key.keyboard.l"),
+    (39, "key.keyboard.semicolon"), (40, "key.keyboard.apostrophe"),
+    (41, "key.keyboard.grave.accent"), (42, "key.keyboard.left.shift"),
+    (43, "key.keyboard.backslash"), (44, "key.keyboard.z"), (45, "key.keyboard.x"),
+    (46, "key.keyboard.c"), (47, "key.keyboard.v"), (48, "key.keyboard.b"), (49, "key.keyboard.n"),
+    (50, "key.keyboard.m"), (51, "key.keyboard.comma"), (52, "key.keyboard.period"),
+    (53, "key.keyboard.slash"), (54, "key.keyboard.right.shift"),
+    (55, "key.keyboard.keypad.multiply"), (56, "key.keyboard.left.alt"),
+    (57, "key.keyboard.space"), (58, "key.keyboard.caps.lock"),
+    (59, "key.keyboard.f1"), (60, "key.keyboard.f2"), (61, "key.keyboard.f3"),
+    (62, "key.keyboard.f4"), (63, "key.keyboard.f5"), (64, "key.keyboard.f6"),
+    (65, "key.keyboard.f7"), (66, "key.keyboard.f8"), (67, "key.keyboard.f9"),
+    (68, "key.keyboard.f10"), (69, "key.keyboard.num.lock"), (70, "key.keyboard.scroll.lock"),
+    (71, "key.keyboard.keypad.7"), (72, "key.keyboard.keypad.8"), (73, "key.keyboard.keypad.9"),
+    (74, "key.keyboard.keypad.subtract"), (75, "key.keyboard.keypad.4"),
+    (76, "key.keyboard.keypad.5"), (77, "key.keyboard.keypad.6"), (78, "key.keyboard.keypad.add"),
+    (79, "key.keyboard.keypad.1"), (80, "key.keyboard.keypad.2"), (81, "key.keyboard.keypad.3"),
+    (82, "key.keyboard.keypad.0"), (83, "key.keyboard.keypad.decimal"),
+    (87, "key.keyboard.f11"), (88, "key.keyboard.f12"), (141, "key.keyboard.keypad.equal"),
+    (156, "key.keyboard.keypad.enter"), (157, "key.keyboard.right.control"),
+    (181, "key.keyboard.keypad.divide"), (183, "key.keyboard.print.screen"),
+    (184, "key.keyboard.right.alt"), (197, "key.keyboard.pause"), (199, "key.keyboard.home"),
+    (200, "key.keyboard.up"), (201, "key.keyboard.page.up"), (203, "key.keyboard.left"),
+    (205, "key.keyboard.right"), (207, "key.keyboard.end"), (208, "key.keyboard.down"),
+    (209, "key.keyboard.page.down"), (210, "key.keyboard.insert"),
+    (211, "key.keyboard.delete"), (219, "key.keyboard.left.win"),
+    (220, "key.keyboard.right.win"), (221, "key.keyboard.menu"),
+];
+
+/// Mouse buttons were stored as `button - 100` before 1.13.
+const MOUSE_BUTTON_OFFSET: i32 = -100;
+const MOUSE_BUTTONS: [&str; 3] = ["key.mouse.left", "key.mouse.right", "key.mouse.middle"];
+
+/// How the key bindings and the language are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OptionsFormat {
+    /// Key codes instead of key names, before 1.13.
+    pub key_codes: bool,
+    /// `en_US` instead of `en_us`, before 1.11.
+    pub uppercase_language: bool,
+}
+
+impl OptionsFormat {
+    /// Format used by the version, e.g. `1.12.2`. Snapshots and unknown versions use the modern format.
+    pub fn for_version(version: &str) -> Self {
+        let minor = version
+            .strip_prefix("1.")
+            .and_then(|rest| rest.split(['.', '-', ' ']).next())
+            .and_then(|minor| minor.parse::<u32>().ok());
+
+        match minor {
+            Some(minor) => Self {
+                key_codes: minor < 13,
+                uppercase_language: minor < 11,
+            },
+            None => Self {
+                key_codes: false,
+                uppercase_language: false,
+            },
+        }
+    }
+}
+
+/// Contents of `options.txt`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(transparent)]
+pub struct GameOptions(BTreeMap<String, String>);
+
+impl GameOptions {
+    /// Lines without a `:` are skipped.
+    pub fn parse(s: &str) -> Self {
+        Self(
+            s.lines()
+                .filter_map(|line| line.split_once(':'))
+                .map(|(key, value)| (key.trim().to_owned(), value.trim_end().to_owned()))
+                .filter(|(key, _)| !key.is_empty())
+                .collect(),
+        )
+    }
+
+    /// Reads the options of the game directory. They are empty if the game has never been launched.
+    pub async fn load(game_dir: &Path) -> anyhow::Result<Self> {
+        match tokio::fs::read_to_string(game_dir.join(OPTIONS_FILE)).await {
+            Ok(s) => Ok(Self::parse(&s)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn save(&self, game_dir: &Path) -> anyhow::Result<()> {
+        write_to_file(self.to_string().as_bytes(), game_dir.join(OPTIONS_FILE)).await
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).map(String::as_str)
+    }
+
+    pub fn set(&mut self, key: impl Into<String>, value: impl Into<String>) {
+        self.0.insert(key.into(), value.into());
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        self.0.remove(key)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Key bindings, e.g. `key.forward` -> `key.keyboard.w`.
+    pub fn key_bindings(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.iter()
+            .filter_map(|(key, value)| Some((key.strip_prefix(KEY_BINDING_PREFIX)?, value)))
+    }
+
+    /// Converts the key bindings and the language from one version's format to another's.
+    ///
+    /// Values that have no counterpart are kept as is.
+    #[must_use]
+    pub fn converted(&self, from_version: &str, to_version: &str) -> Self {
+        let from = OptionsFormat::for_version(from_version);
+        let to = OptionsFormat::for_version(to_version);
+
+        let mut options = self.clone();
+
+        for (key, value) in &mut options.0 {
+            if key.starts_with(KEY_BINDING_PREFIX) && from.key_codes != to.key_codes {
+                let converted = if to.key_codes {
+                    name_to_key_code(value).map(|code| code.to_string())
+                } else {
+                    value.parse().ok().and_then(key_code_to_name)
+                };
+
+                if let Some(converted) = converted {
+                    *value = converted;
+                }
+            } else if key == LANGUAGE_KEY && from.uppercase_language != to.uppercase_language {
+                *value = convert_language(value, to.uppercase_language);
+            }
+        }
+
+        options
+    }
+
+    /// Applies the options of another version on top of these ones.
+    ///
+    /// The data version of these options is kept so the game still upgrades them correctly.
+    pub fn merge(&mut self, other: &GameOptions, other_version: &str, version: &str) {
+        for (key, value) in other.converted(other_version, version).0 {
+            if key != VERSION_KEY {
+                self.0.insert(key, value);
+            }
+        }
+    }
+}
+
+impl Display for GameOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (key, value) in &self.0 {
+            writeln!(f, "{key}:{value}")?;
+        }
+
+        Ok(())
+    }
+}
+
+fn key_code_to_name(code: i32) -> Option<String> {
+    if code < 0 {
+        let button = code - MOUSE_BUTTON_OFFSET;
+        return match usize::try_from(button) {
+            Ok(button) if button < MOUSE_BUTTONS.len() => Some(MOUSE_BUTTONS[button].to_owned()),
+            Ok(button) => Some(format!("key.mouse.{}", button + 1)),
+            Err(_) => None,
+        };
+    }
+
+    KEY_CODES
+        .iter()
+        .find(|(known, _)| *known == code)
+        .map(|(_, name)| (*name).to_owned())
+}
+
+fn name_to_key_code(name: &str) -> Option<i32> {
+    if let Some(button) = MOUSE_BUTTONS.iter().position(|known| *known == name) {
+        return i32::try_from(button)
+            .ok()
+            .map(|button| button + MOUSE_BUTTON_OFFSET);
+    }
+
+    if let Some(button) = name
+        .strip_prefix("key.mouse.")
+        .and_then(|button| button.parse::<i32>().ok())
+    {
+        return Some(button - 1 + MOUSE_BUTTON_OFFSET);
+    }
+
+    KEY_CODES
+        .iter()
+        .find(|(_, known)| *known == name)
+        .map(|(code, _)| *code)
+}
+
+/// `en_us` <-> `en_US`
+fn convert_language(language: &str, uppercase: bool) -> String {
+    match language.split_once('_') {
+        Some((language, region)) if uppercase => {
+            format!("{language}_{}", region.to_ascii_uppercase())
+        }
+        _ => language.to_ascii_lowercase(),
+    }
+}
+
+/// Options applied to every new profile.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct DefaultOptions {
+    /// Version the options were taken from.
+    pub game_version: String,
+    pub options: GameOptions,
+}
+
+impl DefaultOptions {
+    /// Takes the options of a profile as the defaults.
+    pub async fn from_game_dir(game_dir: &Path, game_version: &str) -> anyhow::Result<Self> {
+        Ok(Self {
+            game_version: game_version.to_owned(),
+            options: GameOptions::load(game_dir).await?,
+        })
+    }
+
+    /// Returns `None` if no defaults have been saved.
+    pub async fn load() -> anyhow::Result<Option<Self>> {
+        if !Path::new(DOT_NOMI_DEFAULT_OPTIONS_CONFIG).exists() {
+            return Ok(None);
+        }
+
+        read_toml_config(DOT_NOMI_DEFAULT_OPTIONS_CONFIG)
+            .await
+            .map(Some)
+    }
+
+    pub async fn save(&self) -> anyhow::Result<()> {
+        write_toml_config(self, DOT_NOMI_DEFAULT_OPTIONS_CONFIG).await
+    }
+
+    /// Writes the defaults into the options of the game directory.
+    pub async fn apply(&self, game_dir: &Path, game_version: &str) -> anyhow::Result<()> {
+        let mut options = GameOptions::load(game_dir).await?;
+        options.merge(&self.options, &self.game_version, game_version);
+        options.save(game_dir).await
+    }
+}
+
+/// Copies the options of one profile into another, e.g. to carry the key bindings over to a new version.
+pub async fn copy_options(
+    from_dir: &Path,
+    from_version: &str,
+    to_dir: &Path,
+    to_version: &str,
+) -> anyhow::Result<()> {
+    let source = GameOptions::load(from_dir).await?;
+
+    let mut options = GameOptions::load(to_dir).await?;
+    options.merge(&source, from_version, to_version);
+    options.save(to_dir).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let options =
+            GameOptions::parse("version:3700\nfov:0.5\nlastServer:mc.example.com:25565\nbroken\n");
+
+        assert_eq!(options.len(), 3);
+        assert_eq!(options.get("lastServer"), Some("mc.example.com:25565"));
+        assert_eq!(GameOptions::parse(&options.to_string()), options);
+    }
+
+    #[test]
+    fn format_test() {
+        assert!(OptionsFormat::for_version("1.12.2").key_codes);
+        assert!(OptionsFormat::for_version("1.8.9").uppercase_language);
+        assert!(!OptionsFormat::for_version("1.13").key_codes);
+        assert!(!OptionsFormat::for_version("1.11.2").uppercase_language);
+        assert!(!OptionsFormat::for_version("24w14a").key_codes);
+    }
+
+    #[test]
+    fn convert_test() {
+        let legacy = GameOptions::parse(
+            "version:1343\nlang:en_US\nkey_key.forward:17\nkey_key.attack:-100\nkey_key.pickItem:-98\nkey_key.custom:-96\nfov:0.25\n",
+        );
+
+        let modern = legacy.converted("1.10.2", "1.20.4");
+        assert_eq!(modern.get("lang"), Some("en_us"));
+        assert_eq!(modern.get("key_key.forward"), Some("key.keyboard.w"));
+        assert_eq!(modern.get("key_key.attack"), Some("key.mouse.left"));
+        assert_eq!(modern.get("key_key.pickItem"), Some("key.mouse.middle"));
+        assert_eq!(modern.get("key_key.custom"), Some("key.mouse.5"));
+        assert_eq!(modern.get("fov"), Some("0.25"));
+
+        assert_eq!(modern.converted("1.20.4", "1.10.2"), legacy);
+        assert_eq!(legacy.converted("1.10.2", "1.8.9"), legacy);
+
+        let mut target = GameOptions::parse("version:3700\nfov:0.0\nguiScale:2\n");
+        target.merge(&legacy, "1.10.2", "1.20.4");
+        assert_eq!(target.get("version"), Some("3700"));
+        assert_eq!(target.get("fov"), Some("0.25"));
+        assert_eq!(target.get("guiScale"), Some("2"));
+        assert_eq!(
+            target.key_bindings().collect::<Vec<_>>(),
+            [
+                ("key.attack", "key.mouse.left"),
+                ("key.custom", "key.mouse.5"),
+                ("key.forward", "key.keyboard.w"),
+                ("key.pickItem", "key.mouse.middle"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn copy_test() {
+        let root = std::env::temp_dir().join(format!("nomi-options-test-{}", std::process::id()));
+        let (from, to) = (root.join("from"), root.join("to"));
+
+        std::fs::create_dir_all(&from).unwrap();
+        std::fs::write(from.join(OPTIONS_FILE), "key_key.jump:key.keyboard.space\n").unwrap();
+
+        copy_options(&from, "1.20.4", &to, "1.8.9").await.unwrap();
+        assert_eq!(
+            GameOptions::load(&to).await.unwrap().get("key_key.jump"),
+            Some("57")
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,199 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// How often the resources of a running game are sampled.
+pub const SAMPLE_INTERVAL: Duration = Duration::from_secs(1);
+/// Samples kept for every game, two minutes with the default interval.
+pub const MAX_SAMPLES: usize = 120;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ResourceSample {
+    /// Time since the game was started.
+    pub uptime: Duration,
+    /// CPU usage since the previous sample in percent of one core,
+    /// so it goes above 100 when the game uses several cores.
+    pub cpu_percent: f32,
+    /// Resident memory in bytes.
+    pub memory: u64,
+}
+
+/// The latest [`MAX_SAMPLES`] samples of a game, shared with its [`GameHandle`](super::supervisor::GameHandle).
+#[derive(Debug, Clone, Default)]
+pub struct ResourceSamples(Arc<Mutex<VecDeque<ResourceSample>>>);
+
+impl ResourceSamples {
+    pub fn push(&self, sample: ResourceSample) {
+        let mut samples = self.lock();
+
+        if samples.len() == MAX_SAMPLES {
+            samples.pop_front();
+        }
+        samples.push_back(sample);
+    }
+
+    pub fn latest(&self) -> Option<ResourceSample> {
+        self.lock().back().copied()
+    }
+
+    /// Oldest first.
+    pub fn to_vec(&self) -> Vec<ResourceSample> {
+        self.lock().iter().copied().collect()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<ResourceSample>> {
+        // Samples are plain data so a poisoned lock is fine
+        self.0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+/// Reads the resource usage of a process from `/proc/<pid>`.
+///
+/// Sampling is only supported on Linux, elsewhere [`ProcessSampler::sample`] always returns `None`.
+#[derive(Debug)]
+pub struct ProcessSampler {
+    pid: u32,
+    started: Instant,
+    /// Uptime and CPU time of the previous sample.
+    previous: (Duration, Duration),
+}
+
+impl ProcessSampler {
+    pub fn new(pid: u32, started: Instant) -> Self {
+        Self {
+            pid,
+            started,
+            previous: (Duration::ZERO, Duration::ZERO),
+        }
+    }
+
+    /// Returns `None` if the process has exited or its usage cannot be read.
+    pub fn sample(&mut self) -> Option<ResourceSample> {
+        let usage = read_process_usage(self.pid)?;
+        let uptime = self.started.elapsed();
+
+        let (previous_uptime, previous_cpu_time) = self.previous;
+        let wall = uptime.saturating_sub(previous_uptime);
+        let cpu = usage.cpu_time.saturating_sub(previous_cpu_time);
+        self.previous = (uptime, usage.cpu_time);
+
+        let cpu_percent = if wall.is_zero() {
+            0.0
+        } else {
+            cpu.as_secs_f32() / wall.as_secs_f32() * 100.0
+        };
+
+        Some(ResourceSample {
+            uptime,
+            cpu_percent,
+            memory: usage.memory,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct ProcessUsage {
+    /// User and system time.
+    cpu_time: Duration,
+    memory: u64,
+}
+
+#[cfg(target_os = "linux")]
+fn read_process_usage(pid: u32) -> Option<ProcessUsage> {
+    // SAFETY: `sysconf` has no memory safety requirements
+    let (ticks_per_second, page_size) = unsafe {
+        (
+            libc::sysconf(libc::_SC_CLK_TCK),
+            libc::sysconf(libc::_SC_PAGESIZE),
+        )
+    };
+    let ticks_per_second = u64::try_from(ticks_per_second).ok().filter(|t| *t > 0)?;
+    let page_size = u64::try_from(page_size).ok()?;
+
+    let stat = std::fs::read_to_string(format!("/proc/{pid}/stat")).ok()?;
+    let statm = std::fs::read_to_string(format!("/proc/{pid}/statm")).ok()?;
+
+    let ticks = parse_stat_cpu_ticks(&stat)?;
+    let pages = parse_statm_resident_pages(&statm)?;
+
+    Some(ProcessUsage {
+        cpu_time: Duration::from_secs(ticks / ticks_per_second)
+            + Duration::from_nanos((ticks % ticks_per_second) * 1_000_000_000 / ticks_per_second),
+        memory: pages * page_size,
+    })
+}
+
+#[cfg(not(target_os = "linux"))]
+fn read_process_usage(_pid: u32) -> Option<ProcessUsage> {
+    None
+}
+
+/// `utime + stime` of `/proc/<pid>/stat`, in clock ticks.
+fn parse_stat_cpu_ticks(stat: &str) -> Option<u64> {
+    // The process name may contain spaces and parentheses so the fields are counted from the last `)`
+    let (_, fields) = stat.rsplit_once(')')?;
+    let mut fields = fields.split_whitespace().skip(11);
+
+    let utime = fields.next()?.parse::<u64>().ok()?;
+    let stime = fields.next()?.parse::<u64>().ok()?;
+
+    Some(utime + stime)
+}
+
+/// Second field of `/proc/<pid>/statm`.
+fn parse_statm_resident_pages(statm: &str) -> Option<u64> {
+    statm.split_whitespace().nth(1)?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let stat = "4242 (java (main) x) S 1 4242 4242 0 -1 4194560 97130 0 12 0 1500 250 0 0 20 0 60 0 123456 6000000000 300000 18446744073709551615";
+        assert_eq!(parse_stat_cpu_ticks(stat), Some(1750));
+        assert_eq!(parse_stat_cpu_ticks("4242 (java"), None);
+
+        assert_eq!(
+            parse_statm_resident_pages("1500000 300000 5000 1 0 400000 0\n"),
+            Some(300_000)
+        );
+    }
+
+    #[test]
+    fn samples_test() {
+        let samples = ResourceSamples::default();
+
+        for second in 0..=MAX_SAMPLES as u64 {
+            samples.push(ResourceSample {
+                uptime: Duration::from_secs(second),
+                cpu_percent: 50.0,
+                memory: second,
+            });
+        }
+
+        let all = samples.to_vec();
+        assert_eq!(all.len(), MAX_SAMPLES);
+        assert_eq!(all[0].memory, 1);
+        assert_eq!(samples.latest().unwrap().memory, MAX_SAMPLES as u64);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn sample_test() {
+        let mut sampler = ProcessSampler::new(std::process::id(), Instant::now());
+
+        let sample = sampler.sample().unwrap();
+        assert!(sample.memory > 0);
+        assert!(sample.cpu_percent >= 0.0);
+
+        assert!(ProcessSampler::new(u32::MAX, Instant::now())
+            .sample()
+            .is_none());
+    }
+}
@@ -0,0 +1,241 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    fs::write_to_file,
+    nbt::{Compound, Nbt, Tag},
+};
+
+/// Server list of the multiplayer menu inside the game directory.
+pub const SERVERS_FILE: &str = "servers.dat";
+
+/// What the game does with the resource pack the server offers.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ResourcePackPolicy {
+    /// Asks the player every time.
+    #[default]
+    Prompt,
+    Enabled,
+    Disabled,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ServerEntry {
+    pub name: String,
+    /// Address with an optional port, e.g. `mc.example.com:25566`.
+    pub ip: String,
+    /// Base64 encoded PNG. The game updates it on every ping.
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub resource_packs: ResourcePackPolicy,
+    /// Fields Nomi does not know about, kept when the list is saved.
+    #[serde(skip)]
+    pub extra: Compound,
+}
+
+impl ServerEntry {
+    pub fn new(name: impl Into<String>, ip: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            ip: ip.into(),
+            icon: None,
+            resource_packs: ResourcePackPolicy::default(),
+            extra: Compound::new(),
+        }
+    }
+
+    fn from_compound(mut compound: Compound) -> Self {
+        let mut take_string = |key| match compound.remove(key) {
+            Some(Tag::String(value)) => Some(value),
+            _ => None,
+        };
+
+        let name = take_string("name").unwrap_or_default();
+        let ip = take_string("ip").unwrap_or_default();
+        let icon = take_string("icon");
+
+        let resource_packs = match compound
+            .remove("acceptTextures")
+            .and_then(|tag| tag.as_byte())
+        {
+            Some(0) => ResourcePackPolicy::Disabled,
+            Some(_) => ResourcePackPolicy::Enabled,
+            None => ResourcePackPolicy::Prompt,
+        };
+
+        Self {
+            name,
+            ip,
+            icon,
+            resource_packs,
+            extra: compound,
+        }
+    }
+
+    fn to_compound(&self) -> Compound {
+        let mut compound = self.extra.clone();
+
+        compound.insert("name".into(), self.name.as_str().into());
+        compound.insert("ip".into(), self.ip.as_str().into());
+
+        if let Some(icon) = &self.icon {
+            compound.insert("icon".into(), icon.as_str().into());
+        }
+
+        match self.resource_packs {
+            ResourcePackPolicy::Prompt => {}
+            ResourcePackPolicy::Enabled => {
+                compound.insert("acceptTextures".into(), Tag::Byte(1));
+            }
+            ResourcePackPolicy::Disabled => {
+                compound.insert("acceptTextures".into(), Tag::Byte(0));
+            }
+        }
+
+        compound
+    }
+}
+
+/// Contents of `servers.dat`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ServerList {
+    pub entries: Vec<ServerEntry>,
+    /// Other fields of the file, kept when the list is saved.
+    extra: Compound,
+}
+
+impl ServerList {
+    /// Reads the list of the game directory. The list is empty if the game has never saved it.
+    pub async fn load(game_dir: &Path) -> anyhow::Result<Self> {
+        match tokio::fs::read(game_dir.join(SERVERS_FILE)).await {
+            Ok(bytes) => Ok(Self::from_nbt(Nbt::from_bytes(&bytes)?)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub async fn save(&self, game_dir: &Path) -> anyhow::Result<()> {
+        write_to_file(&self.to_nbt().to_bytes()?, game_dir.join(SERVERS_FILE)).await
+    }
+
+    pub fn from_nbt(nbt: Nbt) -> Self {
+        let mut extra = nbt.root;
+
+        let entries = match extra.remove("servers") {
+            Some(Tag::List(servers)) => servers
+                .into_iter()
+                .filter_map(|server| match server {
+                    Tag::Compound(compound) => Some(ServerEntry::from_compound(compound)),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+
+        Self { entries, extra }
+    }
+
+    pub fn to_nbt(&self) -> Nbt {
+        let mut root = self.extra.clone();
+
+        root.insert(
+            "servers".into(),
+            Tag::List(
+                self.entries
+                    .iter()
+                    .map(|entry| entry.to_compound().into())
+                    .collect(),
+            ),
+        );
+
+        Nbt::new(root)
+    }
+
+    pub fn position(&self, ip: &str) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.ip.eq_ignore_ascii_case(ip))
+    }
+
+    pub fn add(&mut self, entry: ServerEntry) {
+        self.entries.push(entry);
+    }
+
+    /// Adds the entry unless a server with the same address is already listed.
+    ///
+    /// Returns `true` if the entry has been added.
+    pub fn add_unique(&mut self, entry: ServerEntry) -> bool {
+        if self.position(&entry.ip).is_some() {
+            return false;
+        }
+
+        self.add(entry);
+        true
+    }
+
+    pub fn remove(&mut self, index: usize) -> Option<ServerEntry> {
+        (index < self.entries.len()).then(|| self.entries.remove(index))
+    }
+
+    /// Moves the entry to `to`, shifting the entries in between.
+    ///
+    /// Returns `false` if any of the indexes is out of bounds.
+    pub fn move_entry(&mut self, from: usize, to: usize) -> bool {
+        if from >= self.entries.len() || to >= self.entries.len() {
+            return false;
+        }
+
+        let entry = self.entries.remove(from);
+        self.entries.insert(to, entry);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn servers_test() {
+        let game_dir =
+            std::env::temp_dir().join(format!("nomi-servers-test-{}", std::process::id()));
+
+        let mut list = ServerList::load(&game_dir).await.unwrap();
+        assert!(list.entries.is_empty());
+
+        let mut hypixel = ServerEntry::new("Hypixel", "mc.hypixel.net");
+        hypixel.resource_packs = ResourcePackPolicy::Disabled;
+        hypixel.extra.insert("hidden".into(), Tag::Byte(0));
+
+        list.add(ServerEntry::new("Local", "localhost"));
+        list.add(hypixel);
+        assert!(list.add_unique(ServerEntry::new("Team", "team.example.com:25566")));
+        assert!(!list.add_unique(ServerEntry::new("Duplicate", "LOCALHOST")));
+
+        assert!(list.move_entry(2, 0));
+        assert!(!list.move_entry(0, 3));
+        assert_eq!(list.remove(1).unwrap().name, "Local");
+        assert!(list.remove(5).is_none());
+
+        list.save(&game_dir).await.unwrap();
+        let loaded = ServerList::load(&game_dir).await.unwrap();
+        assert_eq!(loaded, list);
+        assert_eq!(
+            loaded
+                .entries
+                .iter()
+                .map(|entry| entry.name.as_str())
+                .collect::<Vec<_>>(),
+            ["Team", "Hypixel"]
+        );
+        assert_eq!(
+            loaded.entries[1].resource_packs,
+            ResourcePackPolicy::Disabled
+        );
+        assert_eq!(loaded.entries[1].extra["hidden"], Tag::Byte(0));
+
+        std::fs::remove_dir_all(game_dir).unwrap();
+    }
+}
@@ -0,0 +1,262 @@
+use std::{
+    io,
+    path::PathBuf,
+    time::{Duration, SystemTime},
+};
+
+use tokio::io::{AsyncWriteExt, BufWriter};
+
+use super::supervisor::{GameOutput, OutputStream};
+
+/// Size after which the rest of a session's output is dropped.
+pub const DEFAULT_MAX_SESSION_LOG_SIZE: u64 = 16 * 1024 * 1024;
+/// Session logs kept for every profile.
+pub const DEFAULT_KEPT_SESSION_LOGS: usize = 20;
+
+const STDERR_PREFIX: &str = "[STDERR] ";
+
+/// Output of every launch, kept as `<dir>/<profile id>/<unix millis>.log`.
+#[derive(Debug, Clone)]
+pub struct SessionLogs {
+    dir: PathBuf,
+    max_size: u64,
+    keep: usize,
+}
+
+/// A past or running session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionLog {
+    pub path: PathBuf,
+    pub started: SystemTime,
+    /// Size in bytes.
+    pub size: u64,
+}
+
+impl SessionLog {
+    pub async fn read(&self) -> anyhow::Result<String> {
+        let bytes = tokio::fs::read(&self.path).await?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+impl SessionLogs {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            max_size: DEFAULT_MAX_SESSION_LOG_SIZE,
+            keep: DEFAULT_KEPT_SESSION_LOGS,
+        }
+    }
+
+    #[must_use]
+    pub fn with_max_size(mut self, max_size: u64) -> Self {
+        self.max_size = max_size;
+        self
+    }
+
+    /// Sets how many logs are kept for every profile. The oldest ones are removed when a new session starts.
+    #[must_use]
+    pub fn with_keep(mut self, keep: usize) -> Self {
+        self.keep = keep.max(1);
+        self
+    }
+
+    pub fn profile_dir(&self, profile_id: usize) -> PathBuf {
+        self.dir.join(profile_id.to_string())
+    }
+
+    /// Session logs of the profile, the newest first.
+    pub fn list(&self, profile_id: usize) -> anyhow::Result<Vec<SessionLog>> {
+        let entries = match std::fs::read_dir(self.profile_dir(profile_id)) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut logs = Vec::new();
+
+        for entry in entries {
+            let entry = entry?;
+            let path = entry.path();
+
+            if path.extension().is_none_or(|extension| extension != "log") {
+                continue;
+            }
+
+            let Some(millis) = path
+                .file_stem()
+                .and_then(|stem| stem.to_str())
+                .and_then(|stem| stem.parse::<u64>().ok())
+            else {
+                continue;
+            };
+
+            logs.push(SessionLog {
+                path,
+                started: SystemTime::UNIX_EPOCH + Duration::from_millis(millis),
+                size: entry.metadata()?.len(),
+            });
+        }
+
+        logs.sort_by_key(|log| std::cmp::Reverse(log.started));
+
+        Ok(logs)
+    }
+
+    /// Removes the logs of the profile, e.g. after it has been deleted.
+    pub async fn remove_profile(&self, profile_id: usize) -> anyhow::Result<()> {
+        match tokio::fs::remove_dir_all(self.profile_dir(profile_id)).await {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    /// Creates the log of a new session and removes the oldest ones.
+    pub fn start(
+        &self,
+        profile_id: usize,
+        started: SystemTime,
+    ) -> anyhow::Result<SessionLogWriter> {
+        let dir = self.profile_dir(profile_id);
+        std::fs::create_dir_all(&dir)?;
+
+        for old in self.list(profile_id)?.iter().skip(self.keep - 1) {
+            std::fs::remove_file(&old.path)?;
+        }
+
+        let millis = started
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        let file = std::fs::File::create(dir.join(format!("{millis}.log")))?;
+
+        Ok(SessionLogWriter {
+            file: BufWriter::new(tokio::fs::File::from_std(file)),
+            written: 0,
+            max_size: self.max_size,
+            is_truncated: false,
+        })
+    }
+}
+
+/// Writes the output of a single session. Lines past the size limit are dropped.
+#[derive(Debug)]
+pub struct SessionLogWriter {
+    file: BufWriter<tokio::fs::File>,
+    written: u64,
+    max_size: u64,
+    is_truncated: bool,
+}
+
+impl SessionLogWriter {
+    pub async fn write(&mut self, output: &GameOutput) -> io::Result<()> {
+        if self.is_truncated {
+            return Ok(());
+        }
+
+        let prefix = match output.stream {
+            OutputStream::Stdout => "",
+            OutputStream::Stderr => STDERR_PREFIX,
+        };
+        let length = (prefix.len() + output.line.len() + 1) as u64;
+
+        if self.written + length > self.max_size {
+            self.is_truncated = true;
+            let notice = format!(
+                "... the log has reached {} bytes, the rest of the output is dropped\n",
+                self.max_size
+            );
+            return self.file.write_all(notice.as_bytes()).await;
+        }
+
+        self.written += length;
+        self.file.write_all(prefix.as_bytes()).await?;
+        self.file.write_all(output.line.as_bytes()).await?;
+        self.file.write_all(b"\n").await
+    }
+
+    pub async fn finish(mut self) -> io::Result<()> {
+        self.file.flush().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("nomi-session-logs-{name}-{}", std::process::id()))
+    }
+
+    fn line(stream: OutputStream, line: &str) -> GameOutput {
+        GameOutput {
+            stream,
+            line: line.to_owned(),
+        }
+    }
+
+    #[tokio::test]
+    async fn write_test() {
+        let dir = temp_dir("write");
+        let logs = SessionLogs::new(&dir).with_max_size(40);
+
+        let mut writer = logs.start(1, SystemTime::now()).unwrap();
+        writer
+            .write(&line(OutputStream::Stdout, "first"))
+            .await
+            .unwrap();
+        writer
+            .write(&line(OutputStream::Stderr, "second"))
+            .await
+            .unwrap();
+        writer
+            .write(&line(OutputStream::Stdout, &"x".repeat(40)))
+            .await
+            .unwrap();
+        writer
+            .write(&line(OutputStream::Stdout, "dropped"))
+            .await
+            .unwrap();
+        writer.finish().await.unwrap();
+
+        let log = logs.list(1).unwrap().remove(0);
+        let content = log.read().await.unwrap();
+        let lines = content.lines().collect::<Vec<_>>();
+
+        assert_eq!(lines[..2], ["first", "[STDERR] second"]);
+        assert!(lines[2].contains("dropped"));
+        assert_eq!(lines.len(), 3);
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn retention_test() {
+        let dir = temp_dir("retention");
+        let logs = SessionLogs::new(&dir).with_keep(2);
+
+        for second in 1..=3 {
+            let started = SystemTime::UNIX_EPOCH + Duration::from_secs(second);
+            logs.start(7, started).unwrap().finish().await.unwrap();
+        }
+
+        let started = logs
+            .list(7)
+            .unwrap()
+            .into_iter()
+            .map(|log| log.started)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            started,
+            [
+                SystemTime::UNIX_EPOCH + Duration::from_secs(3),
+                SystemTime::UNIX_EPOCH + Duration::from_secs(2),
+            ]
+        );
+
+        logs.remove_profile(7).await.unwrap();
+        assert!(logs.list(7).unwrap().is_empty());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,309 @@
+use std::{
+    fmt::Display,
+    io,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+/// Folder of the game directory that can be shared between the profiles.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum SharedFolder {
+    Screenshots,
+    ResourcePacks,
+    ShaderPacks,
+    Saves,
+}
+
+impl SharedFolder {
+    pub const ALL: [SharedFolder; 4] = [
+        Self::Screenshots,
+        Self::ResourcePacks,
+        Self::ShaderPacks,
+        Self::Saves,
+    ];
+
+    /// Name of the folder inside the game directory.
+    pub fn dir_name(self) -> &'static str {
+        match self {
+            Self::Screenshots => "screenshots",
+            Self::ResourcePacks => "resourcepacks",
+            Self::ShaderPacks => "shaderpacks",
+            Self::Saves => "saves",
+        }
+    }
+}
+
+impl Display for SharedFolder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Screenshots => "Screenshots",
+            Self::ResourcePacks => "Resource packs",
+            Self::ShaderPacks => "Shader packs",
+            Self::Saves => "Worlds",
+        };
+
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkStatus {
+    /// The link already pointed to the shared folder.
+    Valid,
+    Created,
+    /// The link was broken or pointed somewhere else.
+    Repaired,
+    /// The profile's own folder was merged into the shared one and replaced with the link.
+    Merged,
+}
+
+/// Makes `<game_dir>/<folder>` a symlink to `<shared_root>/<folder>`.
+///
+/// Contents of an existing folder are moved into the shared folder.
+/// Entries with names already taken in the shared folder are renamed instead of overwritten.
+pub fn link_shared_folder(
+    game_dir: &Path,
+    shared_root: &Path,
+    folder: SharedFolder,
+) -> anyhow::Result<LinkStatus> {
+    let shared = shared_root.join(folder.dir_name());
+    std::fs::create_dir_all(&shared)?;
+    let shared = shared.canonicalize()?;
+
+    std::fs::create_dir_all(game_dir)?;
+    let link = game_dir.join(folder.dir_name());
+
+    let metadata = match std::fs::symlink_metadata(&link) {
+        Ok(metadata) => metadata,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            create_dir_link(&shared, &link)?;
+            return Ok(LinkStatus::Created);
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    if metadata.is_symlink() {
+        if link.canonicalize().is_ok_and(|target| target == shared) {
+            return Ok(LinkStatus::Valid);
+        }
+
+        remove_dir_link(&link)?;
+        create_dir_link(&shared, &link)?;
+        return Ok(LinkStatus::Repaired);
+    }
+
+    if !metadata.is_dir() {
+        return Err(anyhow::anyhow!(
+            "Cannot share {folder}: {} is a file",
+            link.display()
+        ));
+    }
+
+    merge_into(&link, &shared)?;
+    std::fs::remove_dir(&link)?;
+    create_dir_link(&shared, &link)?;
+
+    Ok(LinkStatus::Merged)
+}
+
+/// Links every folder and logs what has been changed.
+pub fn link_shared_folders(
+    game_dir: &Path,
+    shared_root: &Path,
+    folders: impl IntoIterator<Item = SharedFolder>,
+) -> anyhow::Result<()> {
+    for folder in folders {
+        match link_shared_folder(game_dir, shared_root, folder)? {
+            LinkStatus::Valid => {}
+            LinkStatus::Created => info!("Linked the shared {folder} folder"),
+            LinkStatus::Repaired => warn!("Repaired the link to the shared {folder} folder"),
+            LinkStatus::Merged => {
+                info!("Moved the profile's {folder} into the shared folder");
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Replaces the link to `<shared_root>/<folder>` with an empty folder of the profile.
+///
+/// The files stay in the shared folder. Returns `false` if the folder was not linked.
+pub fn unlink_shared_folder(
+    game_dir: &Path,
+    shared_root: &Path,
+    folder: SharedFolder,
+) -> anyhow::Result<bool> {
+    let link = game_dir.join(folder.dir_name());
+
+    let is_link = std::fs::symlink_metadata(&link).is_ok_and(|metadata| metadata.is_symlink());
+    if !is_link {
+        return Ok(false);
+    }
+
+    let shared = shared_root.join(folder.dir_name());
+    let points_to_shared = match (link.canonicalize(), shared.canonicalize()) {
+        (Ok(target), Ok(shared)) => target == shared,
+        // The shared folder is gone, the link is broken either way
+        (Err(_), _) => true,
+        (Ok(_), Err(_)) => false,
+    };
+    if !points_to_shared {
+        return Ok(false);
+    }
+
+    remove_dir_link(&link)?;
+    std::fs::create_dir(&link)?;
+
+    Ok(true)
+}
+
+/// Moves every entry of `from` into `to` without overwriting anything.
+fn merge_into(from: &Path, to: &Path) -> io::Result<()> {
+    for entry in std::fs::read_dir(from)? {
+        let entry = entry?;
+        let source = entry.path();
+        let target = to.join(entry.file_name());
+
+        if !target.exists() {
+            move_entry(&source, &target)?;
+        } else if source.is_dir() && target.is_dir() {
+            merge_into(&source, &target)?;
+            std::fs::remove_dir(&source)?;
+        } else {
+            move_entry(&source, &free_name(&target))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// `Screenshot.png` -> `Screenshot (1).png`
+fn free_name(path: &Path) -> PathBuf {
+    let stem = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let extension = path
+        .extension()
+        .map(|extension| format!(".{}", extension.to_string_lossy()))
+        .unwrap_or_default();
+
+    let mut index = 1;
+    loop {
+        let candidate = path.with_file_name(format!("{stem} ({index}){extension}"));
+        if !candidate.exists() {
+            return candidate;
+        }
+        index += 1;
+    }
+}
+
+/// Renames the entry or copies it if the rename is not possible (e.g. across file systems).
+fn move_entry(from: &Path, to: &Path) -> io::Result<()> {
+    if std::fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+
+    if from.is_dir() {
+        std::fs::create_dir_all(to)?;
+        for entry in std::fs::read_dir(from)? {
+            let entry = entry?;
+            move_entry(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        std::fs::remove_dir(from)
+    } else {
+        std::fs::copy(from, to)?;
+        std::fs::remove_file(from)
+    }
+}
+
+#[cfg(unix)]
+fn create_dir_link(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(windows)]
+fn create_dir_link(target: &Path, link: &Path) -> io::Result<()> {
+    std::os::windows::fs::symlink_dir(target, link).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("{e}. Creating symlinks on Windows requires the Developer Mode"),
+        )
+    })
+}
+
+#[cfg(unix)]
+fn remove_dir_link(link: &Path) -> io::Result<()> {
+    std::fs::remove_file(link)
+}
+
+#[cfg(windows)]
+fn remove_dir_link(link: &Path) -> io::Result<()> {
+    std::fs::remove_dir(link)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn link_test() {
+        let root = std::env::temp_dir().join(format!("nomi-shared-test-{}", std::process::id()));
+        let shared = root.join("shared");
+        let first = root.join("first");
+        let second = root.join("second");
+
+        let screenshots = first.join("screenshots");
+        std::fs::create_dir_all(&screenshots).unwrap();
+        std::fs::write(screenshots.join("a.png"), "first").unwrap();
+
+        std::fs::create_dir_all(shared.join("screenshots")).unwrap();
+        std::fs::write(shared.join("screenshots").join("a.png"), "shared").unwrap();
+
+        let status = link_shared_folder(&first, &shared, SharedFolder::Screenshots).unwrap();
+        assert_eq!(status, LinkStatus::Merged);
+        assert!(std::fs::symlink_metadata(&screenshots)
+            .unwrap()
+            .is_symlink());
+        assert_eq!(
+            std::fs::read_to_string(shared.join("screenshots").join("a (1).png")).unwrap(),
+            "first"
+        );
+        assert_eq!(
+            std::fs::read_to_string(screenshots.join("a.png")).unwrap(),
+            "shared"
+        );
+
+        assert_eq!(
+            link_shared_folder(&first, &shared, SharedFolder::Screenshots).unwrap(),
+            LinkStatus::Valid
+        );
+        assert_eq!(
+            link_shared_folder(&second, &shared, SharedFolder::Screenshots).unwrap(),
+            LinkStatus::Created
+        );
+        assert!(second.join("screenshots").join("a (1).png").exists());
+
+        // A link to a removed folder
+        std::fs::remove_file(&screenshots).unwrap();
+        create_dir_link(&root.join("missing"), &screenshots).unwrap();
+        assert_eq!(
+            link_shared_folder(&first, &shared, SharedFolder::Screenshots).unwrap(),
+            LinkStatus::Repaired
+        );
+        assert!(screenshots.join("a.png").exists());
+
+        assert!(unlink_shared_folder(&first, &shared, SharedFolder::Screenshots).unwrap());
+        assert!(!std::fs::symlink_metadata(&screenshots)
+            .unwrap()
+            .is_symlink());
+        assert_eq!(std::fs::read_dir(&screenshots).unwrap().count(), 0);
+        assert!(shared.join("screenshots").join("a.png").exists());
+        assert!(!unlink_shared_folder(&first, &shared, SharedFolder::Screenshots).unwrap());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,834 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    process::Stdio,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant, SystemTime},
+};
+
+use tokio::{
+    io::{AsyncBufReadExt, AsyncRead, BufReader},
+    process::{Child, Command},
+    sync::{broadcast, mpsc, watch},
+    task::{JoinHandle, JoinSet},
+};
+use tracing::{info, warn};
+
+use crate::repository::java_runner::JavaRunner;
+
+use super::{
+    crash::{Crash, ExitReason},
+    history::{HistoryStore, LaunchRecord},
+    launch::{arguments::UserData, quick_play::QuickPlay, LaunchInstance},
+    resources::{ProcessSampler, ResourceSample, ResourceSamples, SAMPLE_INTERVAL},
+    session_logs::{SessionLogWriter, SessionLogs},
+};
+
+/// Number of output lines kept for slow subscribers.
+const OUTPUT_CAPACITY: usize = 1024;
+/// How long to wait for the remaining output after the game has exited.
+/// The pipes may be kept open by processes the game has spawned.
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(1);
+/// How long a game may take to exit after [`StopRequest::Graceful`] before it is killed.
+pub const DEFAULT_STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, thiserror::Error)]
+pub enum SupervisorError {
+    #[error("The profile with id {0} is already running")]
+    AlreadyRunning(usize),
+    #[error("The profile with id {0} is not running")]
+    NotRunning(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameOutput {
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopRequest {
+    /// Asks the game to exit (`SIGTERM` on unix, same as `Forced` elsewhere).
+    /// The game is killed if it is still running after the supervisor's stop timeout.
+    Graceful,
+    /// Kills the game immediately.
+    Forced,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameExit {
+    pub profile_id: usize,
+    /// `None` if the process was terminated by a signal.
+    pub code: Option<i32>,
+    pub duration: Duration,
+    /// `None` if the game has exited normally or was stopped by the user.
+    pub crash: Option<Crash>,
+}
+
+impl GameExit {
+    pub fn is_success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+/// Handle to a game started by the [`Supervisor`].
+#[derive(Debug, Clone)]
+pub struct GameHandle {
+    pub profile_id: usize,
+    pub pid: Option<u32>,
+    pub started_at: SystemTime,
+    output: broadcast::Sender<GameOutput>,
+    stop: mpsc::UnboundedSender<StopRequest>,
+    exit: watch::Receiver<Option<GameExit>>,
+    resources: ResourceSamples,
+}
+
+impl GameHandle {
+    /// Receives the game's output lines starting from now.
+    ///
+    /// All output is delivered before the exit is published.
+    pub fn subscribe(&self) -> broadcast::Receiver<GameOutput> {
+        self.output.subscribe()
+    }
+
+    pub fn stop(&self, request: StopRequest) {
+        // The game has already exited if the receiver is dropped
+        let _ = self.stop.send(request);
+    }
+
+    pub fn exit(&self) -> Option<GameExit> {
+        self.exit.borrow().clone()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.exit.borrow().is_none()
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed().unwrap_or_default()
+    }
+
+    /// The latest CPU and memory usage, `None` until the first sample or if sampling is not supported.
+    pub fn resource_usage(&self) -> Option<ResourceSample> {
+        self.resources.latest()
+    }
+
+    /// Recent resource usage, oldest first.
+    pub fn resource_history(&self) -> Vec<ResourceSample> {
+        self.resources.to_vec()
+    }
+
+    /// Waits until the game exits.
+    ///
+    /// Returns `None` if the game is no longer watched, e.g. the runtime has been shut down.
+    pub async fn wait(&self) -> Option<GameExit> {
+        let mut exit = self.exit.clone();
+        let exit = exit.wait_for(Option::is_some).await.ok()?;
+        exit.clone()
+    }
+}
+
+#[derive(Debug)]
+enum Entry {
+    /// Reserved by [`Supervisor::launch`] while the game is prepared.
+    Starting,
+    Running(GameHandle),
+}
+
+impl Entry {
+    fn handle(&self) -> Option<&GameHandle> {
+        match self {
+            Entry::Starting => None,
+            Entry::Running(handle) => Some(handle),
+        }
+    }
+}
+
+/// Removes the [`Entry::Starting`] reservation unless the game has been spawned.
+struct Reservation<'a> {
+    supervisor: &'a Supervisor,
+    profile_id: usize,
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        let mut registry = self.supervisor.registry();
+        if matches!(registry.get(&self.profile_id), Some(Entry::Starting)) {
+            registry.remove(&self.profile_id);
+        }
+    }
+}
+
+/// Keeps track of the running games.
+///
+/// Cloning the supervisor is cheap and all clones share the same registry.
+#[derive(Debug, Clone)]
+pub struct Supervisor {
+    running: Arc<Mutex<HashMap<usize, Entry>>>,
+    history: Option<HistoryStore>,
+    session_logs: Option<SessionLogs>,
+    stop_timeout: Duration,
+}
+
+impl Default for Supervisor {
+    fn default() -> Self {
+        Self {
+            running: Arc::default(),
+            history: None,
+            session_logs: None,
+            stop_timeout: DEFAULT_STOP_TIMEOUT,
+        }
+    }
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records every game started with [`Supervisor::launch`] in the history.
+    #[must_use]
+    pub fn with_history(mut self, history: HistoryStore) -> Self {
+        self.history = Some(history);
+        self
+    }
+
+    /// Writes the output of every game to its own log.
+    #[must_use]
+    pub fn with_session_logs(mut self, session_logs: SessionLogs) -> Self {
+        self.session_logs = Some(session_logs);
+        self
+    }
+
+    /// Sets how long a game may take to exit after [`StopRequest::Graceful`].
+    #[must_use]
+    pub fn with_stop_timeout(mut self, timeout: Duration) -> Self {
+        self.stop_timeout = timeout;
+        self
+    }
+
+    pub fn history(&self) -> Option<&HistoryStore> {
+        self.history.as_ref()
+    }
+
+    pub fn session_logs(&self) -> Option<&SessionLogs> {
+        self.session_logs.as_ref()
+    }
+
+    /// Also `true` while [`Supervisor::launch`] is preparing the game.
+    pub fn is_running(&self, profile_id: usize) -> bool {
+        self.registry().contains_key(&profile_id)
+    }
+
+    pub fn get(&self, profile_id: usize) -> Option<GameHandle> {
+        self.registry()
+            .get(&profile_id)
+            .and_then(Entry::handle)
+            .cloned()
+    }
+
+    pub fn running(&self) -> Vec<GameHandle> {
+        self.registry()
+            .values()
+            .filter_map(Entry::handle)
+            .cloned()
+            .collect()
+    }
+
+    pub fn stop(&self, profile_id: usize, request: StopRequest) -> Result<(), SupervisorError> {
+        self.get(profile_id)
+            .ok_or(SupervisorError::NotRunning(profile_id))
+            .map(|handle| handle.stop(request))
+    }
+
+    /// Builds the launch command for the instance and spawns it.
+    pub async fn launch(
+        &self,
+        profile_id: usize,
+        instance: &LaunchInstance,
+        user_data: UserData,
+        java_runner: &JavaRunner,
+        quick_play: Option<QuickPlay>,
+    ) -> anyhow::Result<GameHandle> {
+        let reservation = self.reserve(profile_id)?;
+
+        let command = instance.command(user_data, java_runner, quick_play).await?;
+
+        let hooks = instance.settings.hooks.clone();
+        let hook_context = instance.hook_context();
+
+        let spawned = match hooks.run_pre_launch(&hook_context).await {
+            Ok(()) => self.spawn_entry(
+                profile_id,
+                command,
+                Some(instance.settings.game_dir.clone()),
+                Some(reservation),
+            ),
+            Err(e) => Err(e),
+        };
+
+        let handle = match spawned {
+            Ok(handle) => handle,
+            Err(e) => {
+                instance.remove_argfile().await;
+                return Err(e);
+            }
+        };
+
+        if hooks.post_exit.is_some() || self.history.is_some() || instance.settings.use_argfile {
+            let game = handle.clone();
+            let history = self.history.clone();
+            let java = java_runner.get_string();
+            let instance = instance.clone();
+
+            tokio::spawn(async move {
+                let exit_code = game.wait().await.and_then(|exit| exit.code);
+                instance.remove_argfile().await;
+
+                if let Some(history) = history {
+                    let record = LaunchRecord {
+                        started_at: game.started_at,
+                        ended_at: SystemTime::now(),
+                        exit_code,
+                        java,
+                    };
+
+                    if let Err(e) = history.record(profile_id, record).await {
+                        warn!("Cannot save the launch history. Error: {e}");
+                    }
+                }
+
+                hooks
+                    .run_post_exit(&hook_context.with_exit_code(exit_code))
+                    .await;
+            });
+        }
+
+        Ok(handle)
+    }
+
+    /// Spawns the command with captured stdout and stderr and starts tracking it.
+    pub fn spawn(&self, profile_id: usize, command: Command) -> anyhow::Result<GameHandle> {
+        self.spawn_in(profile_id, command, None)
+    }
+
+    /// Same as [`Supervisor::spawn`] but also looks for crash reports in `game_dir`.
+    pub fn spawn_in(
+        &self,
+        profile_id: usize,
+        command: Command,
+        game_dir: Option<PathBuf>,
+    ) -> anyhow::Result<GameHandle> {
+        self.spawn_entry(profile_id, command, game_dir, None)
+    }
+
+    /// Marks the profile as running until the returned reservation is dropped.
+    fn reserve(&self, profile_id: usize) -> anyhow::Result<Reservation<'_>> {
+        let mut registry = self.registry();
+
+        if registry.contains_key(&profile_id) {
+            return Err(SupervisorError::AlreadyRunning(profile_id).into());
+        }
+
+        registry.insert(profile_id, Entry::Starting);
+
+        Ok(Reservation {
+            supervisor: self,
+            profile_id,
+        })
+    }
+
+    fn spawn_entry(
+        &self,
+        profile_id: usize,
+        mut command: Command,
+        game_dir: Option<PathBuf>,
+        reservation: Option<Reservation<'_>>,
+    ) -> anyhow::Result<GameHandle> {
+        let mut registry = self.registry();
+
+        if reservation.is_none() && registry.contains_key(&profile_id) {
+            return Err(SupervisorError::AlreadyRunning(profile_id).into());
+        }
+
+        // On error the registry is unlocked before the reservation is dropped
+        let mut child = command
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+
+        let (output_tx, _) = broadcast::channel(OUTPUT_CAPACITY);
+        let (stop_tx, stop_rx) = mpsc::unbounded_channel();
+        let (exit_tx, exit_rx) = watch::channel(None);
+        let started_at = SystemTime::now();
+
+        let session_log = self.session_logs.as_ref().and_then(|session_logs| {
+            session_logs
+                .start(profile_id, started_at)
+                .inspect_err(|e| {
+                    warn!("Cannot create the session log (profile: {profile_id}). Error: {e}");
+                })
+                .ok()
+        });
+        let (log_tx, session_log) = session_log.map(spawn_session_log).unzip();
+
+        let mut forwarders = JoinSet::new();
+
+        if let Some(stdout) = child.stdout.take() {
+            forwarders.spawn(forward_lines(
+                stdout,
+                OutputStream::Stdout,
+                output_tx.clone(),
+                log_tx.clone(),
+            ));
+        }
+
+        if let Some(stderr) = child.stderr.take() {
+            forwarders.spawn(forward_lines(
+                stderr,
+                OutputStream::Stderr,
+                output_tx.clone(),
+                log_tx,
+            ));
+        }
+
+        let handle = GameHandle {
+            profile_id,
+            pid: child.id(),
+            started_at,
+            output: output_tx,
+            stop: stop_tx,
+            exit: exit_rx,
+            resources: ResourceSamples::default(),
+        };
+
+        info!(
+            "Started the game (profile: {profile_id}, pid: {})",
+            handle
+                .pid
+                .map_or("unknown".to_owned(), |pid| pid.to_string())
+        );
+
+        registry.insert(profile_id, Entry::Running(handle.clone()));
+        // The entry is taken over by the game, it must not be removed by the reservation
+        std::mem::forget(reservation);
+        drop(registry);
+
+        tokio::spawn(self.clone().watch(
+            Watched {
+                profile_id,
+                child,
+                game_dir,
+                started_at: handle.started_at,
+                resources: handle.resources.clone(),
+                session_log,
+            },
+            forwarders,
+            stop_rx,
+            exit_tx,
+        ));
+
+        Ok(handle)
+    }
+
+    async fn watch(
+        self,
+        Watched {
+            profile_id,
+            mut child,
+            game_dir,
+            started_at,
+            resources,
+            session_log,
+        }: Watched,
+        mut forwarders: JoinSet<()>,
+        mut stop: mpsc::UnboundedReceiver<StopRequest>,
+        exit: watch::Sender<Option<GameExit>>,
+    ) {
+        let started = Instant::now();
+        let mut is_stopped = false;
+        let mut kill_deadline = None;
+
+        let mut sampler = child.id().map(|pid| ProcessSampler::new(pid, started));
+        let mut sampling = tokio::time::interval(SAMPLE_INTERVAL);
+        sampling.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+        let status = loop {
+            tokio::select! {
+                status = child.wait() => break status,
+                Some(request) = stop.recv() => {
+                    is_stopped = true;
+                    handle_stop_request(&mut child, request);
+
+                    if request == StopRequest::Graceful {
+                        kill_deadline.get_or_insert(tokio::time::Instant::now() + self.stop_timeout);
+                    }
+                }
+                () = tokio::time::sleep_until(kill_deadline.unwrap_or_else(tokio::time::Instant::now)), if kill_deadline.is_some() => {
+                    warn!("The game did not exit in {:?}, killing it (profile: {profile_id})", self.stop_timeout);
+                    kill_deadline = None;
+                    handle_stop_request(&mut child, StopRequest::Forced);
+                }
+                _ = sampling.tick(), if sampler.is_some() => {
+                    if let Some(sample) = sampler.as_mut().and_then(ProcessSampler::sample) {
+                        resources.push(sample);
+                    }
+                }
+            }
+        };
+
+        let drain = async { while forwarders.join_next().await.is_some() {} };
+        if tokio::time::timeout(OUTPUT_DRAIN_TIMEOUT, drain)
+            .await
+            .is_err()
+        {
+            forwarders.abort_all();
+        }
+
+        // The writer finishes once the forwarders are gone
+        if let Some(session_log) = session_log {
+            if tokio::time::timeout(OUTPUT_DRAIN_TIMEOUT, session_log)
+                .await
+                .is_err()
+            {
+                warn!("The session log is still being written (profile: {profile_id})");
+            }
+        }
+
+        let reason = match status {
+            Ok(status) => ExitReason::from_status(status),
+            Err(e) => {
+                warn!("Cannot get the exit status of the game (profile: {profile_id}). Error: {e}");
+                ExitReason::Unknown
+            }
+        };
+        let code = match reason {
+            ExitReason::Code(code) => Some(code),
+            ExitReason::Signal(_) | ExitReason::Unknown => None,
+        };
+
+        let crash = if is_stopped {
+            None
+        } else {
+            Crash::detect(reason, game_dir.as_deref(), started_at).await
+        };
+
+        if let Some(crash) = &crash {
+            warn!("{crash} (profile: {profile_id})");
+        }
+
+        let game_exit = GameExit {
+            profile_id,
+            code,
+            duration: started.elapsed(),
+            crash,
+        };
+
+        info!(
+            "Minecraft exit code: {} (profile: {profile_id}, played for {:?})",
+            code.map_or("none".to_owned(), |code| code.to_string()),
+            game_exit.duration
+        );
+
+        self.registry().remove(&profile_id);
+        let _ = exit.send(Some(game_exit));
+    }
+
+    fn registry(&self) -> std::sync::MutexGuard<'_, HashMap<usize, Entry>> {
+        // The registry is always left in a consistent state so a poisoned lock is fine
+        self.running
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+struct Watched {
+    profile_id: usize,
+    child: Child,
+    game_dir: Option<PathBuf>,
+    started_at: SystemTime,
+    resources: ResourceSamples,
+    session_log: Option<JoinHandle<()>>,
+}
+
+/// Writes the lines sent to the returned sender until all its clones are dropped.
+fn spawn_session_log(
+    mut writer: SessionLogWriter,
+) -> (mpsc::UnboundedSender<GameOutput>, JoinHandle<()>) {
+    let (tx, mut rx) = mpsc::unbounded_channel::<GameOutput>();
+
+    let task = tokio::spawn(async move {
+        while let Some(output) = rx.recv().await {
+            if let Err(e) = writer.write(&output).await {
+                warn!("Cannot write the session log. Error: {e}");
+                return;
+            }
+        }
+
+        if let Err(e) = writer.finish().await {
+            warn!("Cannot write the session log. Error: {e}");
+        }
+    });
+
+    (tx, task)
+}
+
+fn handle_stop_request(child: &mut Child, request: StopRequest) {
+    match request {
+        #[cfg(unix)]
+        StopRequest::Graceful => {
+            if let Some(pid) = child.id().and_then(|pid| i32::try_from(pid).ok()) {
+                // SAFETY: `kill` has no memory safety requirements
+                if unsafe { libc::kill(pid, libc::SIGTERM) } != 0 {
+                    warn!(
+                        "Cannot send SIGTERM to the game. Error: {}",
+                        std::io::Error::last_os_error()
+                    );
+                }
+            }
+        }
+        #[cfg(not(unix))]
+        StopRequest::Graceful => {
+            let _ = child
+                .start_kill()
+                .inspect_err(|e| warn!("Cannot stop the game. Error: {e}"));
+        }
+        StopRequest::Forced => {
+            let _ = child
+                .start_kill()
+                .inspect_err(|e| warn!("Cannot kill the game. Error: {e}"));
+        }
+    }
+}
+
+async fn forward_lines(
+    reader: impl AsyncRead + Unpin,
+    stream: OutputStream,
+    sender: broadcast::Sender<GameOutput>,
+    session_log: Option<mpsc::UnboundedSender<GameOutput>>,
+) {
+    let mut lines = BufReader::new(reader).lines();
+
+    while let Ok(Some(line)) = lines.next_line().await {
+        let output = GameOutput { stream, line };
+
+        if let Some(session_log) = &session_log {
+            // The writer has failed and already reported it
+            let _ = session_log.send(output.clone());
+        }
+
+        // Nobody is subscribed
+        let _ = sender.send(output);
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use crate::instance::launch::{hooks::LaunchHooks, LaunchInstanceBuilder, LaunchSettings};
+
+    use super::*;
+
+    fn shell(script: &str) -> Command {
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(script);
+        command
+    }
+
+    #[tokio::test]
+    async fn output_and_exit_test() {
+        let supervisor = Supervisor::new();
+
+        let handle = supervisor
+            .spawn(1, shell("sleep 0.2; echo out; echo err >&2; exit 3"))
+            .unwrap();
+        let mut output = handle.subscribe();
+
+        assert!(supervisor.is_running(1));
+        assert!(matches!(
+            supervisor
+                .spawn(1, shell("true"))
+                .unwrap_err()
+                .downcast_ref::<SupervisorError>(),
+            Some(SupervisorError::AlreadyRunning(1))
+        ));
+
+        let exit = handle.wait().await.unwrap();
+        assert_eq!(exit.code, Some(3));
+        assert_eq!(exit.crash.unwrap().reason, ExitReason::Code(3));
+        assert!(!supervisor.is_running(1));
+
+        let mut lines = vec![];
+        while let Ok(line) = output.try_recv() {
+            lines.push(line);
+        }
+
+        assert!(lines.contains(&GameOutput {
+            stream: OutputStream::Stdout,
+            line: "out".into()
+        }));
+        assert!(lines.contains(&GameOutput {
+            stream: OutputStream::Stderr,
+            line: "err".into()
+        }));
+    }
+
+    #[tokio::test]
+    async fn stop_test() {
+        let supervisor = Supervisor::new();
+
+        let handle = supervisor.spawn(2, shell("sleep 10")).unwrap();
+        supervisor.stop(2, StopRequest::Graceful).unwrap();
+        let exit = handle.wait().await.unwrap();
+        assert_eq!(exit.code, None);
+        assert!(exit.crash.is_none());
+
+        let handle = supervisor.spawn(2, shell("sleep 10")).unwrap();
+        supervisor.stop(2, StopRequest::Forced).unwrap();
+        assert_eq!(handle.wait().await.unwrap().code, None);
+
+        assert!(matches!(
+            supervisor.stop(2, StopRequest::Forced),
+            Err(SupervisorError::NotRunning(2))
+        ));
+    }
+
+    #[tokio::test]
+    async fn concurrent_launch_test() {
+        let dir =
+            std::env::temp_dir().join(format!("nomi-supervisor-launch-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let manifest = serde_json::json!({
+            "minecraftArguments": "--username ${auth_player_name}",
+            "assetIndex": { "id": "1", "sha1": "", "size": 0, "totalSize": 0, "url": "" },
+            "assets": "1",
+            "complianceLevel": 0,
+            "downloads": { "client": { "sha1": "", "size": 0, "url": "" } },
+            "id": "1.0",
+            "javaVersion": { "component": "jre-legacy", "majorVersion": 8 },
+            "libraries": [],
+            "mainClass": "net.minecraft.client.Minecraft",
+            "minimumLauncherVersion": 0,
+            "releaseTime": "",
+            "time": "",
+            "type": "release"
+        });
+        std::fs::write(dir.join("1.0.json"), manifest.to_string()).unwrap();
+
+        let instance = LaunchInstanceBuilder::new()
+            .settings(LaunchSettings {
+                game_dir: dir.clone(),
+                manifest_file: dir.join("1.0.json"),
+                natives_dir: dir.join("natives"),
+                version_jar_file: dir.join("1.0.jar"),
+                version: "1.0".into(),
+                hooks: LaunchHooks {
+                    pre_launch: Some("sleep 0.3; echo started >> pre_launch.log".into()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            })
+            .build();
+
+        let java_runner = JavaRunner::Command("true".into());
+
+        let supervisor = Supervisor::new();
+        let (first, second) = tokio::join!(
+            supervisor.launch(6, &instance, UserData::default(), &java_runner, None),
+            supervisor.launch(6, &instance, UserData::default(), &java_runner, None),
+        );
+
+        let error = second.unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<SupervisorError>(),
+            Some(SupervisorError::AlreadyRunning(6))
+        ));
+        first.unwrap().wait().await.unwrap();
+
+        let log = std::fs::read_to_string(dir.join("pre_launch.log")).unwrap();
+        assert_eq!(log.lines().count(), 1);
+        assert!(!supervisor.is_running(6));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn failed_launch_test() {
+        let supervisor = Supervisor::new();
+        let instance = LaunchInstanceBuilder::new()
+            .settings(LaunchSettings {
+                manifest_file: "missing.json".into(),
+                ..Default::default()
+            })
+            .build();
+
+        let java_runner = JavaRunner::Command("true".into());
+
+        assert!(supervisor
+            .launch(7, &instance, UserData::default(), &java_runner, None)
+            .await
+            .is_err());
+        assert!(!supervisor.is_running(7));
+    }
+
+    #[tokio::test]
+    async fn session_log_test() {
+        let dir = std::env::temp_dir().join(format!("nomi-supervisor-logs-{}", std::process::id()));
+        let supervisor = Supervisor::new().with_session_logs(SessionLogs::new(&dir));
+
+        let handle = supervisor
+            .spawn(5, shell("echo hello; echo oops >&2"))
+            .unwrap();
+        handle.wait().await.unwrap();
+
+        let logs = supervisor.session_logs().unwrap().list(5).unwrap();
+        assert_eq!(logs.len(), 1);
+
+        let content = logs[0].read().await.unwrap();
+        assert!(content.contains("hello\n"));
+        assert!(content.contains("[STDERR] oops\n"));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn stop_timeout_test() {
+        let supervisor = Supervisor::new().with_stop_timeout(Duration::from_millis(200));
+
+        let handle = supervisor
+            .spawn(3, shell("trap '' TERM; while true; do sleep 0.1; done"))
+            .unwrap();
+        // Gives the shell time to ignore the signal
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        supervisor.stop(3, StopRequest::Graceful).unwrap();
+        let exit = tokio::time::timeout(Duration::from_secs(5), handle.wait())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(exit.code, None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[tokio::test]
+    async fn resources_test() {
+        let supervisor = Supervisor::new();
+
+        let handle = supervisor.spawn(4, shell("sleep 10")).unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let sample = handle.resource_usage().unwrap();
+        assert!(sample.memory > 0);
+        assert_eq!(handle.resource_history().len(), 1);
+
+        supervisor.stop(4, StopRequest::Forced).unwrap();
+        handle.wait().await.unwrap();
+    }
+}
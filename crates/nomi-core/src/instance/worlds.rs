@@ -0,0 +1,586 @@
+use std::{
+    fs::File,
+    io,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use anyhow::{anyhow, bail};
+use egui_task_manager::Progress;
+use tracing::{info, warn};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::{
+    downloads::progress::ProgressSender,
+    nbt::{Nbt, Tag},
+};
+
+/// Directory inside the game directory with the worlds.
+pub const SAVES_DIR: &str = "saves";
+pub const LEVEL_DAT: &str = "level.dat";
+
+/// Held by the game while the world is open, cannot be read on Windows.
+const SESSION_LOCK: &str = "session.lock";
+
+/// One more file has been archived or extracted.
+pub struct FileProgress;
+
+impl Progress for FileProgress {
+    fn apply(&self, current: &mut u32) {
+        *current += 1;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameMode {
+    Survival,
+    Creative,
+    Adventure,
+    Spectator,
+}
+
+impl GameMode {
+    pub fn from_id(id: i32) -> Option<Self> {
+        match id {
+            0 => Some(Self::Survival),
+            1 => Some(Self::Creative),
+            2 => Some(Self::Adventure),
+            3 => Some(Self::Spectator),
+            _ => None,
+        }
+    }
+}
+
+/// The parts of `level.dat` shown to the player.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LevelInfo {
+    pub name: String,
+    /// `None` for worlds created before 1.9.
+    pub game_version: Option<String>,
+    pub last_played: Option<SystemTime>,
+    pub game_mode: Option<GameMode>,
+    pub hardcore: bool,
+    pub seed: Option<i64>,
+}
+
+impl LevelInfo {
+    pub fn from_nbt(nbt: &Nbt) -> Option<Self> {
+        let data = nbt.root.get("Data")?.as_compound()?;
+        let get = |key: &str| data.get(key);
+
+        let last_played = get("LastPlayed")
+            .and_then(Tag::as_long)
+            .and_then(|millis| u64::try_from(millis).ok())
+            .map(|millis| SystemTime::UNIX_EPOCH + Duration::from_millis(millis));
+
+        // 1.16+ keeps the seed in the world generation settings
+        let seed = get("WorldGenSettings")
+            .and_then(Tag::as_compound)
+            .and_then(|settings| settings.get("seed"))
+            .or_else(|| get("RandomSeed"))
+            .and_then(Tag::as_long);
+
+        Some(Self {
+            name: get("LevelName")
+                .and_then(Tag::as_str)
+                .unwrap_or_default()
+                .to_owned(),
+            game_version: get("Version")
+                .and_then(Tag::as_compound)
+                .and_then(|version| version.get("Name"))
+                .and_then(Tag::as_str)
+                .map(ToOwned::to_owned),
+            last_played,
+            game_mode: get("GameType")
+                .and_then(Tag::as_int)
+                .and_then(GameMode::from_id),
+            hardcore: get("hardcore")
+                .and_then(Tag::as_byte)
+                .is_some_and(|b| b != 0),
+            seed,
+        })
+    }
+
+    pub async fn read(world_dir: &Path) -> anyhow::Result<Self> {
+        let bytes = tokio::fs::read(world_dir.join(LEVEL_DAT)).await?;
+        let nbt = Nbt::from_bytes(&bytes)?;
+
+        Self::from_nbt(&nbt)
+            .ok_or_else(|| anyhow!("{} has no world data", world_dir.join(LEVEL_DAT).display()))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct World {
+    pub dir: PathBuf,
+    pub info: LevelInfo,
+}
+
+impl World {
+    /// Name of the world's folder, which is what the backups are grouped by.
+    pub fn folder_name(&self) -> String {
+        folder_name(&self.dir)
+    }
+}
+
+/// Worlds of the game directory, the most recently played first.
+///
+/// Folders without a readable `level.dat` are skipped.
+pub async fn list_worlds(game_dir: &Path) -> anyhow::Result<Vec<World>> {
+    let saves = game_dir.join(SAVES_DIR);
+
+    let mut entries = match tokio::fs::read_dir(&saves).await {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut worlds = Vec::new();
+
+    while let Some(entry) = entries.next_entry().await? {
+        let dir = entry.path();
+        if !tokio::fs::try_exists(dir.join(LEVEL_DAT))
+            .await
+            .unwrap_or(false)
+        {
+            continue;
+        }
+
+        match LevelInfo::read(&dir).await {
+            Ok(info) => worlds.push(World { dir, info }),
+            Err(e) => warn!("Skipping the world {}. Error: {e}", dir.display()),
+        }
+    }
+
+    worlds.sort_by_key(|world| std::cmp::Reverse(world.info.last_played));
+
+    Ok(worlds)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Backup {
+    pub path: PathBuf,
+    pub created: SystemTime,
+}
+
+/// Backups of the world, the newest first.
+///
+/// Backups are kept as `<backups_dir>/<world folder>/<unix millis>.zip`.
+pub fn list_backups(backups_dir: &Path, world_folder: &str) -> anyhow::Result<Vec<Backup>> {
+    let dir = backups_dir.join(world_folder);
+
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut backups = Vec::new();
+
+    for entry in entries {
+        let path = entry?.path();
+
+        if path.extension().is_none_or(|extension| extension != "zip") {
+            continue;
+        }
+
+        let Some(millis) = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse::<u64>().ok())
+        else {
+            continue;
+        };
+
+        backups.push(Backup {
+            path,
+            created: SystemTime::UNIX_EPOCH + Duration::from_millis(millis),
+        });
+    }
+
+    backups.sort_by_key(|backup| std::cmp::Reverse(backup.created));
+
+    Ok(backups)
+}
+
+/// Archives the world and removes the oldest backups so at most `keep` are left.
+pub async fn backup_world(
+    world_dir: &Path,
+    backups_dir: &Path,
+    keep: usize,
+    progress: &dyn ProgressSender<FileProgress>,
+) -> anyhow::Result<Backup> {
+    let folder = folder_name(world_dir);
+    let created = SystemTime::now();
+    let millis = created.duration_since(SystemTime::UNIX_EPOCH)?.as_millis();
+
+    let path = backups_dir.join(&folder).join(format!("{millis}.zip"));
+
+    let world_dir = world_dir.to_path_buf();
+    let backups_dir = backups_dir.to_path_buf();
+    let archive = path.clone();
+
+    run_blocking(progress, move |tick| {
+        write_archive(&world_dir, &archive, tick)?;
+
+        info!("Backed up the world {folder} to {}", archive.display());
+
+        for outdated in list_backups(&backups_dir, &folder)?
+            .iter()
+            .skip(keep.max(1))
+        {
+            std::fs::remove_file(&outdated.path)?;
+        }
+
+        Ok(())
+    })
+    .await?;
+
+    Ok(Backup { path, created })
+}
+
+/// Replaces the world with the contents of the backup.
+///
+/// The backup is extracted next to the world first, so a broken archive leaves the world as is.
+pub async fn restore_backup(
+    backup: &Path,
+    world_dir: &Path,
+    progress: &dyn ProgressSender<FileProgress>,
+) -> anyhow::Result<()> {
+    let saves = world_dir
+        .parent()
+        .ok_or_else(|| anyhow!("{} is not inside a saves directory", world_dir.display()))?;
+
+    let temp = saves.join(format!(".{}.restoring", folder_name(world_dir)));
+    let backup = backup.to_path_buf();
+    let world_dir = world_dir.to_path_buf();
+
+    run_blocking(progress, move |tick| {
+        if temp.exists() {
+            std::fs::remove_dir_all(&temp)?;
+        }
+
+        if let Err(e) = extract_archive(&backup, &temp, tick) {
+            let _ = std::fs::remove_dir_all(&temp);
+            return Err(e);
+        }
+
+        if world_dir.exists() {
+            std::fs::remove_dir_all(&world_dir)?;
+        }
+        std::fs::rename(&temp, &world_dir)?;
+
+        Ok(())
+    })
+    .await
+}
+
+/// Archives the world as `<world folder>/...`, the layout [`import_world`] expects.
+pub async fn export_world(
+    world_dir: &Path,
+    archive: &Path,
+    progress: &dyn ProgressSender<FileProgress>,
+) -> anyhow::Result<()> {
+    let world_dir = world_dir.to_path_buf();
+    let archive = archive.to_path_buf();
+
+    run_blocking(progress, move |tick| {
+        write_archive(&world_dir, &archive, tick)
+    })
+    .await
+}
+
+/// Extracts the world from the archive into the saves directory of `game_dir`.
+///
+/// The world may be at the root of the archive or inside a folder.
+/// Returns the directory of the imported world, renamed if the name is already taken.
+pub async fn import_world(
+    archive: &Path,
+    game_dir: &Path,
+    progress: &dyn ProgressSender<FileProgress>,
+) -> anyhow::Result<PathBuf> {
+    let saves = game_dir.join(SAVES_DIR);
+    let archive = archive.to_path_buf();
+
+    run_blocking(progress, move |tick| {
+        let root = world_root(&archive)?;
+
+        let name = root
+            .file_name()
+            .or_else(|| archive.file_stem())
+            .map_or_else(
+                || "World".to_owned(),
+                |name| name.to_string_lossy().into_owned(),
+            );
+
+        let mut target = saves.join(&name);
+        let mut index = 1;
+        while target.exists() {
+            target = saves.join(format!("{name} ({index})"));
+            index += 1;
+        }
+
+        extract_archive(&archive, &target, tick)?;
+
+        Ok(target)
+    })
+    .await
+}
+
+/// Number of files [`export_world`] and [`backup_world`] archive.
+pub async fn count_world_files(world_dir: &Path) -> anyhow::Result<u32> {
+    let world_dir = world_dir.to_path_buf();
+    let files = tokio::task::spawn_blocking(move || world_files(&world_dir)).await??;
+
+    Ok(u32::try_from(files.len())?)
+}
+
+/// Number of files [`import_world`] and [`restore_backup`] extract.
+pub async fn count_archive_files(archive: &Path) -> anyhow::Result<u32> {
+    let archive = archive.to_path_buf();
+
+    tokio::task::spawn_blocking(move || {
+        let archive = ZipArchive::new(File::open(archive)?)?;
+        Ok(u32::try_from(
+            archive
+                .file_names()
+                .filter(|name| !name.ends_with('/'))
+                .count(),
+        )?)
+    })
+    .await?
+}
+
+/// Runs the file work on the blocking thread pool and forwards every `tick` it makes to `progress`.
+async fn run_blocking<T: Send + 'static>(
+    progress: &dyn ProgressSender<FileProgress>,
+    work: impl FnOnce(&dyn Fn()) -> anyhow::Result<T> + Send + 'static,
+) -> anyhow::Result<T> {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+
+    let task = tokio::task::spawn_blocking(move || {
+        work(&|| {
+            // Nobody waits for the progress once the task is dropped
+            let _ = tx.send(());
+        })
+    });
+
+    // Ends once the work is done and the sender is dropped
+    while rx.recv().await.is_some() {
+        progress.update(FileProgress).await;
+    }
+
+    task.await?
+}
+
+fn write_archive(world_dir: &Path, archive: &Path, tick: &dyn Fn()) -> anyhow::Result<()> {
+    let folder = folder_name(world_dir);
+    let files = world_files(world_dir)?;
+
+    if let Some(parent) = archive.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    // Written next to the target so a failed export does not leave a broken archive
+    let temp = archive.with_extension("zip.part");
+    let mut zip = ZipWriter::new(File::create(&temp)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    for file in files {
+        let relative = file.strip_prefix(world_dir)?;
+        let name = Path::new(&folder)
+            .join(relative)
+            .to_string_lossy()
+            .replace('\\', "/");
+
+        zip.start_file(name, options)?;
+        io::copy(&mut File::open(&file)?, &mut zip)?;
+
+        tick();
+    }
+
+    zip.finish()?;
+    std::fs::rename(&temp, archive)?;
+
+    Ok(())
+}
+
+fn extract_archive(archive: &Path, target: &Path, tick: &dyn Fn()) -> anyhow::Result<()> {
+    let root = world_root(archive)?;
+    let mut zip = ZipArchive::new(File::open(archive)?)?;
+
+    std::fs::create_dir_all(target)?;
+
+    for index in 0..zip.len() {
+        let mut file = zip.by_index(index)?;
+
+        // Protects from paths like `../../file`
+        let Some(name) = file.enclosed_name() else {
+            continue;
+        };
+
+        let Ok(relative) = name.strip_prefix(&root) else {
+            continue;
+        };
+
+        if file.is_dir() || relative.as_os_str().is_empty() {
+            continue;
+        }
+
+        let path = target.join(relative);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        io::copy(&mut file, &mut File::create(path)?)?;
+        drop(file);
+
+        tick();
+    }
+
+    Ok(())
+}
+
+/// Folder of the archive with `level.dat`, the closest one to the root.
+fn world_root(archive: &Path) -> anyhow::Result<PathBuf> {
+    let zip = ZipArchive::new(File::open(archive)?)?;
+
+    let root = zip
+        .file_names()
+        .map(Path::new)
+        .filter(|name| name.file_name().is_some_and(|name| name == LEVEL_DAT))
+        .filter_map(Path::parent)
+        .min_by_key(|parent| parent.components().count())
+        .map(Path::to_path_buf);
+
+    match root {
+        Some(root) => Ok(root),
+        None => bail!("{} does not contain a world", archive.display()),
+    }
+}
+
+fn world_files(world_dir: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    if !world_dir.join(LEVEL_DAT).exists() {
+        bail!("{} is not a world", world_dir.display());
+    }
+
+    let mut files = Vec::new();
+    let mut dirs = vec![world_dir.to_path_buf()];
+
+    while let Some(dir) = dirs.pop() {
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            let path = entry.path();
+
+            if entry.file_type()?.is_dir() {
+                dirs.push(path);
+            } else if entry.file_name() != SESSION_LOCK {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+fn folder_name(world_dir: &Path) -> String {
+    world_dir.file_name().map_or_else(
+        || "World".to_owned(),
+        |name| name.to_string_lossy().into_owned(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::nbt::Compound;
+
+    use super::*;
+
+    /// Builds a minimal `level.dat`.
+    fn level_dat(name: &str, last_played: i64) -> Nbt {
+        let mut version = Compound::new();
+        version.insert("Name".into(), "1.20.4".into());
+
+        let mut settings = Compound::new();
+        settings.insert("seed".into(), Tag::Long(-42));
+
+        let mut data = Compound::new();
+        data.insert("LevelName".into(), name.into());
+        data.insert("LastPlayed".into(), Tag::Long(last_played));
+        data.insert("GameType".into(), Tag::Int(1));
+        data.insert("hardcore".into(), Tag::Byte(0));
+        data.insert("Version".into(), version.into());
+        data.insert("WorldGenSettings".into(), settings.into());
+
+        let mut root = Compound::new();
+        root.insert("Data".into(), data.into());
+
+        Nbt::new(root)
+    }
+
+    fn write_world(dir: &Path, name: &str, last_played: i64) {
+        std::fs::create_dir_all(dir.join("region")).unwrap();
+        std::fs::write(
+            dir.join(LEVEL_DAT),
+            level_dat(name, last_played).to_gzip_bytes().unwrap(),
+        )
+        .unwrap();
+        std::fs::write(dir.join("region").join("r.0.0.mca"), name).unwrap();
+        std::fs::write(dir.join(SESSION_LOCK), "").unwrap();
+    }
+
+    #[tokio::test]
+    async fn worlds_test() {
+        let root = std::env::temp_dir().join(format!("nomi-worlds-test-{}", std::process::id()));
+        let game_dir = root.join("game");
+        let backups = root.join("backups");
+        let (progress, _rx) = std::sync::mpsc::channel();
+
+        let survival = game_dir.join(SAVES_DIR).join("Survival");
+        write_world(&survival, "My survival", 2000);
+        write_world(&game_dir.join(SAVES_DIR).join("Creative"), "Flat", 1000);
+        std::fs::create_dir_all(game_dir.join(SAVES_DIR).join("empty")).unwrap();
+
+        let worlds = list_worlds(&game_dir).await.unwrap();
+        assert_eq!(worlds.len(), 2);
+        assert_eq!(worlds[0].info.name, "My survival");
+        assert_eq!(worlds[0].info.game_version.as_deref(), Some("1.20.4"));
+        assert_eq!(worlds[0].info.game_mode, Some(GameMode::Creative));
+        assert_eq!(worlds[0].info.seed, Some(-42));
+        assert_eq!(worlds[0].folder_name(), "Survival");
+
+        assert_eq!(count_world_files(&survival).await.unwrap(), 2);
+
+        for _ in 0..3 {
+            backup_world(&survival, &backups, 2, &progress)
+                .await
+                .unwrap();
+            // Backups are named by milliseconds
+            tokio::time::sleep(Duration::from_millis(2)).await;
+        }
+        let backup_list = list_backups(&backups, "Survival").unwrap();
+        assert_eq!(backup_list.len(), 2);
+        assert_eq!(count_archive_files(&backup_list[0].path).await.unwrap(), 2);
+
+        std::fs::write(survival.join("region").join("r.0.0.mca"), "broken").unwrap();
+        restore_backup(&backup_list[0].path, &survival, &progress)
+            .await
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(survival.join("region").join("r.0.0.mca")).unwrap(),
+            "My survival"
+        );
+
+        let exported = root.join("exports").join("Survival.zip");
+        export_world(&survival, &exported, &progress).await.unwrap();
+        let imported = import_world(&exported, &game_dir, &progress).await.unwrap();
+        assert_eq!(imported, game_dir.join(SAVES_DIR).join("Survival (1)"));
+        assert_eq!(
+            LevelInfo::read(&imported).await.unwrap().name,
+            "My survival"
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -3,11 +3,13 @@
 #![allow(clippy::module_name_repetitions)]
 #![allow(clippy::must_use_candidate)]
 #![allow(clippy::cast_possible_truncation)]
+pub mod auth;
 pub mod configs;
 pub mod downloads;
 pub mod instance;
 pub mod loaders;
 pub mod repository;
+pub mod server_ping;
 
 pub mod error;
 pub mod utils;
@@ -15,6 +17,7 @@ pub mod utils;
 pub mod fs;
 pub mod game_paths;
 pub mod maven_data;
+pub mod nbt;
 pub mod state;
 
 pub mod consts;
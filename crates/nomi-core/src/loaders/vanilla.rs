@@ -2,7 +2,7 @@ use std::path::Path;
 
 use reqwest::Client;
 
-use tracing::error;
+use tracing::{error, warn};
 
 use crate::{
     downloads::{
@@ -16,7 +16,8 @@ use crate::{
     },
     fs::write_to_file,
     game_paths::GamePaths,
-    repository::manifest::{Classifiers, DownloadFile, Library, Manifest},
+    instance::launch::{natives::native_download, rules::Environment},
+    repository::manifest::{DownloadFile, Library, Manifest},
     state::get_launcher_manifest,
 };
 
@@ -95,6 +96,10 @@ struct VanillaLibrariesMapper<'a> {
 
 impl LibrariesMapper<Library> for VanillaLibrariesMapper<'_> {
     fn proceed(&self, library: &Library) -> Option<FileDownloader> {
+        if !Environment::current().is_library_allowed(library) {
+            return None;
+        }
+
         library
             .downloads
             .artifact
@@ -109,20 +114,16 @@ struct VanillaNativeLibrariesMapper<'a> {
 
 impl LibrariesMapper<Library> for VanillaNativeLibrariesMapper<'_> {
     fn proceed(&self, library: &Library) -> Option<FileDownloader> {
-        fn match_natives(natives: &Classifiers) -> Option<&DownloadFile> {
-            match std::env::consts::OS {
-                "linux" => natives.natives_linux.as_ref(),
-                "windows" => natives.natives_windows.as_ref(),
-                "macos" => natives.natives_macos.as_ref(),
-                _ => unreachable!(),
-            }
+        let environment = Environment::current();
+
+        if !environment.is_library_allowed(library) {
+            return None;
         }
 
-        library
-            .downloads
-            .classifiers
-            .as_ref()
-            .and_then(match_natives)
+        native_download(&environment, library)
+            .inspect_err(|e| warn!("Skipping natives of {}. Error: {e}", library.name))
+            .ok()
+            .flatten()
             .and_then(|file| manifest_file_to_downloader(file, self.path))
     }
 }
@@ -0,0 +1,559 @@
+//! Minimal reader and writer of Minecraft's Named Binary Tag format.
+
+use std::{
+    collections::BTreeMap,
+    io::{self, Read, Write},
+};
+
+use flate2::{read::GzDecoder, write::GzEncoder, Compression};
+
+/// The game refuses deeper structures as well.
+const MAX_DEPTH: usize = 512;
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+pub type Compound = BTreeMap<String, Tag>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Tag {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<i8>),
+    String(String),
+    /// All elements must be of the same type.
+    List(Vec<Tag>),
+    Compound(Compound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum NbtError {
+    #[error(transparent)]
+    Io(#[from] io::Error),
+    #[error("Unknown tag id {0}")]
+    UnknownTag(u8),
+    #[error("The root tag must be a compound, found tag id {0}")]
+    RootIsNotCompound(u8),
+    #[error("Invalid string")]
+    InvalidString,
+    #[error("Invalid length {0}")]
+    InvalidLength(i64),
+    #[error("String is too long ({0} bytes)")]
+    StringTooLong(usize),
+    #[error("List elements must be of the same type")]
+    MixedList,
+    #[error("Nesting is deeper than {MAX_DEPTH} levels")]
+    TooDeep,
+}
+
+impl Tag {
+    pub fn id(&self) -> u8 {
+        match self {
+            Tag::Byte(_) => 1,
+            Tag::Short(_) => 2,
+            Tag::Int(_) => 3,
+            Tag::Long(_) => 4,
+            Tag::Float(_) => 5,
+            Tag::Double(_) => 6,
+            Tag::ByteArray(_) => 7,
+            Tag::String(_) => 8,
+            Tag::List(_) => 9,
+            Tag::Compound(_) => 10,
+            Tag::IntArray(_) => 11,
+            Tag::LongArray(_) => 12,
+        }
+    }
+
+    pub fn as_byte(&self) -> Option<i8> {
+        match self {
+            Tag::Byte(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_int(&self) -> Option<i32> {
+        match self {
+            Tag::Int(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_long(&self) -> Option<i64> {
+        match self {
+            Tag::Long(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Tag::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Tag]> {
+        match self {
+            Tag::List(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound(&self) -> Option<&Compound> {
+        match self {
+            Tag::Compound(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_compound_mut(&mut self) -> Option<&mut Compound> {
+        match self {
+            Tag::Compound(value) => Some(value),
+            _ => None,
+        }
+    }
+}
+
+impl From<i8> for Tag {
+    fn from(value: i8) -> Self {
+        Tag::Byte(value)
+    }
+}
+
+impl From<i32> for Tag {
+    fn from(value: i32) -> Self {
+        Tag::Int(value)
+    }
+}
+
+impl From<i64> for Tag {
+    fn from(value: i64) -> Self {
+        Tag::Long(value)
+    }
+}
+
+impl From<&str> for Tag {
+    fn from(value: &str) -> Self {
+        Tag::String(value.to_owned())
+    }
+}
+
+impl From<String> for Tag {
+    fn from(value: String) -> Self {
+        Tag::String(value)
+    }
+}
+
+impl From<Compound> for Tag {
+    fn from(value: Compound) -> Self {
+        Tag::Compound(value)
+    }
+}
+
+/// NBT file: a named root compound.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Nbt {
+    /// Usually empty.
+    pub name: String,
+    pub root: Compound,
+}
+
+impl Nbt {
+    pub fn new(root: Compound) -> Self {
+        Self {
+            name: String::new(),
+            root,
+        }
+    }
+
+    /// Reads uncompressed NBT.
+    pub fn read(reader: impl Read) -> Result<Self, NbtError> {
+        let mut decoder = Decoder { reader };
+
+        let id = decoder.u8()?;
+        if id != 10 {
+            return Err(NbtError::RootIsNotCompound(id));
+        }
+
+        let name = decoder.string()?;
+        let root = decoder.compound(0)?;
+
+        Ok(Self { name, root })
+    }
+
+    /// Writes uncompressed NBT.
+    pub fn write(&self, writer: impl Write) -> Result<(), NbtError> {
+        let mut encoder = Encoder { writer };
+
+        encoder.writer.write_all(&[10])?;
+        encoder.string(&self.name)?;
+        encoder.compound(&self.root)
+    }
+
+    /// Reads NBT that is either uncompressed (e.g. `servers.dat`) or gzipped (e.g. `level.dat`).
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, NbtError> {
+        if bytes.starts_with(&GZIP_MAGIC) {
+            Self::read(GzDecoder::new(bytes))
+        } else {
+            Self::read(bytes)
+        }
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, NbtError> {
+        let mut bytes = Vec::new();
+        self.write(&mut bytes)?;
+        Ok(bytes)
+    }
+
+    pub fn to_gzip_bytes(&self) -> Result<Vec<u8>, NbtError> {
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        self.write(&mut encoder)?;
+        Ok(encoder.finish()?)
+    }
+}
+
+struct Decoder<R> {
+    reader: R,
+}
+
+impl<R: Read> Decoder<R> {
+    fn array<const N: usize>(&mut self) -> io::Result<[u8; N]> {
+        let mut buf = [0; N];
+        self.reader.read_exact(&mut buf)?;
+        Ok(buf)
+    }
+
+    fn u8(&mut self) -> io::Result<u8> {
+        self.array::<1>().map(|[byte]| byte)
+    }
+
+    fn i32(&mut self) -> io::Result<i32> {
+        self.array().map(i32::from_be_bytes)
+    }
+
+    fn length(&mut self) -> Result<usize, NbtError> {
+        let length = self.i32()?;
+        usize::try_from(length).map_err(|_| NbtError::InvalidLength(length.into()))
+    }
+
+    fn bytes(&mut self, length: usize) -> Result<Vec<u8>, NbtError> {
+        // Does not trust the length to allocate the buffer, the data may be broken
+        let mut bytes = Vec::new();
+        (&mut self.reader)
+            .take(length as u64)
+            .read_to_end(&mut bytes)?;
+
+        if bytes.len() == length {
+            Ok(bytes)
+        } else {
+            Err(io::Error::from(io::ErrorKind::UnexpectedEof).into())
+        }
+    }
+
+    fn string(&mut self) -> Result<String, NbtError> {
+        let length = u16::from_be_bytes(self.array()?);
+        let bytes = self.bytes(length.into())?;
+
+        decode_mutf8(&bytes).ok_or(NbtError::InvalidString)
+    }
+
+    fn compound(&mut self, depth: usize) -> Result<Compound, NbtError> {
+        let mut compound = Compound::new();
+
+        loop {
+            let id = self.u8()?;
+            if id == 0 {
+                return Ok(compound);
+            }
+
+            let name = self.string()?;
+            let tag = self.payload(id, depth + 1)?;
+            compound.insert(name, tag);
+        }
+    }
+
+    fn payload(&mut self, id: u8, depth: usize) -> Result<Tag, NbtError> {
+        if depth > MAX_DEPTH {
+            return Err(NbtError::TooDeep);
+        }
+
+        let tag = match id {
+            1 => Tag::Byte(i8::from_be_bytes(self.array()?)),
+            2 => Tag::Short(i16::from_be_bytes(self.array()?)),
+            3 => Tag::Int(self.i32()?),
+            4 => Tag::Long(i64::from_be_bytes(self.array()?)),
+            5 => Tag::Float(f32::from_be_bytes(self.array()?)),
+            6 => Tag::Double(f64::from_be_bytes(self.array()?)),
+            7 => {
+                let length = self.length()?;
+                let bytes = self.bytes(length)?;
+                Tag::ByteArray(bytes.into_iter().map(|b| i8::from_be_bytes([b])).collect())
+            }
+            8 => Tag::String(self.string()?),
+            9 => {
+                let element_id = self.u8()?;
+                let length = self.length()?;
+
+                if element_id == 0 && length > 0 {
+                    return Err(NbtError::UnknownTag(0));
+                }
+
+                let mut list = Vec::new();
+                for _ in 0..length {
+                    list.push(self.payload(element_id, depth + 1)?);
+                }
+                Tag::List(list)
+            }
+            10 => Tag::Compound(self.compound(depth)?),
+            11 => {
+                let length = self.length()?;
+                let bytes = self.bytes(length.saturating_mul(4))?;
+                Tag::IntArray(
+                    bytes
+                        .chunks_exact(4)
+                        .map(|chunk| i32::from_be_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+                        .collect(),
+                )
+            }
+            12 => {
+                let length = self.length()?;
+                let bytes = self.bytes(length.saturating_mul(8))?;
+                Tag::LongArray(
+                    bytes
+                        .chunks_exact(8)
+                        .map(|chunk| {
+                            let mut buf = [0; 8];
+                            buf.copy_from_slice(chunk);
+                            i64::from_be_bytes(buf)
+                        })
+                        .collect(),
+                )
+            }
+            id => return Err(NbtError::UnknownTag(id)),
+        };
+
+        Ok(tag)
+    }
+}
+
+struct Encoder<W> {
+    writer: W,
+}
+
+impl<W: Write> Encoder<W> {
+    fn length(&mut self, length: usize) -> Result<(), NbtError> {
+        let length = i32::try_from(length)
+            .map_err(|_| NbtError::InvalidLength(i64::try_from(length).unwrap_or(i64::MAX)))?;
+        self.writer.write_all(&length.to_be_bytes())?;
+        Ok(())
+    }
+
+    fn string(&mut self, value: &str) -> Result<(), NbtError> {
+        let bytes = encode_mutf8(value);
+        let length =
+            u16::try_from(bytes.len()).map_err(|_| NbtError::StringTooLong(bytes.len()))?;
+
+        self.writer.write_all(&length.to_be_bytes())?;
+        self.writer.write_all(&bytes)?;
+        Ok(())
+    }
+
+    fn compound(&mut self, compound: &Compound) -> Result<(), NbtError> {
+        for (name, tag) in compound {
+            self.writer.write_all(&[tag.id()])?;
+            self.string(name)?;
+            self.payload(tag)?;
+        }
+
+        self.writer.write_all(&[0])?;
+        Ok(())
+    }
+
+    fn payload(&mut self, tag: &Tag) -> Result<(), NbtError> {
+        match tag {
+            Tag::Byte(value) => self.writer.write_all(&value.to_be_bytes())?,
+            Tag::Short(value) => self.writer.write_all(&value.to_be_bytes())?,
+            Tag::Int(value) => self.writer.write_all(&value.to_be_bytes())?,
+            Tag::Long(value) => self.writer.write_all(&value.to_be_bytes())?,
+            Tag::Float(value) => self.writer.write_all(&value.to_be_bytes())?,
+            Tag::Double(value) => self.writer.write_all(&value.to_be_bytes())?,
+            Tag::ByteArray(values) => {
+                self.length(values.len())?;
+                for value in values {
+                    self.writer.write_all(&value.to_be_bytes())?;
+                }
+            }
+            Tag::String(value) => self.string(value)?,
+            Tag::List(values) => {
+                let element_id = values.first().map_or(0, Tag::id);
+                if values.iter().any(|value| value.id() != element_id) {
+                    return Err(NbtError::MixedList);
+                }
+
+                self.writer.write_all(&[element_id])?;
+                self.length(values.len())?;
+                for value in values {
+                    self.payload(value)?;
+                }
+            }
+            Tag::Compound(compound) => self.compound(compound)?,
+            Tag::IntArray(values) => {
+                self.length(values.len())?;
+                for value in values {
+                    self.writer.write_all(&value.to_be_bytes())?;
+                }
+            }
+            Tag::LongArray(values) => {
+                self.length(values.len())?;
+                for value in values {
+                    self.writer.write_all(&value.to_be_bytes())?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Decodes Java's modified UTF-8: `\0` takes two bytes and supplementary characters are surrogate pairs.
+fn decode_mutf8(bytes: &[u8]) -> Option<String> {
+    if let Ok(s) = std::str::from_utf8(bytes) {
+        return Some(s.to_owned());
+    }
+
+    let continuation = |index: usize| {
+        bytes
+            .get(index)
+            .filter(|byte| *byte & 0xC0 == 0x80)
+            .map(|byte| u16::from(byte & 0x3F))
+    };
+
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+
+    while let Some(&byte) = bytes.get(index) {
+        let (unit, length) = match byte {
+            0x00..=0x7F => (u16::from(byte), 1),
+            0xC0..=0xDF => (u16::from(byte & 0x1F) << 6 | continuation(index + 1)?, 2),
+            0xE0..=0xEF => (
+                u16::from(byte & 0x0F) << 12
+                    | continuation(index + 1)? << 6
+                    | continuation(index + 2)?,
+                3,
+            ),
+            _ => return None,
+        };
+
+        units.push(unit);
+        index += length;
+    }
+
+    String::from_utf16(&units).ok()
+}
+
+fn encode_mutf8(value: &str) -> Vec<u8> {
+    if !value.chars().any(|c| c == '\0' || u32::from(c) > 0xFFFF) {
+        return value.as_bytes().to_vec();
+    }
+
+    let mut bytes = Vec::with_capacity(value.len() + 2);
+
+    for unit in value.encode_utf16() {
+        match unit {
+            0x01..=0x7F => bytes.push(unit as u8),
+            0x00 | 0x80..=0x7FF => {
+                bytes.push(0xC0 | (unit >> 6) as u8);
+                bytes.push(0x80 | (unit & 0x3F) as u8);
+            }
+            _ => {
+                bytes.push(0xE0 | (unit >> 12) as u8);
+                bytes.push(0x80 | ((unit >> 6) & 0x3F) as u8);
+                bytes.push(0x80 | (unit & 0x3F) as u8);
+            }
+        }
+    }
+
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hello_world_test() {
+        let mut bytes = vec![0x0a, 0x00, 0x0b];
+        bytes.extend(b"hello world");
+        bytes.extend([0x08, 0x00, 0x04]);
+        bytes.extend(b"name");
+        bytes.extend([0x00, 0x09]);
+        bytes.extend(b"Bananrama");
+        bytes.push(0x00);
+
+        let nbt = Nbt::from_bytes(&bytes).unwrap();
+        assert_eq!(nbt.name, "hello world");
+        assert_eq!(nbt.root["name"].as_str(), Some("Bananrama"));
+
+        assert_eq!(nbt.to_bytes().unwrap(), bytes);
+    }
+
+    #[test]
+    fn round_trip_test() {
+        let mut nested = Compound::new();
+        nested.insert("text".into(), "zero\0 and 😀".into());
+        nested.insert("ints".into(), Tag::IntArray(vec![1, -2, i32::MAX]));
+        nested.insert("longs".into(), Tag::LongArray(vec![i64::MIN, 0]));
+        nested.insert("bytes".into(), Tag::ByteArray(vec![-1, 0, 1]));
+
+        let mut root = Compound::new();
+        root.insert("byte".into(), Tag::Byte(-5));
+        root.insert("short".into(), Tag::Short(300));
+        root.insert("float".into(), Tag::Float(0.5));
+        root.insert("double".into(), Tag::Double(-1.25));
+        root.insert("empty".into(), Tag::List(vec![]));
+        root.insert(
+            "list".into(),
+            Tag::List(vec![nested.clone().into(), nested.into()]),
+        );
+
+        let nbt = Nbt::new(root);
+
+        assert_eq!(Nbt::from_bytes(&nbt.to_bytes().unwrap()).unwrap(), nbt);
+        assert_eq!(Nbt::from_bytes(&nbt.to_gzip_bytes().unwrap()).unwrap(), nbt);
+    }
+
+    #[test]
+    fn mutf8_test() {
+        assert_eq!(encode_mutf8("a\0b"), [b'a', 0xC0, 0x80, b'b']);
+        assert_eq!(encode_mutf8("😀"), [0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]);
+        assert_eq!(
+            decode_mutf8(&[0xED, 0xA0, 0xBD, 0xED, 0xB8, 0x80]).as_deref(),
+            Some("😀")
+        );
+        assert!(decode_mutf8(&[0xC0]).is_none());
+    }
+
+    #[test]
+    fn invalid_test() {
+        let mut root = Compound::new();
+        root.insert("mixed".into(), Tag::List(vec![Tag::Byte(1), Tag::Int(1)]));
+        assert!(matches!(
+            Nbt::new(root).to_bytes(),
+            Err(NbtError::MixedList)
+        ));
+
+        assert!(matches!(
+            Nbt::from_bytes(&[0x08, 0x00, 0x00]),
+            Err(NbtError::RootIsNotCompound(8))
+        ));
+        assert!(Nbt::from_bytes(&[
+            0x0a, 0x00, 0x00, 0x07, 0x00, 0x01, b'a', 0x7f, 0xff, 0xff, 0xff
+        ])
+        .is_err());
+    }
+}
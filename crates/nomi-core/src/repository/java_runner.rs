@@ -35,6 +35,41 @@ impl JavaRunner {
     pub fn command(s: &str) -> JavaRunner {
         JavaRunner::Command(s.to_string())
     }
+
+    /// Runs `java -version` and returns the major version of the runtime, e.g. `8` or `17`.
+    pub async fn major_version(&self) -> anyhow::Result<u32> {
+        let output = tokio::process::Command::new(self.get())
+            .arg("-version")
+            .output()
+            .await?;
+
+        // The version is printed to stderr
+        let output = String::from_utf8_lossy(&output.stderr);
+
+        parse_major_version(&output).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Cannot find the Java version in the output of `{} -version`",
+                self.get_string()
+            )
+        })
+    }
+}
+
+/// Parses the output of `java -version`.
+///
+/// `1.8.0_392` -> 8, `17.0.9` -> 17, `21` -> 21
+fn parse_major_version(output: &str) -> Option<u32> {
+    let (_, rest) = output.split_once("version \"")?;
+    let (version, _) = rest.split_once('"')?;
+
+    let mut parts = version.split(['.', '_', '-', '+']);
+    let major = parts.next()?;
+
+    if major == "1" {
+        parts.next()?.parse().ok()
+    } else {
+        major.parse().ok()
+    }
 }
 
 impl Default for JavaRunner {
@@ -42,3 +77,28 @@ impl Default for JavaRunner {
         JavaRunner::Command("java".to_string())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_major_version_test() {
+        let java_8 = r#"openjdk version "1.8.0_392"
+OpenJDK Runtime Environment (Temurin)(build 1.8.0_392-b08)"#;
+        let java_17 = r#"openjdk version "17.0.9" 2023-10-17
+OpenJDK Runtime Environment Temurin-17.0.9+9 (build 17.0.9+9)"#;
+
+        assert_eq!(parse_major_version(java_8), Some(8));
+        assert_eq!(parse_major_version(java_17), Some(17));
+        assert_eq!(
+            parse_major_version(r#"java version "21" 2023-09-19"#),
+            Some(21)
+        );
+        assert_eq!(
+            parse_major_version(r#"openjdk version "22-ea" 2024-03-19"#),
+            Some(22)
+        );
+        assert_eq!(parse_major_version("command not found"), None);
+    }
+}
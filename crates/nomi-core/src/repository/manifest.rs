@@ -1,4 +1,7 @@
-use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
@@ -87,6 +90,9 @@ pub enum Action {
 pub struct Features {
     pub is_demo_user: Option<bool>,
     pub has_custom_resolution: Option<bool>,
+    pub has_quick_plays_support: Option<bool>,
+    pub is_quick_play_singleplayer: Option<bool>,
+    pub is_quick_play_multiplayer: Option<bool>,
     pub is_quick_play_realms: Option<bool>,
 }
 
@@ -94,7 +100,46 @@ pub struct Features {
 pub struct Os {
     pub arch: Option<String>,
     pub name: Option<String>,
-    pub version: Option<String>,
+    pub version: Option<OsVersion>,
+}
+
+/// Pattern of the OS version, compiled once when the manifest is read.
+#[derive(Debug, Clone)]
+pub struct OsVersion {
+    pattern: String,
+    /// `None` if the pattern is not a valid regex.
+    regex: Option<Regex>,
+}
+
+impl OsVersion {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        let pattern = pattern.into();
+        let regex = Regex::new(&pattern).ok();
+        Self { pattern, regex }
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// An invalid pattern never matches.
+    pub fn is_match(&self, version: &str) -> bool {
+        self.regex
+            .as_ref()
+            .is_some_and(|regex| regex.is_match(version))
+    }
+}
+
+impl Serialize for OsVersion {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.pattern)
+    }
+}
+
+impl<'de> Deserialize<'de> for OsVersion {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::new)
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -136,10 +181,20 @@ pub struct JavaVersion {
 pub struct Library {
     pub downloads: LibraryDownloads,
     pub name: String,
-    // pub natives: Option<ManifestNatives>,
+    /// OS name to the classifier of the library's natives.
+    /// The classifier may contain `${arch}` which is replaced with `32` or `64`.
+    pub natives: Option<HashMap<String, String>>,
+    pub extract: Option<Extract>,
     pub rules: Option<Vec<Rule>>,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Extract {
+    /// Path prefixes inside the archive that must not be extracted.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct LibraryDownloads {
@@ -147,13 +202,8 @@ pub struct LibraryDownloads {
     pub classifiers: Option<Classifiers>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
-#[serde(rename_all = "kebab-case")]
-pub struct Classifiers {
-    pub natives_macos: Option<DownloadFile>,
-    pub natives_windows: Option<DownloadFile>,
-    pub natives_linux: Option<DownloadFile>,
-}
+/// Classifier (e.g. `natives-windows-64`) to its file.
+pub type Classifiers = HashMap<String, DownloadFile>;
 
 #[cfg(test)]
 mod tests {
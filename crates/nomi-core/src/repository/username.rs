@@ -1,6 +1,8 @@
+use md5::{Digest, Md5};
 use regex::Regex;
 use serde::{de::Visitor, Deserialize, Serialize};
 use thiserror::Error;
+use uuid::{Builder, Uuid};
 
 #[derive(Serialize, Debug, PartialEq, Clone)]
 pub struct Username(String);
@@ -74,6 +76,17 @@ impl Username {
     pub fn get(&self) -> &str {
         &self.0
     }
+
+    /// UUID of the player in offline mode.
+    ///
+    /// Derived the same way vanilla servers do it, so the player keeps the same UUID
+    /// (and the inventory in singleplayer worlds and on LAN servers) across launches.
+    pub fn offline_uuid(&self) -> Uuid {
+        let hash = Md5::digest(format!("OfflinePlayer:{}", self.0));
+
+        // Name-based UUID (version 3) without a namespace, see `UUID.nameUUIDFromBytes` in Java
+        Builder::from_md5_bytes(hash.into()).into_uuid()
+    }
 }
 
 #[cfg(test)]
@@ -92,6 +105,22 @@ mod tests {
         println!("{toml}");
     }
 
+    #[test]
+    fn offline_uuid_test() {
+        let notch = Username::new("Notch").unwrap().offline_uuid();
+        assert_eq!(notch.to_string(), "b50ad385-829d-3141-a216-7e7d7539ba7f");
+        assert_eq!(notch.get_version_num(), 3);
+
+        assert_eq!(
+            Username::default().offline_uuid(),
+            Username::new("Nomi").unwrap().offline_uuid()
+        );
+        assert_ne!(
+            Username::new("Nomi").unwrap().offline_uuid(),
+            Username::new("nomi").unwrap().offline_uuid()
+        );
+    }
+
     #[test]
     fn deserialize_test() {
         let s = "username = \"ssd\"";
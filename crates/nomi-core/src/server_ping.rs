@@ -0,0 +1,515 @@
+//! Server List Ping: the status the multiplayer menu shows for every server.
+
+use std::{
+    fmt::Display,
+    net::IpAddr,
+    time::{Duration, Instant, SystemTime},
+};
+
+use anyhow::{anyhow, bail, Context};
+use serde::Deserialize;
+use tokio::{
+    io::{AsyncRead, AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+use tracing::debug;
+
+pub mod legacy;
+pub mod srv;
+
+pub const DEFAULT_PORT: u16 = 25565;
+
+/// How long the whole ping may take.
+pub const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The server does not use the protocol version to answer a status request.
+const STATUS_PROTOCOL_VERSION: i32 = -1;
+
+/// Favicons are small but some servers put a lot into the description.
+const MAX_PACKET_LENGTH: usize = 2 * 1024 * 1024;
+
+/// `host[:port]` as written in the server list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerAddress {
+    pub host: String,
+    /// `None` if the address has no port. The SRV record or [`DEFAULT_PORT`] is used then.
+    pub port: Option<u16>,
+}
+
+impl ServerAddress {
+    pub fn parse(address: &str) -> anyhow::Result<Self> {
+        let address = address.trim();
+
+        // [::1]:25565
+        if let Some(rest) = address.strip_prefix('[') {
+            let (host, rest) = rest
+                .split_once(']')
+                .ok_or_else(|| anyhow!("Unterminated IPv6 address `{address}`"))?;
+
+            let port = match rest.strip_prefix(':') {
+                Some(port) => Some(parse_port(port)?),
+                None if rest.is_empty() => None,
+                None => bail!("Invalid server address `{address}`"),
+            };
+
+            return Ok(Self {
+                host: host.to_owned(),
+                port,
+            });
+        }
+
+        let (host, port) = match address.rsplit_once(':') {
+            // A bare IPv6 address
+            Some((host, _)) if host.contains(':') => (address, None),
+            Some((host, port)) => (host, Some(parse_port(port)?)),
+            None => (address, None),
+        };
+
+        if host.is_empty() {
+            bail!("The server address is empty");
+        }
+
+        Ok(Self {
+            host: host.to_owned(),
+            port,
+        })
+    }
+
+    /// Host and port to connect to, following the `_minecraft._tcp` SRV record if there is one.
+    pub async fn resolve(&self) -> (String, u16) {
+        if let Some(port) = self.port {
+            return (self.host.clone(), port);
+        }
+
+        if self.host.parse::<IpAddr>().is_err() {
+            match srv::lookup_minecraft(&self.host).await {
+                Ok(Some(record)) => return (record.target, record.port),
+                Ok(None) => {}
+                Err(e) => debug!("SRV lookup for {} failed. Error: {e}", self.host),
+            }
+        }
+
+        (self.host.clone(), DEFAULT_PORT)
+    }
+}
+
+impl Display for ServerAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+
+        match self.port {
+            Some(port) => write!(f, "{host}:{port}"),
+            None => f.write_str(&host),
+        }
+    }
+}
+
+fn parse_port(port: &str) -> anyhow::Result<u16> {
+    port.parse()
+        .with_context(|| format!("Invalid server port `{port}`"))
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlayerSample {
+    pub name: String,
+    pub id: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerStatus {
+    /// E.g. `1.20.4` or `Paper 1.20.4`.
+    pub version: String,
+    pub protocol: i32,
+    pub players_online: i64,
+    pub players_max: i64,
+    pub players_sample: Vec<PlayerSample>,
+    /// Description without the formatting codes.
+    pub motd: String,
+    /// `data:image/png;base64,...`
+    pub favicon: Option<String>,
+    pub latency: Duration,
+    /// The server only answered the pre-1.7 ping.
+    pub is_legacy: bool,
+}
+
+/// Pings the server, falling back to the legacy ping for servers older than 1.7.
+pub async fn ping(address: &str) -> anyhow::Result<ServerStatus> {
+    let address = ServerAddress::parse(address)?;
+
+    tokio::time::timeout(PING_TIMEOUT, async {
+        let (host, port) = address.resolve().await;
+
+        match ping_modern(&host, port).await {
+            Ok(status) => Ok(status),
+            Err(e) => {
+                debug!("Modern ping of {address} failed, trying the legacy one. Error: {e}");
+                legacy::ping_legacy(&host, port).await
+            }
+        }
+    })
+    .await
+    .map_err(|_| {
+        anyhow!(
+            "{address} did not respond in {} seconds",
+            PING_TIMEOUT.as_secs()
+        )
+    })?
+}
+
+/// Handshake, status request and ping of 1.7+ servers.
+pub async fn ping_modern(host: &str, port: u16) -> anyhow::Result<ServerStatus> {
+    let mut stream = TcpStream::connect((host, port)).await?;
+
+    let mut handshake = Vec::new();
+    write_var_int(&mut handshake, 0x00);
+    write_var_int(&mut handshake, STATUS_PROTOCOL_VERSION);
+    write_string(&mut handshake, host)?;
+    handshake.extend(port.to_be_bytes());
+    write_var_int(&mut handshake, 1);
+
+    send_packet(&mut stream, &handshake).await?;
+
+    let status_started = Instant::now();
+    send_packet(&mut stream, &[0x00]).await?;
+
+    let response = read_packet(&mut stream).await?;
+    let status_latency = status_started.elapsed();
+    let mut reader = response.as_slice();
+    if read_var_int_sync(&mut reader)? != 0x00 {
+        bail!("Unexpected status response packet");
+    }
+    let json = read_string(&mut reader)?;
+
+    let payload = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map_or(0, |time| {
+            i64::try_from(time.as_millis()).unwrap_or_default()
+        });
+    let started = Instant::now();
+    let mut ping = vec![0x01];
+    ping.extend(payload.to_be_bytes());
+    send_packet(&mut stream, &ping).await?;
+
+    // Some servers close the connection instead of answering the ping
+    let latency = match read_packet(&mut stream).await {
+        Ok(answer) if answer.first() == Some(&0x01) => started.elapsed(),
+        _ => {
+            debug!("{host}:{port} did not answer the ping, using the status response time");
+            status_latency
+        }
+    };
+
+    let response: StatusResponse = serde_json::from_str(&json)?;
+    Ok(response.into_status(latency))
+}
+
+#[derive(Deserialize)]
+struct StatusResponse {
+    #[serde(default)]
+    version: VersionResponse,
+    #[serde(default)]
+    players: PlayersResponse,
+    #[serde(default)]
+    description: serde_json::Value,
+    favicon: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct VersionResponse {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    protocol: i32,
+}
+
+#[derive(Deserialize, Default)]
+struct PlayersResponse {
+    #[serde(default)]
+    max: i64,
+    #[serde(default)]
+    online: i64,
+    #[serde(default)]
+    sample: Vec<SampleResponse>,
+}
+
+#[derive(Deserialize)]
+struct SampleResponse {
+    #[serde(default)]
+    name: String,
+    #[serde(default)]
+    id: String,
+}
+
+impl StatusResponse {
+    fn into_status(self, latency: Duration) -> ServerStatus {
+        let mut motd = String::new();
+        flatten_text(&self.description, &mut motd);
+
+        ServerStatus {
+            version: self.version.name,
+            protocol: self.version.protocol,
+            players_online: self.players.online,
+            players_max: self.players.max,
+            players_sample: self
+                .players
+                .sample
+                .into_iter()
+                .map(|sample| PlayerSample {
+                    name: sample.name,
+                    id: sample.id,
+                })
+                .collect(),
+            motd: strip_formatting(&motd),
+            favicon: self.favicon,
+            latency,
+            is_legacy: false,
+        }
+    }
+}
+
+/// Collects the text of a chat component.
+fn flatten_text(component: &serde_json::Value, text: &mut String) {
+    match component {
+        serde_json::Value::String(s) => text.push_str(s),
+        serde_json::Value::Array(components) => {
+            for component in components {
+                flatten_text(component, text);
+            }
+        }
+        serde_json::Value::Object(object) => {
+            if let Some(serde_json::Value::String(s)) = object.get("text") {
+                text.push_str(s);
+            }
+            if let Some(extra) = object.get("extra") {
+                flatten_text(extra, text);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Removes `§` formatting codes, e.g. `§aGreen` -> `Green`.
+pub fn strip_formatting(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '§' {
+            chars.next();
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
+
+async fn send_packet(stream: &mut TcpStream, packet: &[u8]) -> anyhow::Result<()> {
+    let mut data = Vec::with_capacity(packet.len() + 5);
+    write_var_int(&mut data, i32::try_from(packet.len())?);
+    data.extend(packet);
+
+    stream.write_all(&data).await?;
+    Ok(())
+}
+
+async fn read_packet(stream: &mut (impl AsyncRead + Unpin)) -> anyhow::Result<Vec<u8>> {
+    let length = usize::try_from(read_var_int(stream).await?)?;
+    if length > MAX_PACKET_LENGTH {
+        bail!("The packet is too large ({length} bytes)");
+    }
+
+    let mut packet = vec![0; length];
+    stream.read_exact(&mut packet).await?;
+    Ok(packet)
+}
+
+fn write_var_int(buf: &mut Vec<u8>, value: i32) {
+    let mut value = u32::from_ne_bytes(value.to_ne_bytes());
+
+    loop {
+        let byte = (value & 0x7F) as u8;
+        value >>= 7;
+
+        if value == 0 {
+            buf.push(byte);
+            return;
+        }
+
+        buf.push(byte | 0x80);
+    }
+}
+
+fn write_string(buf: &mut Vec<u8>, value: &str) -> anyhow::Result<()> {
+    write_var_int(buf, i32::try_from(value.len())?);
+    buf.extend(value.as_bytes());
+    Ok(())
+}
+
+fn decode_var_int(bytes: impl IntoIterator<Item = u8>) -> anyhow::Result<i32> {
+    let mut value = 0u32;
+
+    for (index, byte) in bytes.into_iter().take(5).enumerate() {
+        value |= u32::from(byte & 0x7F) << (7 * index);
+
+        if byte & 0x80 == 0 {
+            return Ok(i32::from_ne_bytes(value.to_ne_bytes()));
+        }
+    }
+
+    bail!("Invalid VarInt")
+}
+
+async fn read_var_int(stream: &mut (impl AsyncRead + Unpin)) -> anyhow::Result<i32> {
+    let mut bytes = Vec::with_capacity(5);
+
+    loop {
+        let byte = stream.read_u8().await?;
+        bytes.push(byte);
+
+        if byte & 0x80 == 0 || bytes.len() == 5 {
+            return decode_var_int(bytes);
+        }
+    }
+}
+
+fn read_var_int_sync(reader: &mut &[u8]) -> anyhow::Result<i32> {
+    let length = reader
+        .iter()
+        .position(|byte| byte & 0x80 == 0)
+        .map_or(reader.len(), |index| index + 1);
+
+    let (bytes, rest) = reader.split_at(length.min(5));
+    *reader = rest;
+
+    decode_var_int(bytes.iter().copied())
+}
+
+fn read_string(reader: &mut &[u8]) -> anyhow::Result<String> {
+    let length = usize::try_from(read_var_int_sync(reader)?)?;
+    if length > reader.len() {
+        bail!("The string is longer than the packet");
+    }
+
+    let (bytes, rest) = reader.split_at(length);
+    *reader = rest;
+
+    Ok(String::from_utf8(bytes.to_vec())?)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[test]
+    fn var_int_test() {
+        for (value, bytes) in [
+            (0, vec![0x00]),
+            (127, vec![0x7f]),
+            (300, vec![0xac, 0x02]),
+            (2_147_483_647, vec![0xff, 0xff, 0xff, 0xff, 0x07]),
+            (-1, vec![0xff, 0xff, 0xff, 0xff, 0x0f]),
+        ] {
+            let mut buf = Vec::new();
+            write_var_int(&mut buf, value);
+            assert_eq!(buf, bytes);
+            assert_eq!(read_var_int_sync(&mut buf.as_slice()).unwrap(), value);
+        }
+
+        assert!(read_var_int_sync(&mut [0xff; 6].as_slice()).is_err());
+    }
+
+    #[test]
+    fn address_test() {
+        let parse = |address| ServerAddress::parse(address).unwrap();
+
+        assert_eq!(
+            parse("mc.example.com"),
+            ServerAddress {
+                host: "mc.example.com".into(),
+                port: None
+            }
+        );
+        assert_eq!(parse("localhost:25566").port, Some(25566));
+        assert_eq!(parse("[::1]:25566").host, "::1");
+        assert_eq!(parse("::1").port, None);
+        assert_eq!(parse("[::1]:25566").to_string(), "[::1]:25566");
+        assert!(ServerAddress::parse("localhost:abc").is_err());
+        assert!(ServerAddress::parse("").is_err());
+    }
+
+    #[test]
+    fn motd_test() {
+        let description = serde_json::json!({
+            "text": "§aHello ",
+            "extra": [{ "text": "world" }, "!"]
+        });
+
+        let mut text = String::new();
+        flatten_text(&description, &mut text);
+        assert_eq!(strip_formatting(&text), "Hello world!");
+    }
+
+    /// Answers a single status request the way a vanilla server does.
+    async fn stand_in_server(listener: TcpListener, status: String) {
+        let (mut stream, _) = listener.accept().await.unwrap();
+
+        let handshake = read_packet(&mut stream).await.unwrap();
+        let mut reader = handshake.as_slice();
+        assert_eq!(read_var_int_sync(&mut reader).unwrap(), 0x00);
+        assert_eq!(
+            read_var_int_sync(&mut reader).unwrap(),
+            STATUS_PROTOCOL_VERSION
+        );
+        assert_eq!(read_string(&mut reader).unwrap(), "127.0.0.1");
+        assert_eq!(reader.last(), Some(&1));
+
+        assert_eq!(read_packet(&mut stream).await.unwrap(), [0x00]);
+
+        let mut response = vec![0x00];
+        write_string(&mut response, &status).unwrap();
+        send_packet(&mut stream, &response).await.unwrap();
+
+        let ping = read_packet(&mut stream).await.unwrap();
+        assert_eq!(ping[0], 0x01);
+        send_packet(&mut stream, &ping).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn ping_test() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let status = serde_json::json!({
+            "version": { "name": "1.20.4", "protocol": 765 },
+            "players": {
+                "max": 20,
+                "online": 1,
+                "sample": [{ "name": "Notch", "id": "069a79f4-44e9-4726-a5be-fca90e38aaf5" }]
+            },
+            "description": { "text": "§6Team server" },
+            "favicon": "data:image/png;base64,AAAA"
+        });
+        let server = tokio::spawn(stand_in_server(listener, status.to_string()));
+
+        let status = ping(&format!("127.0.0.1:{port}")).await.unwrap();
+        server.await.unwrap();
+
+        assert_eq!(status.version, "1.20.4");
+        assert_eq!(status.protocol, 765);
+        assert_eq!((status.players_online, status.players_max), (1, 20));
+        assert_eq!(status.players_sample[0].name, "Notch");
+        assert_eq!(status.motd, "Team server");
+        assert_eq!(
+            status.favicon.as_deref(),
+            Some("data:image/png;base64,AAAA")
+        );
+        assert!(!status.is_legacy);
+    }
+}
@@ -0,0 +1,132 @@
+use std::time::Instant;
+
+use anyhow::bail;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+};
+
+use super::{strip_formatting, ServerStatus};
+
+/// `0xFE 0x01`: understood by 1.4+ servers, older ones ignore the second byte.
+const LEGACY_PING: [u8; 2] = [0xFE, 0x01];
+const KICK_PACKET: u8 = 0xFF;
+/// Prefix of the 1.4+ response.
+const RESPONSE_PREFIX: &str = "§1\0";
+
+/// The `0xFE` ping of servers older than 1.7.
+pub async fn ping_legacy(host: &str, port: u16) -> anyhow::Result<ServerStatus> {
+    let mut stream = TcpStream::connect((host, port)).await?;
+
+    let started = Instant::now();
+    stream.write_all(&LEGACY_PING).await?;
+
+    if stream.read_u8().await? != KICK_PACKET {
+        bail!("Unexpected legacy ping response");
+    }
+    let latency = started.elapsed();
+
+    let length = stream.read_u16().await?;
+    let mut units = Vec::with_capacity(length.into());
+    for _ in 0..length {
+        units.push(stream.read_u16().await?);
+    }
+
+    let mut status = parse_legacy_response(&String::from_utf16(&units)?)?;
+    status.latency = latency;
+    Ok(status)
+}
+
+/// Parses the kick message the server answers the legacy ping with.
+///
+/// 1.4+: `§1\0<protocol>\0<version>\0<motd>\0<online>\0<max>`, older: `<motd>§<online>§<max>`.
+pub fn parse_legacy_response(response: &str) -> anyhow::Result<ServerStatus> {
+    let mut status = ServerStatus {
+        version: String::new(),
+        protocol: 0,
+        players_online: 0,
+        players_max: 0,
+        players_sample: Vec::new(),
+        motd: String::new(),
+        favicon: None,
+        latency: std::time::Duration::ZERO,
+        is_legacy: true,
+    };
+
+    if let Some(fields) = response.strip_prefix(RESPONSE_PREFIX) {
+        let fields = fields.split('\0').collect::<Vec<_>>();
+        let [protocol, version, motd, online, max] = fields.as_slice() else {
+            bail!("Invalid legacy ping response");
+        };
+
+        status.protocol = protocol.parse()?;
+        status.version = String::from(*version);
+        status.motd = strip_formatting(motd);
+        status.players_online = online.parse()?;
+        status.players_max = max.parse()?;
+    } else {
+        let mut fields = response.rsplitn(3, '§');
+        let (Some(max), Some(online), Some(motd)) = (fields.next(), fields.next(), fields.next())
+        else {
+            bail!("Invalid legacy ping response");
+        };
+
+        status.motd = strip_formatting(motd);
+        status.players_online = online.parse()?;
+        status.players_max = max.parse()?;
+    }
+
+    Ok(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+
+    use super::*;
+
+    #[test]
+    fn parse_test() {
+        let status =
+            parse_legacy_response("§1\u{0}127\u{0}1.6.4\u{0}§aOld server\u{0}3\u{0}10").unwrap();
+        assert_eq!(status.protocol, 127);
+        assert_eq!(status.version, "1.6.4");
+        assert_eq!(status.motd, "Old server");
+        assert_eq!((status.players_online, status.players_max), (3, 10));
+
+        let status = parse_legacy_response("Beta server§0§20").unwrap();
+        assert_eq!(status.motd, "Beta server");
+        assert_eq!(status.players_max, 20);
+
+        assert!(parse_legacy_response("nonsense").is_err());
+    }
+
+    #[tokio::test]
+    async fn ping_test() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        let server = tokio::spawn(async move {
+            let (mut stream, _) = listener.accept().await.unwrap();
+
+            let mut request = [0; 2];
+            stream.read_exact(&mut request).await.unwrap();
+            assert_eq!(request, LEGACY_PING);
+
+            let units = "§1\u{0}78\u{0}1.5.2\u{0}Legacy\u{0}0\u{0}8"
+                .encode_utf16()
+                .collect::<Vec<_>>();
+            let mut response = vec![KICK_PACKET];
+            response.extend((units.len() as u16).to_be_bytes());
+            response.extend(units.iter().flat_map(|unit| unit.to_be_bytes()));
+            stream.write_all(&response).await.unwrap();
+        });
+
+        let status = ping_legacy("127.0.0.1", port).await.unwrap();
+        server.await.unwrap();
+
+        assert!(status.is_legacy);
+        assert_eq!(status.version, "1.5.2");
+        assert_eq!(status.players_max, 8);
+    }
+}
@@ -0,0 +1,82 @@
+//! Looks up the SRV record of a Minecraft server with the system resolver configuration.
+
+use std::time::Duration;
+
+use anyhow::anyhow;
+use hickory_resolver::{error::ResolveErrorKind, TokioAsyncResolver};
+
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrvRecord {
+    pub priority: u16,
+    pub weight: u16,
+    pub port: u16,
+    /// Without the trailing dot.
+    pub target: String,
+}
+
+/// Looks up `_minecraft._tcp.<host>`.
+///
+/// Returns `Ok(None)` if the host has no record.
+pub async fn lookup_minecraft(host: &str) -> anyhow::Result<Option<SrvRecord>> {
+    // The trailing dot makes the name fully qualified so the search domains are not tried
+    let name = format!("_minecraft._tcp.{}.", host.trim_end_matches('.'));
+
+    let resolver = TokioAsyncResolver::tokio_from_system_conf()?;
+
+    let lookup = match tokio::time::timeout(LOOKUP_TIMEOUT, resolver.srv_lookup(name))
+        .await
+        .map_err(|_| anyhow!("The SRV lookup for {host} timed out"))?
+    {
+        Ok(lookup) => lookup,
+        Err(e) if matches!(e.kind(), ResolveErrorKind::NoRecordsFound { .. }) => return Ok(None),
+        Err(e) => return Err(e.into()),
+    };
+
+    let records = lookup
+        .iter()
+        .map(|srv| SrvRecord {
+            priority: srv.priority(),
+            weight: srv.weight(),
+            port: srv.port(),
+            target: srv.target().to_utf8().trim_end_matches('.').to_owned(),
+        })
+        .collect();
+
+    Ok(pick_record(records))
+}
+
+/// The record with the lowest priority and then the highest weight.
+pub fn pick_record(records: Vec<SrvRecord>) -> Option<SrvRecord> {
+    records
+        .into_iter()
+        .min_by_key(|record| (record.priority, std::cmp::Reverse(record.weight)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pick_record_test() {
+        let record = |priority, weight, port, target: &str| SrvRecord {
+            priority,
+            weight,
+            port,
+            target: target.to_owned(),
+        };
+
+        let records = vec![
+            record(20, 0, 25567, "backup.example.com"),
+            record(10, 1, 25568, "light.example.com"),
+            record(10, 5, 25566, "play.example.com"),
+        ];
+
+        let record = pick_record(records).unwrap();
+        assert_eq!(record.port, 25566);
+        assert_eq!(record.target, "play.example.com");
+
+        assert!(pick_record(Vec::new()).is_none());
+    }
+}
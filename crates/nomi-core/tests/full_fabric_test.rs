@@ -48,6 +48,12 @@ async fn full_fabric_test() {
         version_jar_file: mc_dir.join("versions/Full-fabric-test/1.19.4.jar"),
         version: "1.19.4".to_string(),
         version_type: nomi_core::repository::manifest::VersionType::Release,
+        memory: nomi_core::instance::launch::memory::MemoryMode::Manual,
+        window: None,
+        profile_name: None,
+        hooks: Default::default(),
+        use_argfile: false,
+        shared_folders: Default::default(),
     };
 
     let launch = instance.launch_instance(settings, None);
@@ -68,7 +74,7 @@ async fn full_fabric_test() {
         .build();
 
     dbg!(profile)
-        .launch(UserData::default(), &JavaRunner::default())
+        .launch(UserData::default(), &JavaRunner::default(), None)
         .await
         .unwrap();
 }
@@ -42,6 +42,12 @@ async fn vanilla_test() {
         version_jar_file: mc_dir.join("versions/1.20/1.20.jar"),
         version: "1.20".to_string(),
         version_type: nomi_core::repository::manifest::VersionType::Release,
+        memory: nomi_core::instance::launch::memory::MemoryMode::Manual,
+        window: None,
+        profile_name: None,
+        hooks: Default::default(),
+        use_argfile: false,
+        shared_folders: Default::default(),
     };
 
     // let l = builder.launch_instance(settings);